@@ -0,0 +1,12 @@
+//! Read-only HTTP API over the SQLite database `catflip-indexer` writes.
+//!
+//! Every response is computed from the indexed `bets` rows and the latest
+//! `vault_snapshots` row, so the service needs no RPC access and can be run
+//! against a copied database file.
+
+pub mod error;
+pub mod queries;
+pub mod routes;
+
+pub use error::ApiError;
+pub use routes::{router, AppState};
@@ -0,0 +1,43 @@
+use catflip_api::{router, AppState};
+use clap::Parser;
+use log::info;
+use rusqlite::{Connection, OpenFlags};
+use std::{net::SocketAddr, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "catflip-api",
+    version,
+    about = "Serve catflip bet history and stats from an indexer database"
+)]
+struct Args {
+    /// SQLite database written by catflip-indexer
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "CATFLIP_API_DB",
+        default_value = "catflip.db"
+    )]
+    db: PathBuf,
+
+    #[arg(long, env = "CATFLIP_API_LISTEN", default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Program whose vault snapshots `/vault` serves
+    #[arg(long, default_value_t = catflip::ID.to_string())]
+    program_id: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    let conn = Connection::open_with_flags(&args.db, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let app = router(AppState::new(conn, args.program_id));
+
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    info!("serving {} on {}", args.db.display(), args.listen);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use catflip_indexer::{Result, StatsFilter};
+use rusqlite::{named_params, Connection, Row};
+use serde::Serialize;
+
+/// A row of the indexer's `bets` table. `outcome` is `None` while open.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Bet {
+    pub bet_id: String,
+    pub program: String,
+    pub game: String,
+    pub player: String,
+    pub stake: u64,
+    pub outcome: Option<String>,
+    pub payout: u64,
+    pub placed_signature: Option<String>,
+    pub placed_at: Option<i64>,
+    pub resolved_signature: Option<String>,
+    pub resolved_at: Option<i64>,
+}
+
+impl Bet {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            bet_id: row.get("bet_id")?,
+            program: row.get("program")?,
+            game: row.get("game")?,
+            player: row.get("player")?,
+            stake: row.get("stake")?,
+            outcome: row.get("outcome")?,
+            payout: row.get("payout")?,
+            placed_signature: row.get("placed_signature")?,
+            placed_at: row.get("placed_at")?,
+            resolved_signature: row.get("resolved_signature")?,
+            resolved_at: row.get("resolved_at")?,
+        })
+    }
+}
+
+/// A player's bets, most recent first.
+pub fn history(conn: &Connection, player: &str, limit: u32, offset: u64) -> Result<Vec<Bet>> {
+    let mut stmt = conn.prepare(
+        "SELECT * FROM bets WHERE player = :player
+         ORDER BY COALESCE(resolved_at, placed_at) DESC, bet_id DESC
+         LIMIT :limit OFFSET :offset",
+    )?;
+    let rows = stmt.query_map(
+        named_params! { ":player": player, ":limit": limit, ":offset": offset },
+        Bet::from_row,
+    )?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Winning bets by net win (payout less stake), largest first.
+pub fn biggest_wins(conn: &Connection, filter: &StatsFilter, limit: u32) -> Result<Vec<Bet>> {
+    let mut stmt = conn.prepare(
+        "SELECT * FROM bets
+         WHERE outcome = 'won'
+           AND (:program IS NULL OR program = :program)
+           AND (:game IS NULL OR game = :game)
+           AND (:player IS NULL OR player = :player)
+           AND (:since IS NULL OR resolved_at >= :since)
+           AND (:until IS NULL OR resolved_at < :until)
+         ORDER BY payout - stake DESC, resolved_at DESC, bet_id
+         LIMIT :limit",
+    )?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":program": filter.program,
+            ":game": filter.game,
+            ":player": filter.player,
+            ":since": filter.since,
+            ":until": filter.until,
+            ":limit": limit,
+        },
+        Bet::from_row,
+    )?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
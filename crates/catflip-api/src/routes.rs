@@ -0,0 +1,165 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Json, Router,
+};
+use catflip_indexer::{stats, vault, GameStats, Stats, StatsFilter, VaultSnapshot, WindowStats};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    queries::{self, Bet},
+    ApiError,
+};
+
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 100;
+const DEFAULT_BUCKET: i64 = 3600;
+
+#[derive(Clone)]
+pub struct AppState {
+    conn: Arc<Mutex<Connection>>,
+    program_id: String,
+}
+
+impl AppState {
+    /// `program_id` selects which vault `/vault` reports.
+    pub fn new(conn: Connection, program_id: String) -> Self {
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+            program_id,
+        }
+    }
+
+    fn query<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> catflip_indexer::Result<T>,
+    ) -> Result<T, ApiError> {
+        let conn = self
+            .conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(f(&conn)?)
+    }
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/players/:player/bets", get(player_bets))
+        .route("/stats", get(global_stats))
+        .route("/stats/games", get(game_stats))
+        .route("/stats/series", get(series))
+        .route("/wins", get(biggest_wins))
+        .route("/vault", get(vault_state))
+        .with_state(state)
+}
+
+/// Query string accepted by the stats and wins endpoints. `since` and
+/// `until` are unix timestamps, `until` exclusive; `bucket` is the series
+/// window length in seconds.
+#[derive(Debug, Default, Deserialize)]
+pub struct WindowParams {
+    pub program: Option<String>,
+    pub game: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub bucket: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+impl WindowParams {
+    fn filter(&self) -> StatsFilter {
+        StatsFilter {
+            program: self.program.clone(),
+            game: self.game.clone(),
+            player: None,
+            since: self.since,
+            until: self.until,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageParams {
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BetPage {
+    pub bets: Vec<Bet>,
+    /// Offset of the next page, absent on the last one.
+    pub next_offset: Option<u64>,
+}
+
+async fn player_bets(
+    State(state): State<AppState>,
+    Path(player): Path<String>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<BetPage>, ApiError> {
+    let limit = limit(page.limit)?;
+    // Fetch one extra row to learn whether another page exists.
+    let mut bets = state.query(|conn| queries::history(conn, &player, limit + 1, page.offset))?;
+    let next_offset = (bets.len() > limit as usize).then(|| page.offset + limit as u64);
+    bets.truncate(limit as usize);
+    Ok(Json(BetPage { bets, next_offset }))
+}
+
+async fn global_stats(
+    State(state): State<AppState>,
+    Query(window): Query<WindowParams>,
+) -> Result<Json<Stats>, ApiError> {
+    Ok(Json(
+        state.query(|conn| stats::stats(conn, &window.filter()))?,
+    ))
+}
+
+async fn game_stats(
+    State(state): State<AppState>,
+    Query(window): Query<WindowParams>,
+) -> Result<Json<Vec<GameStats>>, ApiError> {
+    Ok(Json(
+        state.query(|conn| stats::by_game(conn, &window.filter()))?,
+    ))
+}
+
+async fn series(
+    State(state): State<AppState>,
+    Query(window): Query<WindowParams>,
+) -> Result<Json<Vec<WindowStats>>, ApiError> {
+    let bucket = window.bucket.unwrap_or(DEFAULT_BUCKET);
+    if bucket <= 0 {
+        return Err(ApiError::BadRequest("bucket must be positive".to_string()));
+    }
+    Ok(Json(state.query(|conn| {
+        stats::series(conn, &window.filter(), bucket)
+    })?))
+}
+
+async fn biggest_wins(
+    State(state): State<AppState>,
+    Query(window): Query<WindowParams>,
+) -> Result<Json<Vec<Bet>>, ApiError> {
+    let limit = limit(window.limit)?;
+    Ok(Json(state.query(|conn| {
+        queries::biggest_wins(conn, &window.filter(), limit)
+    })?))
+}
+
+async fn vault_state(State(state): State<AppState>) -> Result<Json<VaultSnapshot>, ApiError> {
+    state
+        .query(|conn| vault::latest(conn, &state.program_id))?
+        .map(Json)
+        .ok_or(ApiError::NotFound("vault snapshot"))
+}
+
+fn limit(requested: Option<u32>) -> Result<u32, ApiError> {
+    match requested.unwrap_or(DEFAULT_LIMIT) {
+        limit @ 1..=MAX_LIMIT => Ok(limit),
+        _ => Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {MAX_LIMIT}"
+        ))),
+    }
+}
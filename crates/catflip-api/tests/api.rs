@@ -0,0 +1,160 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use catflip_api::{router, AppState};
+use catflip_indexer::{source, Indexer, Programs, Store, VaultSnapshot};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use solana_sdk::pubkey;
+use std::{fs, path::Path};
+use tower::ServiceExt;
+
+const ALICE: &str = "GgBuCkRrrVhzSiPtLHz7mJ2Kn8u67gL82a3J2PBNM8RX";
+const BOB: &str = "3yyVBQSeSTMNuBQyzMi1QD5NLbasNFn5miaHMPAnHDzj";
+const T0: i64 = 1_760_000_000;
+
+/// The indexer's recorded fixtures, indexed into an in-memory database.
+fn app(with_vault: bool) -> Router {
+    let programs = Programs {
+        catflip: catflip::ID,
+        catflip_minimal: Some(pubkey!("2wCZy4ecVjYhFx9Wr5tQCv1AfWXm3s2XWFsoGrd73Htg")),
+    };
+    let mut indexer = Indexer::new(Store::open_in_memory().unwrap(), programs);
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../catflip-indexer/tests/fixtures");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    for path in paths {
+        for tx in source::load_fixture(&path).unwrap() {
+            indexer.ingest(&tx).unwrap();
+        }
+    }
+
+    if with_vault {
+        for (slot, lamports) in [(900, 5_000_000_000), (1_500, 4_842_000_000)] {
+            indexer
+                .store
+                .record_vault(&VaultSnapshot {
+                    program_id: catflip::ID.to_string(),
+                    slot,
+                    observed_at: T0 + slot as i64,
+                    address: "Vau1t11111111111111111111111111111111111111".to_string(),
+                    lamports,
+                    max_bet: lamports / 10,
+                    is_paused: false,
+                    min_bet_lamports: 1_000_000,
+                    max_exposure_bps: 1_000,
+                    house_edge_bps: 200,
+                    total_volume: 350_000_000,
+                    total_bets: 3,
+                    total_wins: 1,
+                })
+                .unwrap();
+        }
+    }
+
+    router(AppState::new(
+        indexer.store.into_connection(),
+        catflip::ID.to_string(),
+    ))
+}
+
+async fn get(app: Router, uri: &str) -> (StatusCode, Value) {
+    let response = app
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn player_history_pages_newest_first() {
+    let app = app(false);
+
+    let (status, page) = get(app.clone(), &format!("/players/{ALICE}/bets?limit=2")).await;
+    assert_eq!(status, StatusCode::OK);
+    let bets = page["bets"].as_array().unwrap();
+    assert_eq!(bets.len(), 2);
+    assert_eq!(bets[0]["program"], "catflip-minimal");
+    assert_eq!(bets[0]["outcome"], "won");
+    assert_eq!(bets[1]["outcome"], "lost");
+    assert_eq!(page["next_offset"], 2);
+
+    let (_, page) = get(
+        app.clone(),
+        &format!("/players/{ALICE}/bets?limit=2&offset=2"),
+    )
+    .await;
+    let bets = page["bets"].as_array().unwrap();
+    assert_eq!(bets.len(), 1);
+    assert_eq!(bets[0]["stake"], 100_000_000);
+    assert_eq!(page["next_offset"], Value::Null);
+
+    let (status, _) = get(app, &format!("/players/{BOB}/bets?limit=0")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn stats_over_windows_and_games() {
+    let app = app(false);
+
+    let (_, all) = get(app.clone(), "/stats").await;
+    assert_eq!(all["bets"], 5);
+    assert_eq!(all["volume"], 550_000_000);
+    assert_eq!(all["house_pnl"], 158_000_000);
+
+    // The catflip-minimal bets resolve an hour after the catflip ones.
+    let (_, early) = get(app.clone(), &format!("/stats?until={}", T0 + 3_600)).await;
+    assert_eq!(early["bets"], 3);
+    assert_eq!(early["refunded"], 1);
+    assert_eq!(early["volume"], 150_000_000);
+    assert_eq!(early["payouts"], 196_000_000);
+    assert_eq!(early["house_pnl"], -46_000_000);
+
+    let (_, games) = get(app.clone(), "/stats/games").await;
+    let games = games.as_array().unwrap();
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0]["program"], "catflip");
+    assert_eq!(games[0]["game"], "coinflip");
+    assert_eq!(games[1]["program"], "catflip-minimal");
+    assert_eq!(games[1]["volume"], 400_000_000);
+
+    let (_, series) = get(app.clone(), "/stats/series?bucket=3600").await;
+    let series = series.as_array().unwrap();
+    assert_eq!(series.len(), 2);
+    assert!(series[0]["start"].as_i64().unwrap() < series[1]["start"].as_i64().unwrap());
+    let total: u64 = series.iter().map(|w| w["bets"].as_u64().unwrap()).sum();
+    assert_eq!(total, 5);
+
+    let (status, _) = get(app, "/stats/series?bucket=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn biggest_wins_rank_by_net_win() {
+    let (status, wins) = get(app(false), "/wins?limit=5").await;
+    assert_eq!(status, StatusCode::OK);
+    let wins = wins.as_array().unwrap();
+    assert_eq!(wins.len(), 2);
+    assert!(wins.iter().all(|bet| bet["player"] == ALICE));
+    assert!(wins.iter().all(|bet| bet["outcome"] == "won"));
+}
+
+#[tokio::test]
+async fn vault_serves_latest_snapshot() {
+    let (status, _) = get(app(false), "/vault").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, vault) = get(app(true), "/vault").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(vault["slot"], 1_500);
+    assert_eq!(vault["lamports"], 4_842_000_000u64);
+    assert_eq!(vault["max_bet"], 484_200_000);
+}
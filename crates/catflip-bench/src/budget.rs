@@ -0,0 +1,113 @@
+use std::{collections::BTreeMap, fmt, path::Path};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::{report::ProgramReport, Program};
+
+/// Limits from a program's `budget.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct Budget {
+    pub binary: Option<BinaryBudget>,
+    /// Compute unit limit per instruction.
+    #[serde(default)]
+    pub compute_units: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BinaryBudget {
+    pub max_bytes: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Violation {
+    Binary {
+        program: Program,
+        bytes: u64,
+        max_bytes: u64,
+    },
+    ComputeUnits {
+        program: Program,
+        instruction: String,
+        units: u64,
+        max_units: u64,
+    },
+    /// Measured but missing from the budget, which would otherwise let a new
+    /// instruction grow unchecked.
+    Unbudgeted {
+        program: Program,
+        instruction: String,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Binary {
+                program,
+                bytes,
+                max_bytes,
+            } => write!(
+                f,
+                "{}: binary is {bytes} bytes, budget {max_bytes}",
+                program.name()
+            ),
+            Violation::ComputeUnits {
+                program,
+                instruction,
+                units,
+                max_units,
+            } => write!(
+                f,
+                "{}: {instruction} used {units} CU, budget {max_units}",
+                program.name()
+            ),
+            Violation::Unbudgeted {
+                program,
+                instruction,
+            } => write!(
+                f,
+                "{}: {instruction} has no compute unit budget",
+                program.name()
+            ),
+        }
+    }
+}
+
+impl Budget {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Everything in `report` that is over budget.
+    pub fn check(&self, report: &ProgramReport) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let (Some(bytes), Some(binary)) = (report.so_bytes, &self.binary) {
+            if bytes > binary.max_bytes {
+                violations.push(Violation::Binary {
+                    program: report.program,
+                    bytes,
+                    max_bytes: binary.max_bytes,
+                });
+            }
+        }
+        for (instruction, &units) in &report.compute_units {
+            match self.compute_units.get(instruction) {
+                Some(&max_units) if units > max_units => violations.push(Violation::ComputeUnits {
+                    program: report.program,
+                    instruction: instruction.clone(),
+                    units,
+                    max_units,
+                }),
+                Some(_) => {}
+                None => violations.push(Violation::Unbudgeted {
+                    program: report.program,
+                    instruction: instruction.clone(),
+                }),
+            }
+        }
+        violations
+    }
+}
@@ -0,0 +1,21 @@
+//! Compute unit and binary size benchmarks for catflip, catflip-minimal and
+//! catflip-ultra.
+//!
+//! The three programs play the same game with different trade-offs between
+//! features, compute and size. Each is loaded from its SBF build into an
+//! in-process bank and driven through a fixed scenario that records the
+//! compute units of every instruction, keeping the costliest of repeated
+//! ones, since wins and losses take different paths. The numbers are checked
+//! against `programs/<program>/budget.toml`.
+//!
+//! Builtin processors are not metered, so loading the programs as builtins
+//! only checks that the scenarios still run.
+
+pub mod budget;
+pub mod programs;
+pub mod report;
+mod scenarios;
+
+pub use budget::{Budget, Violation};
+pub use programs::{measure, measure_build, Loader, Program};
+pub use report::{ProgramReport, Report};
@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use catflip_bench::{measure_build, Budget, Program, Report};
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "catflip-bench",
+    version,
+    about = "Measure the programs' compute units and binary sizes against their budgets"
+)]
+struct Args {
+    /// Programs to measure; all of them by default
+    #[arg(long, value_enum, value_delimiter = ',')]
+    program: Vec<Program>,
+
+    /// Directory holding the SBF builds; `SBF_OUT_DIR`, or the workspace's
+    /// `target/deploy`, by default
+    #[arg(long, env = "SBF_OUT_DIR")]
+    so_dir: Option<PathBuf>,
+
+    /// Where to write the JSON report
+    #[arg(long, default_value = "target/bench/report.json")]
+    report: PathBuf,
+
+    /// Skip programs that haven't been built instead of failing, for local
+    /// runs on a partial build
+    #[arg(long)]
+    allow_missing: bool,
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let root = workspace_root();
+    let so_dir = args.so_dir.unwrap_or_else(|| root.join("target/deploy"));
+    let programs = if args.program.is_empty() {
+        Program::ALL.to_vec()
+    } else {
+        args.program
+    };
+
+    let mut report = Report::default();
+    let mut violations = Vec::new();
+    for program in programs {
+        let program_report = measure_build(program, &so_dir, args.allow_missing).await?;
+        let budget = Budget::load(&root.join(program.budget_path()))?;
+        violations.extend(budget.check(&program_report));
+        report.programs.push(program_report);
+    }
+
+    println!("{report}");
+    if let Some(dir) = args.report.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&args.report, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("writing {}", args.report.display()))?;
+
+    if !violations.is_empty() {
+        for violation in &violations {
+            eprintln!("{violation}");
+        }
+        bail!("{} over budget", violations.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{account::Account, bpf_loader, pubkey::Pubkey, rent::Rent};
+
+use crate::{
+    scenarios::{self, Bench},
+    ProgramReport,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Program {
+    Catflip,
+    CatflipMinimal,
+    CatflipUltra,
+}
+
+impl Program {
+    pub const ALL: [Program; 3] = [
+        Program::Catflip,
+        Program::CatflipMinimal,
+        Program::CatflipUltra,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Program::Catflip => "catflip",
+            Program::CatflipMinimal => "catflip-minimal",
+            Program::CatflipUltra => "catflip-ultra",
+        }
+    }
+
+    /// File name of the program's SBF build.
+    pub fn so_name(self) -> String {
+        format!("{}.so", self.name().replace('-', "_"))
+    }
+
+    /// Budget file, relative to the workspace root.
+    pub fn budget_path(self) -> PathBuf {
+        PathBuf::from("programs")
+            .join(self.name())
+            .join("budget.toml")
+    }
+
+    /// Address to load the program at. Anchor rejects any but catflip's
+    /// declared id; the native programs run anywhere.
+    fn program_id(self) -> Pubkey {
+        match self {
+            Program::Catflip => catflip::ID,
+            Program::CatflipMinimal | Program::CatflipUltra => Pubkey::new_unique(),
+        }
+    }
+}
+
+/// How to load a program into the bank.
+pub enum Loader {
+    /// The ELF of an SBF build, metered like on chain.
+    Sbf(Vec<u8>),
+    /// The program's processor compiled into this binary. Unmetered.
+    Builtin,
+}
+
+fn builtin_program_test(program: Program, program_id: Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    match program {
+        Program::Catflip => return catflip_client::testing::program_test(),
+        Program::CatflipMinimal => program_test.add_program(
+            "catflip_minimal",
+            program_id,
+            processor!(catflip_minimal::process_instruction),
+        ),
+        Program::CatflipUltra => program_test.add_program(
+            "catflip_ultra",
+            program_id,
+            processor!(catflip_ultra::process_instruction),
+        ),
+    }
+    program_test
+}
+
+fn program_test(program: Program, program_id: Pubkey, loader: Loader) -> ProgramTest {
+    let elf = match loader {
+        Loader::Sbf(elf) => elf,
+        Loader::Builtin => return builtin_program_test(program, program_id),
+    };
+
+    // What `ProgramTest::add_program` does with a `.so` it finds itself.
+    let mut program_test = ProgramTest::default();
+    program_test.add_account(
+        program_id,
+        Account {
+            lamports: Rent::default().minimum_balance(elf.len()).max(1),
+            data: elf,
+            owner: bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+    program_test
+}
+
+/// Runs `program`'s scenario, returning the compute units of each
+/// instruction by name.
+pub async fn measure(program: Program, loader: Loader) -> anyhow::Result<BTreeMap<String, u64>> {
+    let program_id = program.program_id();
+    let context = program_test(program, program_id, loader)
+        .start_with_context()
+        .await;
+    let mut bench = Bench::new(context, program_id);
+    match program {
+        Program::Catflip => scenarios::catflip(&mut bench).await?,
+        Program::CatflipMinimal => scenarios::catflip_minimal(&mut bench).await?,
+        Program::CatflipUltra => scenarios::catflip_ultra(&mut bench).await?,
+    }
+    Ok(bench.into_units())
+}
+
+/// Measures `program`'s SBF build in `so_dir`. A program that hasn't been
+/// built fails the run, so a CI job that lost a build can't pass by
+/// measuring nothing; `allow_missing` reports it as skipped instead.
+pub async fn measure_build(
+    program: Program,
+    so_dir: &Path,
+    allow_missing: bool,
+) -> anyhow::Result<ProgramReport> {
+    let so = so_dir.join(program.so_name());
+    let elf = match std::fs::read(&so) {
+        Ok(elf) => elf,
+        Err(_) if allow_missing => {
+            return Ok(ProgramReport {
+                program,
+                so_bytes: None,
+                compute_units: BTreeMap::new(),
+            })
+        }
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!(
+                    "reading {}; build it with `cargo build-sbf`, or pass --allow-missing",
+                    so.display()
+                )
+            })
+        }
+    };
+    Ok(ProgramReport {
+        program,
+        so_bytes: Some(elf.len() as u64),
+        compute_units: measure(program, Loader::Sbf(elf))
+            .await
+            .with_context(|| format!("measuring {}", program.name()))?,
+    })
+}
@@ -0,0 +1,45 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+
+use crate::Program;
+
+#[derive(Debug, Serialize)]
+pub struct ProgramReport {
+    pub program: Program,
+    /// Size of the SBF build; `None` if it wasn't built, in which case
+    /// nothing was measured.
+    pub so_bytes: Option<u64>,
+    /// Compute units by instruction.
+    pub compute_units: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub programs: Vec<ProgramReport>,
+}
+
+impl fmt::Display for ProgramReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(bytes) = self.so_bytes else {
+            return write!(f, "{}: not built, skipped", self.program.name());
+        };
+        writeln!(f, "{}: {bytes} bytes", self.program.name())?;
+        for (instruction, units) in &self.compute_units {
+            writeln!(f, "  {instruction:<20} {units:>8} CU")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, program) in self.programs.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{program}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,787 @@
+//! The instructions each program is measured on.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+use borsh::BorshSerialize;
+use catflip::{
+    instructions::{BetGuards, Randomness},
+    slot_hashes::{self, SlotHash},
+    state::{BatchPlan, BetRound, Challenge, PoolRound, Progression, Session, Side},
+};
+use catflip_client::{
+    beacons, instructions,
+    pda::{bet_round_address, challenge_address, pool_address},
+    state::decode,
+    vrf,
+};
+use catflip_minimal::{CatflipInstruction, Odds};
+use catflip_ultra::{
+    header, house_address, OP_BET, OP_COMMIT, OP_INIT_HOUSE, OP_RESOLVE, OP_WITHDRAW,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::Transaction,
+};
+
+const MIN_BET: u64 = LAMPORTS_PER_SOL / 1000;
+const STAKE: u64 = LAMPORTS_PER_SOL / 100;
+const BANKROLL: u64 = 10 * LAMPORTS_PER_SOL;
+const HOUSE_EDGE_BPS: u16 = 200;
+
+/// Bets placed per scenario, so that both outcomes are likely to be seen.
+const BETS: u64 = 8;
+
+pub(crate) struct Bench {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    units: BTreeMap<String, u64>,
+}
+
+impl Bench {
+    pub(crate) fn new(context: ProgramTestContext, program_id: Pubkey) -> Self {
+        Self {
+            context,
+            program_id,
+            units: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn into_units(self) -> BTreeMap<String, u64> {
+        self.units
+    }
+
+    fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    async fn slot(&mut self) -> anyhow::Result<u64> {
+        Ok(self.context.banks_client.get_sysvar::<Clock>().await?.slot)
+    }
+
+    fn warp_to_slot(&mut self, slot: u64) -> anyhow::Result<()> {
+        self.context
+            .warp_to_slot(slot)
+            .map_err(|err| anyhow!("warp to slot {slot}: {err:?}"))
+    }
+
+    async fn bet_round(&mut self, round: &Pubkey) -> anyhow::Result<BetRound> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*round)
+            .await?
+            .ok_or_else(|| anyhow!("bet round {round} was not created"))?;
+        Ok(decode(round, &account)?)
+    }
+
+    async fn challenge(&mut self, address: &Pubkey) -> anyhow::Result<Challenge> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await?
+            .ok_or_else(|| anyhow!("challenge {address} was not created"))?;
+        Ok(decode(address, &account)?)
+    }
+
+    /// The `SlotHashes` entry catflip uses for `target`, warping past it
+    /// first so it has been hashed.
+    async fn slot_hash(&mut self, target: u64) -> anyhow::Result<[u8; 32]> {
+        self.warp_to_slot(target + 1)?;
+        let account = self
+            .context
+            .banks_client
+            .get_account(sysvar::slot_hashes::ID)
+            .await?
+            .ok_or_else(|| anyhow!("the SlotHashes sysvar is missing"))?;
+        match slot_hashes::find(&account.data, target) {
+            SlotHash::Found(hash) => Ok(hash),
+            other => Err(anyhow!("no hash for slot {target}: {other:?}")),
+        }
+    }
+
+    async fn pool(&mut self, address: &Pubkey) -> anyhow::Result<PoolRound> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await?
+            .ok_or_else(|| anyhow!("pool round {address} was not created"))?;
+        Ok(decode(address, &account)?)
+    }
+
+    /// Sends `instruction` on its own, returning the compute units it used.
+    async fn send(&mut self, instruction: Instruction) -> anyhow::Result<u64> {
+        self.send_all(&[instruction]).await
+    }
+
+    /// Sends `instructions` in one transaction, returning the compute units
+    /// they used together.
+    async fn send_all(&mut self, instructions: &[Instruction]) -> anyhow::Result<u64> {
+        self.send_signed(instructions, &[]).await
+    }
+
+    /// [`Bench::send_all`] with `signers` signing next to the payer.
+    async fn send_signed(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> anyhow::Result<u64> {
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer()),
+            &all_signers,
+            blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction.clone())
+            .await?;
+        let details = simulation
+            .simulation_details
+            .ok_or_else(|| anyhow!("simulation returned no details"))?;
+        if let Some(Err(err)) = simulation.result {
+            bail!("{err}\n{}", details.logs.join("\n"));
+        }
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await?;
+        Ok(details.units_consumed)
+    }
+
+    /// Sends `instruction` and records its compute units under `name`,
+    /// keeping the highest if `name` is measured more than once.
+    async fn measure(&mut self, name: &str, instruction: Instruction) -> anyhow::Result<()> {
+        self.measure_all(name, &[instruction]).await
+    }
+
+    /// [`Bench::measure`] for a transaction of several instructions.
+    async fn measure_all(
+        &mut self,
+        name: &str,
+        instructions: &[Instruction],
+    ) -> anyhow::Result<()> {
+        self.measure_signed(name, instructions, &[]).await
+    }
+
+    /// [`Bench::measure_all`] with `signers` signing next to the payer.
+    async fn measure_signed(
+        &mut self,
+        name: &str,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> anyhow::Result<()> {
+        let units = self.send_signed(instructions, signers).await?;
+        let recorded = self.units.entry(name.to_owned()).or_default();
+        *recorded = (*recorded).max(units);
+        Ok(())
+    }
+}
+
+pub(crate) async fn catflip(bench: &mut Bench) -> anyhow::Result<()> {
+    let program_id = bench.program_id;
+    let payer = bench.payer();
+
+    bench
+        .measure(
+            "initialize",
+            instructions::initialize(&program_id, &payer, MIN_BET, 1000, HOUSE_EDGE_BPS),
+        )
+        .await?;
+    bench
+        .measure(
+            "fund_vault",
+            instructions::fund_vault(&program_id, &payer, BANKROLL),
+        )
+        .await?;
+    let vrf_key = Keypair::new();
+    bench
+        .measure(
+            "set_vrf_key",
+            instructions::set_vrf_key(&program_id, &payer, &vrf_key.pubkey()),
+        )
+        .await?;
+
+    // Bet rounds are keyed by slot, so every bet needs a slot of its own.
+    for _ in 0..BETS {
+        let slot = bench.slot().await?;
+        let (round, _) = bet_round_address(&program_id, &payer, slot);
+        // Every guard set, so the measurement covers all of the checks.
+        let guards = BetGuards {
+            min_payout: Some(STAKE),
+            max_edge_bps: Some(HOUSE_EDGE_BPS),
+            last_valid_slot: Some(slot),
+        };
+        let bet = instructions::bet(&program_id, &payer, slot, STAKE, vrf::player_seed(), guards);
+        bench.measure("bet", bet).await?;
+        let bet_round = bench.bet_round(&round).await?;
+        let randomness = vrf::prove_round(&vrf_key, &round, &bet_round);
+        bench
+            .measure(
+                "fulfill_randomness",
+                instructions::fulfill_randomness(&program_id, &round, &bet_round, randomness),
+            )
+            .await?;
+        bench.warp_to_slot(slot + 1)?;
+    }
+
+    // The longest batch, flat and as a stop-loss martingale, which leaves
+    // several stake levels for the bounds to track.
+    let plans = [
+        (Progression::Flat, None),
+        (Progression::Martingale, Some(31 * STAKE)),
+    ];
+    for (progression, stop_loss) in plans {
+        let slot = bench.slot().await?;
+        let (round, _) = bet_round_address(&program_id, &payer, slot);
+        let plan = BatchPlan {
+            base_stake: STAKE,
+            flips: BatchPlan::MAX_FLIPS,
+            progression,
+            stop_loss,
+            take_profit: None,
+        };
+        let place = instructions::place_batch(&program_id, &payer, slot, plan, vrf::player_seed());
+        bench.measure("place_batch", place).await?;
+        let bet_round = bench.bet_round(&round).await?;
+        let randomness = vrf::prove_round(&vrf_key, &round, &bet_round);
+        bench
+            .measure(
+                "fulfill_randomness_batch",
+                instructions::fulfill_randomness(&program_id, &round, &bet_round, randomness),
+            )
+            .await?;
+        bench.warp_to_slot(slot + 1)?;
+    }
+
+    // A streak that wins its opening bet, opens the box again, wins that
+    // step too and cashes out.
+    let slot = bench.slot().await?;
+    let (round, _) = bet_round_address(&program_id, &payer, slot);
+    let start = instructions::start_streak(
+        &program_id,
+        &payer,
+        slot,
+        STAKE,
+        vrf::winning_seed(&vrf_key, &round, 1),
+        BetGuards::default(),
+    );
+    bench.measure("start_streak", start).await?;
+    let bet_round = bench.bet_round(&round).await?;
+    let randomness = vrf::prove_round(&vrf_key, &round, &bet_round);
+    bench
+        .measure(
+            "fulfill_randomness",
+            instructions::fulfill_randomness(&program_id, &round, &bet_round, randomness),
+        )
+        .await?;
+    let again = instructions::continue_streak(
+        &program_id,
+        &payer,
+        &round,
+        vrf::winning_seed(&vrf_key, &round, 2),
+        BetGuards::default(),
+    );
+    bench.measure("continue_streak", again).await?;
+    let bet_round = bench.bet_round(&round).await?;
+    let randomness = vrf::prove_round(&vrf_key, &round, &bet_round);
+    bench
+        .measure(
+            "fulfill_randomness",
+            instructions::fulfill_randomness(&program_id, &round, &bet_round, randomness),
+        )
+        .await?;
+    bench
+        .measure(
+            "cash_out",
+            instructions::cash_out(&program_id, &payer, &round),
+        )
+        .await?;
+    bench.warp_to_slot(slot + 1)?;
+
+    // Challenges need a creator other than the payer, who matches them.
+    let creator = Keypair::new();
+    bench
+        .send(system_instruction::transfer(
+            &payer,
+            &creator.pubkey(),
+            LAMPORTS_PER_SOL,
+        ))
+        .await?;
+    bench
+        .measure("set_rake", instructions::set_rake(&program_id, &payer, 250))
+        .await?;
+    for settles in [true, false] {
+        let slot = bench.slot().await?;
+        let (address, _) = challenge_address(&program_id, &creator.pubkey(), slot);
+        let open = instructions::open_challenge(
+            &program_id,
+            &creator.pubkey(),
+            slot,
+            STAKE,
+            Side::Alive,
+            vrf::player_seed(),
+        );
+        bench
+            .measure_signed("open_challenge", &[open], &[&creator])
+            .await?;
+        let matched =
+            instructions::match_challenge(&program_id, &payer, &address, vrf::player_seed());
+        bench.measure("match_challenge", matched).await?;
+        let challenge = bench.challenge(&address).await?;
+        if settles {
+            let slot_hash = bench.slot_hash(challenge.entropy_slot()).await?;
+            let randomness = vrf::prove_challenge(&vrf_key, &address, &challenge, &slot_hash);
+            bench
+                .measure(
+                    "settle_challenge",
+                    instructions::settle_challenge(&program_id, &address, &challenge, randomness),
+                )
+                .await?;
+        } else {
+            bench.warp_to_slot(challenge.timeout_slot() + 1)?;
+            bench
+                .measure(
+                    "cancel_challenge",
+                    instructions::cancel_challenge(&program_id, &payer, &address, &challenge),
+                )
+                .await?;
+        }
+        let slot = bench.slot().await?;
+        bench.warp_to_slot(slot + 1)?;
+    }
+
+    // A contested pool round that resolves, with the challenge creator on
+    // the other side, and one that nobody resolves.
+    for (round_id, resolves) in [(1, true), (2, false)] {
+        bench
+            .measure(
+                "open_pool",
+                instructions::open_pool(&program_id, &payer, round_id, 2),
+            )
+            .await?;
+        let join = instructions::join_pool(
+            &program_id,
+            &payer,
+            round_id,
+            Side::Alive,
+            STAKE,
+            vrf::player_seed(),
+        );
+        bench.measure("join_pool", join).await?;
+        let join = instructions::join_pool(
+            &program_id,
+            &creator.pubkey(),
+            round_id,
+            Side::Dead,
+            STAKE,
+            vrf::player_seed(),
+        );
+        bench
+            .measure_signed("join_pool", &[join], &[&creator])
+            .await?;
+        let address = pool_address(&program_id, round_id).0;
+        let pool = bench.pool(&address).await?;
+        if resolves {
+            let slot_hash = bench.slot_hash(pool.entropy_slot()).await?;
+            let randomness = vrf::prove_pool(&vrf_key, &address, &pool, &slot_hash);
+            bench
+                .measure(
+                    "resolve_pool",
+                    instructions::resolve_pool(&program_id, &payer, round_id, randomness),
+                )
+                .await?;
+            // The second claim is the last and closes the round.
+            for player in [payer, creator.pubkey()] {
+                bench
+                    .measure(
+                        "claim_position",
+                        instructions::claim_position(&program_id, &payer, &player, round_id),
+                    )
+                    .await?;
+            }
+        } else {
+            bench.warp_to_slot(pool.timeout_slot() + 1)?;
+            for player in [payer, creator.pubkey()] {
+                bench
+                    .measure(
+                        "refund_position",
+                        instructions::refund_position(&program_id, &payer, &player, round_id),
+                    )
+                    .await?;
+            }
+        }
+    }
+
+    // The same again, staked from a player balance and settled by two of
+    // three oracles.
+    bench
+        .measure(
+            "deposit",
+            instructions::deposit(&program_id, &payer, (BETS + 2) * STAKE),
+        )
+        .await?;
+    let oracles: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let oracle_keys: Vec<Pubkey> = oracles.iter().map(Keypair::pubkey).collect();
+    bench
+        .measure(
+            "set_oracles",
+            instructions::set_oracles(&program_id, &payer, &oracle_keys, 2),
+        )
+        .await?;
+    for _ in 0..BETS {
+        let slot = bench.slot().await?;
+        let (round, _) = bet_round_address(&program_id, &payer, slot);
+        let bet = instructions::bet_from_balance(
+            &program_id,
+            &payer,
+            slot,
+            STAKE,
+            vrf::player_seed(),
+            BetGuards::default(),
+        );
+        bench.measure("bet_from_balance", bet).await?;
+        let bet_round = bench.bet_round(&round).await?;
+        let alpha = bet_round.vrf_input(&round);
+        let signed: Vec<_> = oracles[..2]
+            .iter()
+            .map(|oracle| beacons::sign(oracle, &alpha))
+            .collect();
+        let settle = [
+            beacons::instruction(&alpha, &signed),
+            instructions::fulfill_randomness(&program_id, &round, &bet_round, Randomness::Oracles),
+        ];
+        bench
+            .measure_all("fulfill_randomness_oracles", &settle)
+            .await?;
+        bench.warp_to_slot(slot + 1)?;
+    }
+    bench
+        .measure(
+            "withdraw_balance",
+            instructions::withdraw_balance(&program_id, &payer, STAKE),
+        )
+        .await?;
+
+    // The payer is its own session key, which keeps every transaction
+    // single-signer. One bet from the session's wallet funds and one from
+    // the balance.
+    let expires_slot = bench.slot().await? + 100;
+    bench
+        .measure(
+            "create_session",
+            instructions::create_session(
+                &program_id,
+                &payer,
+                &payer,
+                expires_slot,
+                2 * STAKE,
+                Session::ALL,
+            ),
+        )
+        .await?;
+    for from_balance in [false, true] {
+        let slot = bench.slot().await?;
+        let bet = instructions::session_bet(
+            &program_id,
+            &payer,
+            &payer,
+            slot,
+            STAKE,
+            vrf::player_seed(),
+            BetGuards::default(),
+            from_balance,
+        );
+        bench.measure("session_bet", bet).await?;
+        bench.warp_to_slot(slot + 1)?;
+    }
+    bench
+        .measure(
+            "revoke_session",
+            instructions::revoke_session(&program_id, &payer, &payer),
+        )
+        .await?;
+
+    let slot = bench.slot().await?;
+    let (round, _) = bet_round_address(&program_id, &payer, slot);
+    bench
+        .send(instructions::bet(
+            &program_id,
+            &payer,
+            slot,
+            STAKE,
+            vrf::player_seed(),
+            BetGuards::default(),
+        ))
+        .await?;
+    let bet_round = bench.bet_round(&round).await?;
+    bench.warp_to_slot(slot + BetRound::TIMEOUT_SLOTS + 1)?;
+    bench
+        .measure(
+            "refund_timeout",
+            instructions::refund_timeout(&program_id, &round, &bet_round),
+        )
+        .await?;
+
+    bench
+        .measure(
+            "migrate_in",
+            instructions::migrate_in(
+                &program_id,
+                &payer,
+                &Pubkey::new_unique(),
+                LAMPORTS_PER_SOL,
+                1_000,
+                BANKROLL,
+            ),
+        )
+        .await?;
+
+    bench
+        .measure(
+            "set_limits",
+            instructions::set_limits(&program_id, &payer, MIN_BET, 500),
+        )
+        .await?;
+    bench
+        .measure("set_edge", instructions::set_edge(&program_id, &payer, 300))
+        .await?;
+    bench
+        .measure("quote_bet", instructions::quote_bet(&program_id, STAKE))
+        .await?;
+    bench
+        .measure("vault_status", instructions::vault_status(&program_id))
+        .await?;
+    bench
+        .measure(
+            "set_pause",
+            instructions::set_pause(&program_id, &payer, true),
+        )
+        .await?;
+    bench
+        .measure(
+            "withdraw",
+            instructions::withdraw(&program_id, &payer, LAMPORTS_PER_SOL),
+        )
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn catflip_minimal(bench: &mut Bench) -> anyhow::Result<()> {
+    let program_id = bench.program_id;
+    let payer = bench.payer();
+    let (vault, _) = catflip_minimal::vault_address(&program_id);
+    let instruction = |data: CatflipInstruction, extra: &[AccountMeta]| {
+        let mut accounts = vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(vault, false),
+        ];
+        accounts.extend_from_slice(extra);
+        Instruction::new_with_bytes(program_id, &data.try_to_vec().unwrap(), accounts)
+    };
+    let system = AccountMeta::new_readonly(system_program::ID, false);
+    let instructions = AccountMeta::new_readonly(sysvar::instructions::ID, false);
+
+    bench
+        .measure(
+            "initialize",
+            instruction(
+                CatflipInstruction::Initialize { min_bet: MIN_BET },
+                std::slice::from_ref(&system),
+            ),
+        )
+        .await?;
+    bench
+        .send(system_instruction::transfer(&payer, &vault, BANKROLL))
+        .await?;
+    bench
+        .measure(
+            "set_instant_bets",
+            instruction(CatflipInstruction::SetInstantBets { enabled: true }, &[]),
+        )
+        .await?;
+
+    // Identical transactions would be rejected as already processed, so the
+    // stakes differ by a lamport.
+    for amount in STAKE..STAKE + BETS {
+        let bet = instruction(
+            CatflipInstruction::Bet { amount },
+            &[system.clone(), instructions.clone()],
+        );
+        bench.measure("bet", bet).await?;
+    }
+
+    let commitment = |nonce| catflip_minimal::commitment_address(&program_id, &payer, nonce).0;
+    for nonce in 0..BETS {
+        let commit = instruction(
+            CatflipInstruction::CommitBet {
+                amount: STAKE,
+                nonce,
+            },
+            &[AccountMeta::new(commitment(nonce), false), system.clone()],
+        );
+        bench.measure("commit_bet", commit).await?;
+    }
+    let slot = bench.slot().await?;
+    bench.warp_to_slot(slot + catflip_minimal::RESOLVE_DELAY_SLOTS + 1)?;
+    for nonce in 0..BETS {
+        let resolve = instruction(
+            CatflipInstruction::ResolveBet,
+            &[
+                AccountMeta::new(commitment(nonce), false),
+                AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+            ],
+        );
+        bench.measure("resolve_bet", resolve).await?;
+    }
+
+    for (name, data) in [
+        (
+            "update_config",
+            CatflipInstruction::UpdateConfig {
+                min_bet: MIN_BET,
+                max_bet: 100 * MIN_BET,
+                odds: Odds::default(),
+            },
+        ),
+        (
+            "set_paused",
+            CatflipInstruction::SetPaused { is_paused: true },
+        ),
+        (
+            "withdraw",
+            CatflipInstruction::Withdraw {
+                amount: LAMPORTS_PER_SOL,
+            },
+        ),
+        ("close_vault", CatflipInstruction::CloseVault),
+    ] {
+        bench.measure(name, instruction(data, &[])).await?;
+    }
+
+    // Migrating also closes the vault, so it gets a paused vault of its own.
+    let initialize = CatflipInstruction::Initialize {
+        min_bet: MIN_BET + 1,
+    };
+    bench
+        .send(instruction(initialize, std::slice::from_ref(&system)))
+        .await?;
+    bench
+        .send(instruction(
+            CatflipInstruction::SetPaused { is_paused: true },
+            &[],
+        ))
+        .await?;
+    bench
+        .measure(
+            "migrate_out",
+            instruction(
+                CatflipInstruction::MigrateOut,
+                &[AccountMeta::new(Pubkey::new_unique(), false)],
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn catflip_ultra(bench: &mut Bench) -> anyhow::Result<()> {
+    let program_id = bench.program_id;
+    let payer = bench.payer();
+    let (house, _) = house_address(&program_id);
+    let with_amount = |opcode: u8, amount: u64| {
+        let mut data = vec![header(opcode)];
+        data.extend(amount.to_le_bytes());
+        data
+    };
+
+    bench
+        .measure(
+            "init_house",
+            Instruction::new_with_bytes(
+                program_id,
+                &[header(OP_INIT_HOUSE)],
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new(house, false),
+                    AccountMeta::new_readonly(system_program::ID, false),
+                ],
+            ),
+        )
+        .await?;
+    bench
+        .send(system_instruction::transfer(&payer, &house, BANKROLL))
+        .await?;
+
+    for amount in STAKE..STAKE + BETS {
+        let bet = Instruction::new_with_bytes(
+            program_id,
+            &with_amount(OP_BET, amount),
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(house, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        );
+        bench.measure("bet", bet).await?;
+    }
+
+    let commitment = |nonce| catflip_ultra::commitment_address(&program_id, &payer, nonce).0;
+    for nonce in 0..BETS {
+        let mut data = with_amount(OP_COMMIT, STAKE);
+        data.extend(nonce.to_le_bytes());
+        let commit = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(house, false),
+                AccountMeta::new(commitment(nonce), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        );
+        bench.measure("commit", commit).await?;
+    }
+    let slot = bench.slot().await?;
+    bench.warp_to_slot(slot + catflip_ultra::RESOLVE_DELAY_SLOTS + 1)?;
+    for nonce in 0..BETS {
+        let resolve = Instruction::new_with_bytes(
+            program_id,
+            &[header(OP_RESOLVE)],
+            vec![
+                AccountMeta::new(payer, false),
+                AccountMeta::new(house, false),
+                AccountMeta::new(commitment(nonce), false),
+                AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+            ],
+        );
+        bench.measure("resolve", resolve).await?;
+    }
+
+    bench
+        .measure(
+            "withdraw",
+            Instruction::new_with_bytes(
+                program_id,
+                &with_amount(OP_WITHDRAW, LAMPORTS_PER_SOL),
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new(house, false),
+                ],
+            ),
+        )
+        .await?;
+    Ok(())
+}
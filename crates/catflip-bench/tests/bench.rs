@@ -0,0 +1,87 @@
+use std::{collections::BTreeMap, path::Path};
+
+use catflip_bench::{measure, measure_build, Budget, Loader, Program, ProgramReport, Violation};
+
+fn budget(program: Program) -> Budget {
+    Budget::load(
+        &Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../..")
+            .join(program.budget_path()),
+    )
+    .unwrap()
+}
+
+// Builtins aren't metered, but running the scenarios keeps them in step with
+// the programs, and the budgets in step with the scenarios.
+#[tokio::test]
+async fn every_scenario_runs_and_is_budgeted() {
+    for program in Program::ALL {
+        let units = measure(program, Loader::Builtin).await.unwrap();
+        let budgeted: Vec<_> = budget(program).compute_units.into_keys().collect();
+        let measured: Vec<_> = units.into_keys().collect();
+        assert_eq!(measured, budgeted, "{}", program.name());
+    }
+}
+
+#[tokio::test]
+async fn missing_builds_fail_unless_allowed() {
+    let so_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/no-builds");
+    let err = measure_build(Program::CatflipUltra, &so_dir, false)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("catflip_ultra.so"), "{err:#}");
+
+    let skipped = measure_build(Program::CatflipUltra, &so_dir, true)
+        .await
+        .unwrap();
+    assert_eq!(skipped.so_bytes, None);
+    assert!(skipped.compute_units.is_empty());
+}
+
+#[test]
+fn budgets_flag_what_runs_over() {
+    let budget: Budget = toml::from_str(
+        r#"
+        [binary]
+        max_bytes = 1000
+
+        [compute_units]
+        bet = 5000
+        withdraw = 2000
+        "#,
+    )
+    .unwrap();
+    let report = |so_bytes, bet| ProgramReport {
+        program: Program::CatflipUltra,
+        so_bytes: Some(so_bytes),
+        compute_units: BTreeMap::from([("bet".to_owned(), bet), ("withdraw".to_owned(), 2000)]),
+    };
+
+    assert_eq!(budget.check(&report(1000, 5000)), []);
+    assert_eq!(
+        budget.check(&report(1001, 5001)),
+        [
+            Violation::Binary {
+                program: Program::CatflipUltra,
+                bytes: 1001,
+                max_bytes: 1000,
+            },
+            Violation::ComputeUnits {
+                program: Program::CatflipUltra,
+                instruction: "bet".to_owned(),
+                units: 5001,
+                max_units: 5000,
+            },
+        ]
+    );
+
+    let mut unbudgeted = report(1000, 5000);
+    unbudgeted.compute_units.insert("close".to_owned(), 1);
+    assert_eq!(
+        budget.check(&unbudgeted),
+        [Violation::Unbudgeted {
+            program: Program::CatflipUltra,
+            instruction: "close".to_owned(),
+        }]
+    );
+}
@@ -0,0 +1,346 @@
+use catflip::state::Session;
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_sdk::{native_token::sol_to_lamports, pubkey::Pubkey};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "catflip",
+    version,
+    about = "Operate the catflip vault and bet rounds"
+)]
+pub struct Cli {
+    /// Solana CLI config file to take the RPC URL and keypair from
+    #[arg(short = 'C', long, global = true, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[arg(short = 'u', long, global = true, value_name = "URL_OR_MONIKER")]
+    pub url: Option<String>,
+
+    /// Keypair that signs and pays for transactions
+    #[arg(short = 'k', long, global = true, value_name = "PATH")]
+    pub keypair: Option<String>,
+
+    #[arg(long, global = true, default_value_t = catflip::ID)]
+    pub program_id: Pubkey,
+
+    /// Simulate the transaction and print the logs instead of sending it
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Display)]
+    pub output: OutputFormat,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Display,
+    Json,
+}
+
+/// What a session key may bet with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SessionPermission {
+    /// The player's wallet; the session holds its maximum stake from the
+    /// wallet up front
+    Bet,
+    /// The player's balance
+    BetFromBalance,
+}
+
+impl SessionPermission {
+    pub fn bits(permissions: &[Self]) -> u8 {
+        permissions.iter().fold(0, |bits, permission| {
+            bits | match permission {
+                Self::Bet => Session::BET,
+                Self::BetFromBalance => Session::BET_FROM_BALANCE,
+            }
+        })
+    }
+}
+
+/// How a batch sizes each flip's stake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Progression {
+    /// Every flip stakes the same
+    Flat,
+    /// Double after a loss, back to the first stake after a win
+    Martingale,
+    /// Double after a win, back to the first stake after a loss
+    AntiMartingale,
+}
+
+impl From<Progression> for catflip::state::Progression {
+    fn from(progression: Progression) -> Self {
+        match progression {
+            Progression::Flat => Self::Flat,
+            Progression::Martingale => Self::Martingale,
+            Progression::AntiMartingale => Self::AntiMartingale,
+        }
+    }
+}
+
+/// A side of the box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Side {
+    Alive,
+    Dead,
+}
+
+impl From<Side> for catflip::state::Side {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Alive => Self::Alive,
+            Side::Dead => Self::Dead,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Create the vault PDA with the signer as authority
+    Init {
+        #[arg(long, value_name = "SOL", value_parser = parse_sol, default_value = "0.001")]
+        min_bet: u64,
+        #[arg(long, default_value_t = 1000)]
+        max_exposure_bps: u16,
+        #[arg(long, default_value_t = 200)]
+        house_edge_bps: u16,
+    },
+    /// Add liquidity to the vault
+    Fund {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+    },
+    /// Take liquidity out of the vault (authority only)
+    Withdraw {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+    },
+    /// Move a legacy house's funds into the vault and carry over its lifetime
+    /// stats, once per house (authority only)
+    MigrateIn {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+        /// The legacy house's vault (catflip-minimal) or house PDA
+        /// (catflip-ultra), which marks it as imported
+        #[arg(long)]
+        legacy_house: Pubkey,
+        /// Bets the legacy house has taken
+        #[arg(long, default_value_t = 0)]
+        total_bets: u64,
+        /// Lamports the legacy house has taken in bets
+        #[arg(long, default_value_t = 0)]
+        total_volume: u64,
+    },
+    /// Register the public key of the house's VRF keypair; bets settle with
+    /// proofs from that keypair (authority only)
+    SetVrfKey { key: Pubkey },
+    /// Settle bets from oracle beacons: the first THRESHOLD of the listed
+    /// oracles sign each round, the rest are standbys. An empty list goes
+    /// back to the VRF key (authority only)
+    SetOracles {
+        /// Oracle signatures a round needs
+        #[arg(long, default_value_t = 0)]
+        threshold: u8,
+        oracles: Vec<Pubkey>,
+    },
+    /// Reject new bets
+    Pause,
+    /// Accept bets again
+    Resume,
+    /// Change betting limits; omitted values keep their current setting
+    UpdateConfig {
+        #[arg(long, value_name = "SOL", value_parser = parse_sol)]
+        min_bet: Option<u64>,
+        #[arg(long)]
+        max_exposure_bps: Option<u16>,
+        #[arg(long)]
+        house_edge_bps: Option<u16>,
+        /// Cut of each settled challenge's pot the vault takes
+        #[arg(long)]
+        rake_bps: Option<u16>,
+    },
+    /// Place a bet from the signer's wallet
+    Bet {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+        /// Fail instead of paying out less than this on a win
+        #[arg(long, value_name = "SOL", value_parser = parse_sol)]
+        min_payout: Option<u64>,
+        /// Fail if the house edge has been raised above this
+        #[arg(long)]
+        max_edge_bps: Option<u16>,
+        /// Fail if the bet lands after this slot
+        #[arg(long, value_name = "SLOT")]
+        last_valid_slot: Option<u64>,
+        /// Stake from the signer's balance instead of their wallet; a win is
+        /// paid into the balance
+        #[arg(long)]
+        from_balance: bool,
+        /// Bet for PLAYER, with the signer as their session key
+        #[arg(long, value_name = "PLAYER")]
+        on_behalf_of: Option<Pubkey>,
+    },
+    /// Place up to FLIPS flips as one bet from the signer's wallet. The most
+    /// the sequence can lose is taken up front and the rest returned when it
+    /// settles
+    Batch {
+        /// Stake of the first flip
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        stake: u64,
+        #[arg(long, default_value_t = 10)]
+        flips: u8,
+        #[arg(long, value_enum, default_value_t = Progression::Flat)]
+        progression: Progression,
+        /// Skip any flip that, lost, would put the sequence more than this
+        /// far behind
+        #[arg(long, value_name = "SOL", value_parser = parse_sol)]
+        stop_loss: Option<u64>,
+        /// Stop once the sequence is at least this far ahead
+        #[arg(long, value_name = "SOL", value_parser = parse_sol)]
+        take_profit: Option<u64>,
+    },
+    /// Open a double-or-nothing streak with a bet from the signer's wallet. A
+    /// win stays in the box for `open-again` or `cash-out`
+    Streak {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+    },
+    /// Stake everything a won streak step at ROUND paid on the next step
+    OpenAgain {
+        round: Pubkey,
+        /// Fail if the house edge has been raised above this
+        #[arg(long)]
+        max_edge_bps: Option<u16>,
+    },
+    /// Take the payout of a won streak step at ROUND and end the streak
+    CashOut { round: Pubkey },
+    /// Open a challenge on SIDE for another player to match with an equal
+    /// stake; the winner takes both stakes less the vault's rake at opening
+    Challenge {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+        #[arg(long, value_enum)]
+        side: Side,
+    },
+    /// Take the other side of CHALLENGE, staking as much as its creator
+    MatchChallenge { challenge: Pubkey },
+    /// Settle a matched challenge with a VRF proof or oracle beacons
+    SettleChallenge {
+        challenge: Pubkey,
+        /// The VRF keypair registered with `set-vrf-key`
+        #[arg(long, value_name = "PATH", required_unless_present = "oracle_keypair")]
+        vrf_keypair: Option<String>,
+        /// An oracle keypair registered with `set-oracles`; repeat it as many
+        /// times as the vault's threshold
+        #[arg(long, value_name = "PATH", conflicts_with = "vrf_keypair")]
+        oracle_keypair: Vec<String>,
+    },
+    /// Return the stakes of an unmatched challenge (creator only), or of a
+    /// matched one that has passed its timeout
+    CancelChallenge { challenge: Pubkey },
+    /// Open pool round ROUND_ID, taking stakes on either side for
+    /// BETTING_SLOTS slots; its fee is the vault's rake (authority only)
+    OpenPool {
+        round_id: u64,
+        #[arg(long, value_name = "SLOTS", default_value_t = 150)]
+        betting_slots: u64,
+    },
+    /// Stake SOL on SIDE of pool round ROUND_ID
+    JoinPool {
+        round_id: u64,
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+        #[arg(long, value_enum)]
+        side: Side,
+    },
+    /// Resolve a pool round whose betting has closed, with a VRF proof or
+    /// oracle beacons
+    ResolvePool {
+        round_id: u64,
+        /// The VRF keypair registered with `set-vrf-key`
+        #[arg(long, value_name = "PATH", required_unless_present = "oracle_keypair")]
+        vrf_keypair: Option<String>,
+        /// An oracle keypair registered with `set-oracles`; repeat it as many
+        /// times as the vault's threshold
+        #[arg(long, value_name = "PATH", conflicts_with = "vrf_keypair")]
+        oracle_keypair: Vec<String>,
+    },
+    /// Pay out a position in a resolved pool round, the signer's by default
+    ClaimPool {
+        round_id: u64,
+        #[arg(long)]
+        player: Option<Pubkey>,
+    },
+    /// Return a position's stake from a pool round that was never resolved,
+    /// the signer's by default
+    RefundPool {
+        round_id: u64,
+        #[arg(long)]
+        player: Option<Pubkey>,
+    },
+    /// Print a pool round's phase and pools
+    Pool { round_id: u64 },
+    /// Let KEY bet for the signer without their signature until the session
+    /// expires or is revoked
+    CreateSession {
+        key: Pubkey,
+        /// Slots from now until the session expires
+        #[arg(long, value_name = "SLOTS", default_value_t = 9000)]
+        expires_in: u64,
+        /// Most the key may stake over the whole session
+        #[arg(long, value_name = "SOL", value_parser = parse_sol)]
+        max_stake: u64,
+        /// What the key may bet with; repeat for both
+        #[arg(long, value_enum, required = true)]
+        allow: Vec<SessionPermission>,
+    },
+    /// End the session of KEY, returning unused wallet stake to the signer
+    RevokeSession { key: Pubkey },
+    /// Move SOL from the signer's wallet into their player balance
+    Deposit {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+    },
+    /// Move SOL from the signer's player balance back to their wallet
+    WithdrawBalance {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+    },
+    /// Print a player's balance, the signer's by default
+    Balance { player: Option<Pubkey> },
+    /// Settle an open bet round with a VRF proof or oracle beacons
+    Settle {
+        round: Pubkey,
+        /// The VRF keypair registered with `set-vrf-key`
+        #[arg(long, value_name = "PATH", required_unless_present = "oracle_keypair")]
+        vrf_keypair: Option<String>,
+        /// An oracle keypair registered with `set-oracles`; repeat it as many
+        /// times as the vault's threshold
+        #[arg(long, value_name = "PATH", conflicts_with = "vrf_keypair")]
+        oracle_keypair: Vec<String>,
+    },
+    /// Ask the program what a bet of SOL would pay right now, without placing it
+    Quote {
+        #[arg(value_name = "SOL", value_parser = parse_sol)]
+        amount: u64,
+    },
+    /// Refund a bet round that has passed its timeout
+    Refund { round: Pubkey },
+    /// Print vault configuration, liquidity and lifetime stats
+    Stats,
+}
+
+fn parse_sol(value: &str) -> Result<u64, String> {
+    let sol: f64 = value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a SOL amount"))?;
+    if !sol.is_finite() || sol < 0.0 {
+        return Err(format!("`{value}` is not a SOL amount"));
+    }
+    Ok(sol_to_lamports(sol))
+}
@@ -0,0 +1,502 @@
+use anyhow::{bail, Result};
+use catflip::{
+    instructions::{BetGuards, Randomness},
+    state::BatchPlan,
+};
+use catflip_client::{
+    beacons,
+    config::read_keypair,
+    instructions,
+    pda::{bet_round_address, challenge_address, pool_address},
+    state::{
+        challenge_vrf_input, fetch_bet_round, fetch_challenge, fetch_player_balance, fetch_pool,
+        fetch_vault, pool_vrf_input,
+    },
+    views, vrf, Chain,
+};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::{
+    cli::{Command, SessionPermission},
+    output::{BalanceReport, PoolReport, Report, TransactionReport, VaultReport},
+};
+
+/// Everything a command needs to build, sign and submit transactions.
+pub struct Context<C> {
+    pub chain: C,
+    pub signer: Keypair,
+    pub program_id: Pubkey,
+    pub dry_run: bool,
+}
+
+impl<C: Chain> Context<C> {
+    async fn submit(
+        &self,
+        action: &'static str,
+        instructions: &[Instruction],
+    ) -> Result<TransactionReport> {
+        let blockhash = self.chain.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.signer.pubkey()),
+            &[&self.signer],
+            blockhash,
+        );
+
+        if self.dry_run {
+            let simulation = self.chain.simulate_transaction(&transaction).await?;
+            return Ok(TransactionReport::simulated(action, simulation));
+        }
+
+        let signature = self.chain.send_transaction(&transaction).await?;
+        Ok(TransactionReport::sent(action, signature))
+    }
+}
+
+pub async fn run<C: Chain>(ctx: &Context<C>, command: Command) -> Result<Report> {
+    let program_id = &ctx.program_id;
+    let signer = ctx.signer.pubkey();
+
+    let report = match command {
+        Command::Init {
+            min_bet,
+            max_exposure_bps,
+            house_edge_bps,
+        } => {
+            let ix = instructions::initialize(
+                program_id,
+                &signer,
+                min_bet,
+                max_exposure_bps,
+                house_edge_bps,
+            );
+            ctx.submit("initialize", &[ix]).await?
+        }
+        Command::Fund { amount } => {
+            let ix = instructions::fund_vault(program_id, &signer, amount);
+            ctx.submit("fund_vault", &[ix]).await?
+        }
+        Command::Withdraw { amount } => {
+            let ix = instructions::withdraw(program_id, &signer, amount);
+            ctx.submit("withdraw", &[ix]).await?
+        }
+        Command::MigrateIn {
+            amount,
+            legacy_house,
+            total_bets,
+            total_volume,
+        } => {
+            let ix = instructions::migrate_in(
+                program_id,
+                &signer,
+                &legacy_house,
+                amount,
+                total_bets,
+                total_volume,
+            );
+            ctx.submit("migrate_in", &[ix]).await?
+        }
+        Command::SetVrfKey { key } => {
+            let ix = instructions::set_vrf_key(program_id, &signer, &key);
+            ctx.submit("set_vrf_key", &[ix]).await?
+        }
+        Command::SetOracles { threshold, oracles } => {
+            let ix = instructions::set_oracles(program_id, &signer, &oracles, threshold);
+            ctx.submit("set_oracles", &[ix]).await?
+        }
+        Command::Pause => {
+            let ix = instructions::set_pause(program_id, &signer, true);
+            ctx.submit("pause", &[ix]).await?
+        }
+        Command::Resume => {
+            let ix = instructions::set_pause(program_id, &signer, false);
+            ctx.submit("resume", &[ix]).await?
+        }
+        Command::UpdateConfig {
+            min_bet,
+            max_exposure_bps,
+            house_edge_bps,
+            rake_bps,
+        } => {
+            let current = fetch_vault(&ctx.chain, program_id).await?.vault;
+            let mut ixs = Vec::new();
+            if min_bet.is_some() || max_exposure_bps.is_some() {
+                ixs.push(instructions::set_limits(
+                    program_id,
+                    &signer,
+                    min_bet.unwrap_or(current.min_bet_lamports),
+                    max_exposure_bps.unwrap_or(current.max_exposure_bps),
+                ));
+            }
+            if let Some(house_edge_bps) = house_edge_bps {
+                ixs.push(instructions::set_edge(program_id, &signer, house_edge_bps));
+            }
+            if let Some(rake_bps) = rake_bps {
+                ixs.push(instructions::set_rake(program_id, &signer, rake_bps));
+            }
+            if ixs.is_empty() {
+                bail!(
+                    "nothing to update, pass --min-bet, --max-exposure-bps, --house-edge-bps or --rake-bps"
+                );
+            }
+            ctx.submit("update_config", &ixs).await?
+        }
+        Command::Bet {
+            amount,
+            min_payout,
+            max_edge_bps,
+            last_valid_slot,
+            from_balance,
+            on_behalf_of,
+        } => {
+            let slot = ctx.chain.get_slot().await?;
+            let player = on_behalf_of.unwrap_or(signer);
+            let bet_round = bet_round_address(program_id, &player, slot).0;
+            let guards = BetGuards {
+                min_payout,
+                max_edge_bps,
+                last_valid_slot,
+            };
+            let ix = match on_behalf_of {
+                Some(player) => instructions::session_bet(
+                    program_id,
+                    &signer,
+                    &player,
+                    slot,
+                    amount,
+                    vrf::player_seed(),
+                    guards,
+                    from_balance,
+                ),
+                None if from_balance => instructions::bet_from_balance(
+                    program_id,
+                    &signer,
+                    slot,
+                    amount,
+                    vrf::player_seed(),
+                    guards,
+                ),
+                None => instructions::bet(
+                    program_id,
+                    &signer,
+                    slot,
+                    amount,
+                    vrf::player_seed(),
+                    guards,
+                ),
+            };
+            ctx.submit("bet", &[ix]).await?.with_bet_round(bet_round)
+        }
+        Command::Batch {
+            stake,
+            flips,
+            progression,
+            stop_loss,
+            take_profit,
+        } => {
+            let slot = ctx.chain.get_slot().await?;
+            let bet_round = bet_round_address(program_id, &signer, slot).0;
+            let plan = BatchPlan {
+                base_stake: stake,
+                flips,
+                progression: progression.into(),
+                stop_loss,
+                take_profit,
+            };
+            let ix = instructions::place_batch(program_id, &signer, slot, plan, vrf::player_seed());
+            ctx.submit("place_batch", &[ix])
+                .await?
+                .with_bet_round(bet_round)
+        }
+        Command::Streak { amount } => {
+            let slot = ctx.chain.get_slot().await?;
+            let bet_round = bet_round_address(program_id, &signer, slot).0;
+            let ix = instructions::start_streak(
+                program_id,
+                &signer,
+                slot,
+                amount,
+                vrf::player_seed(),
+                BetGuards::default(),
+            );
+            ctx.submit("start_streak", &[ix])
+                .await?
+                .with_bet_round(bet_round)
+        }
+        Command::OpenAgain {
+            round,
+            max_edge_bps,
+        } => {
+            let guards = BetGuards {
+                max_edge_bps,
+                ..BetGuards::default()
+            };
+            let ix = instructions::continue_streak(
+                program_id,
+                &signer,
+                &round,
+                vrf::player_seed(),
+                guards,
+            );
+            ctx.submit("continue_streak", &[ix])
+                .await?
+                .with_bet_round(round)
+        }
+        Command::CashOut { round } => {
+            let ix = instructions::cash_out(program_id, &signer, &round);
+            ctx.submit("cash_out", &[ix]).await?
+        }
+        Command::Challenge { amount, side } => {
+            let slot = ctx.chain.get_slot().await?;
+            let challenge = challenge_address(program_id, &signer, slot).0;
+            let ix = instructions::open_challenge(
+                program_id,
+                &signer,
+                slot,
+                amount,
+                side.into(),
+                vrf::player_seed(),
+            );
+            ctx.submit("open_challenge", &[ix])
+                .await?
+                .with_challenge(challenge)
+        }
+        Command::MatchChallenge { challenge } => {
+            let ix =
+                instructions::match_challenge(program_id, &signer, &challenge, vrf::player_seed());
+            ctx.submit("match_challenge", &[ix])
+                .await?
+                .with_challenge(challenge)
+        }
+        Command::SettleChallenge {
+            challenge: address,
+            vrf_keypair,
+            oracle_keypair,
+        } => {
+            let challenge = fetch_challenge(&ctx.chain, &address).await?;
+            // Without the entropy slot's hash the program refuses the
+            // settlement before checking any randomness, and says why: not
+            // matched, timed out or not ready yet.
+            let alpha = challenge_vrf_input(&ctx.chain, &address, &challenge)
+                .await?
+                .unwrap_or_else(|| challenge.vrf_input(&address, &[0; 32]));
+            let ixs = match vrf_keypair {
+                Some(path) => {
+                    let (output, proof) = vrf::prove(&read_keypair(&path)?, &alpha);
+                    vec![instructions::settle_challenge(
+                        program_id,
+                        &address,
+                        &challenge,
+                        Randomness::Vrf { output, proof },
+                    )]
+                }
+                None => {
+                    let mut signed = Vec::with_capacity(oracle_keypair.len());
+                    for path in &oracle_keypair {
+                        signed.push(beacons::sign(&read_keypair(path)?, &alpha));
+                    }
+                    vec![
+                        beacons::instruction(&alpha, &signed),
+                        instructions::settle_challenge(
+                            program_id,
+                            &address,
+                            &challenge,
+                            Randomness::Oracles,
+                        ),
+                    ]
+                }
+            };
+            ctx.submit("settle_challenge", &ixs)
+                .await?
+                .with_challenge(address)
+        }
+        Command::CancelChallenge { challenge: address } => {
+            let challenge = fetch_challenge(&ctx.chain, &address).await?;
+            let ix = instructions::cancel_challenge(program_id, &signer, &address, &challenge);
+            ctx.submit("cancel_challenge", &[ix])
+                .await?
+                .with_challenge(address)
+        }
+        Command::OpenPool {
+            round_id,
+            betting_slots,
+        } => {
+            let ix = instructions::open_pool(program_id, &signer, round_id, betting_slots);
+            ctx.submit("open_pool", &[ix])
+                .await?
+                .with_pool(pool_address(program_id, round_id).0)
+        }
+        Command::JoinPool {
+            round_id,
+            amount,
+            side,
+        } => {
+            let ix = instructions::join_pool(
+                program_id,
+                &signer,
+                round_id,
+                side.into(),
+                amount,
+                vrf::player_seed(),
+            );
+            ctx.submit("join_pool", &[ix])
+                .await?
+                .with_pool(pool_address(program_id, round_id).0)
+        }
+        Command::ResolvePool {
+            round_id,
+            vrf_keypair,
+            oracle_keypair,
+        } => {
+            let address = pool_address(program_id, round_id).0;
+            let pool = fetch_pool(&ctx.chain, program_id, round_id).await?;
+            let authority = fetch_vault(&ctx.chain, program_id).await?.vault.authority;
+            // As for challenges, the program says why it can't resolve yet.
+            let alpha = pool_vrf_input(&ctx.chain, &address, &pool)
+                .await?
+                .unwrap_or_else(|| pool.vrf_input(&address, &[0; 32]));
+            let ixs = match vrf_keypair {
+                Some(path) => {
+                    let (output, proof) = vrf::prove(&read_keypair(&path)?, &alpha);
+                    vec![instructions::resolve_pool(
+                        program_id,
+                        &authority,
+                        round_id,
+                        Randomness::Vrf { output, proof },
+                    )]
+                }
+                None => {
+                    let mut signed = Vec::with_capacity(oracle_keypair.len());
+                    for path in &oracle_keypair {
+                        signed.push(beacons::sign(&read_keypair(path)?, &alpha));
+                    }
+                    vec![
+                        beacons::instruction(&alpha, &signed),
+                        instructions::resolve_pool(
+                            program_id,
+                            &authority,
+                            round_id,
+                            Randomness::Oracles,
+                        ),
+                    ]
+                }
+            };
+            ctx.submit("resolve_pool", &ixs).await?.with_pool(address)
+        }
+        Command::ClaimPool { round_id, player } => {
+            let player = player.unwrap_or(signer);
+            let authority = fetch_vault(&ctx.chain, program_id).await?.vault.authority;
+            let ix = instructions::claim_position(program_id, &authority, &player, round_id);
+            ctx.submit("claim_position", &[ix])
+                .await?
+                .with_pool(pool_address(program_id, round_id).0)
+        }
+        Command::RefundPool { round_id, player } => {
+            let player = player.unwrap_or(signer);
+            let authority = fetch_vault(&ctx.chain, program_id).await?.vault.authority;
+            let ix = instructions::refund_position(program_id, &authority, &player, round_id);
+            ctx.submit("refund_position", &[ix])
+                .await?
+                .with_pool(pool_address(program_id, round_id).0)
+        }
+        Command::CreateSession {
+            key,
+            expires_in,
+            max_stake,
+            allow,
+        } => {
+            let expires_slot = ctx.chain.get_slot().await? + expires_in;
+            let ix = instructions::create_session(
+                program_id,
+                &signer,
+                &key,
+                expires_slot,
+                max_stake,
+                SessionPermission::bits(&allow),
+            );
+            ctx.submit("create_session", &[ix]).await?
+        }
+        Command::RevokeSession { key } => {
+            let ix = instructions::revoke_session(program_id, &signer, &key);
+            ctx.submit("revoke_session", &[ix]).await?
+        }
+        Command::Deposit { amount } => {
+            let ix = instructions::deposit(program_id, &signer, amount);
+            ctx.submit("deposit", &[ix]).await?
+        }
+        Command::WithdrawBalance { amount } => {
+            let ix = instructions::withdraw_balance(program_id, &signer, amount);
+            ctx.submit("withdraw_balance", &[ix]).await?
+        }
+        Command::Settle {
+            round,
+            vrf_keypair,
+            oracle_keypair,
+        } => {
+            let bet_round = fetch_bet_round(&ctx.chain, &round).await?;
+            let ixs = match vrf_keypair {
+                Some(path) => {
+                    let randomness = vrf::prove_round(&read_keypair(&path)?, &round, &bet_round);
+                    vec![instructions::fulfill_randomness(
+                        program_id, &round, &bet_round, randomness,
+                    )]
+                }
+                None => {
+                    let alpha = bet_round.vrf_input(&round);
+                    let mut signed = Vec::with_capacity(oracle_keypair.len());
+                    for path in &oracle_keypair {
+                        signed.push(beacons::sign(&read_keypair(path)?, &alpha));
+                    }
+                    vec![
+                        beacons::instruction(&alpha, &signed),
+                        instructions::fulfill_randomness(
+                            program_id,
+                            &round,
+                            &bet_round,
+                            Randomness::Oracles,
+                        ),
+                    ]
+                }
+            };
+            ctx.submit("fulfill_randomness", &ixs)
+                .await?
+                .with_bet_round(round)
+        }
+        Command::Refund { round } => {
+            let bet_round = fetch_bet_round(&ctx.chain, &round).await?;
+            let ix = instructions::refund_timeout(program_id, &round, &bet_round);
+            ctx.submit("refund_timeout", &[ix])
+                .await?
+                .with_bet_round(round)
+        }
+        Command::Quote { amount } => {
+            let quote = views::quote_bet(&ctx.chain, program_id, &signer, amount).await?;
+            return Ok(Report::Quote(quote.into()));
+        }
+        Command::Balance { player } => {
+            let player = player.unwrap_or(signer);
+            let balance = fetch_player_balance(&ctx.chain, program_id, &player).await?;
+            return Ok(Report::Balance(BalanceReport {
+                player: player.to_string(),
+                balance_lamports: balance.map_or(0, |balance| balance.lamports),
+            }));
+        }
+        Command::Pool { round_id } => {
+            let address = pool_address(program_id, round_id).0;
+            let pool = fetch_pool(&ctx.chain, program_id, round_id).await?;
+            let slot = ctx.chain.get_slot().await?;
+            return Ok(Report::Pool(PoolReport::new(&address, &pool, slot)));
+        }
+        Command::Stats => {
+            let state = fetch_vault(&ctx.chain, program_id).await?;
+            return Ok(Report::Vault(VaultReport::new(program_id, &state)));
+        }
+    };
+
+    Ok(Report::Transaction(report))
+}
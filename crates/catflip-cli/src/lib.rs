@@ -0,0 +1,9 @@
+//! `catflip` operator CLI.
+//!
+//! The binary is a thin wrapper around [`commands::run`]; keeping the command
+//! logic in the library lets the integration tests drive it against an
+//! in-process bank instead of a live cluster.
+
+pub mod cli;
+pub mod commands;
+pub mod output;
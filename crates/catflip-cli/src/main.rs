@@ -0,0 +1,60 @@
+use catflip_cli::{
+    cli::{Cli, OutputFormat},
+    commands::{self, Context},
+};
+use catflip_client::config::Settings;
+use clap::Parser;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let output = cli.output;
+
+    let result = async {
+        let settings = Settings::load(
+            cli.config.as_deref(),
+            cli.url.as_deref(),
+            cli.keypair.as_deref(),
+        )?;
+        let ctx = Context {
+            chain: RpcClient::new_with_commitment(settings.url, settings.commitment),
+            signer: settings.keypair,
+            program_id: cli.program_id,
+            dry_run: cli.dry_run,
+        };
+        commands::run(&ctx, cli.command).await
+    }
+    .await;
+
+    match (result, output) {
+        (Ok(report), OutputFormat::Json) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("report serializes")
+            );
+            if report.is_failure() {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        (Ok(report), OutputFormat::Display) => {
+            print!("{report}");
+            if report.is_failure() {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        (Err(err), OutputFormat::Json) => {
+            println!("{}", serde_json::json!({ "error": format!("{err:#}") }));
+            ExitCode::FAILURE
+        }
+        (Err(err), OutputFormat::Display) => {
+            eprintln!("Error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
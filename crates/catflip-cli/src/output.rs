@@ -0,0 +1,347 @@
+use catflip::{
+    instructions::BetQuote,
+    state::{PoolPhase, PoolRound, Side},
+};
+use catflip_client::{state::VaultState, Simulation};
+use serde::Serialize;
+use solana_sdk::{native_token::lamports_to_sol, pubkey::Pubkey, signature::Signature};
+use std::fmt;
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Report {
+    Transaction(TransactionReport),
+    Vault(VaultReport),
+    Quote(QuoteReport),
+    Balance(BalanceReport),
+    Pool(PoolReport),
+}
+
+impl Report {
+    /// Whether the command should exit non-zero; only failed simulations
+    /// produce a report instead of an error.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Report::Transaction(report) if report.error.is_some())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Report::Transaction(report) => report.fmt(f),
+            Report::Vault(report) => report.fmt(f),
+            Report::Quote(report) => report.fmt(f),
+            Report::Balance(report) => report.fmt(f),
+            Report::Pool(report) => report.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionReport {
+    pub action: &'static str,
+    pub simulated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bet_round: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units_consumed: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<String>,
+}
+
+impl TransactionReport {
+    pub fn sent(action: &'static str, signature: Signature) -> Self {
+        Self {
+            action,
+            simulated: false,
+            signature: Some(signature.to_string()),
+            bet_round: None,
+            challenge: None,
+            pool: None,
+            error: None,
+            units_consumed: None,
+            logs: Vec::new(),
+        }
+    }
+
+    pub fn simulated(action: &'static str, simulation: Simulation) -> Self {
+        Self {
+            action,
+            simulated: true,
+            signature: None,
+            bet_round: None,
+            challenge: None,
+            pool: None,
+            error: simulation.err.map(|err| err.to_string()),
+            units_consumed: Some(simulation.units_consumed),
+            logs: simulation.logs,
+        }
+    }
+
+    pub fn with_bet_round(mut self, bet_round: Pubkey) -> Self {
+        self.bet_round = Some(bet_round.to_string());
+        self
+    }
+
+    pub fn with_challenge(mut self, challenge: Pubkey) -> Self {
+        self.challenge = Some(challenge.to_string());
+        self
+    }
+
+    pub fn with_pool(mut self, pool: Pubkey) -> Self {
+        self.pool = Some(pool.to_string());
+        self
+    }
+}
+
+impl fmt::Display for TransactionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.error, self.simulated) {
+            (Some(err), _) => writeln!(f, "{}: simulation failed: {err}", self.action)?,
+            (None, true) => writeln!(f, "{}: simulation succeeded", self.action)?,
+            (None, false) => writeln!(f, "{}: confirmed", self.action)?,
+        }
+        if let Some(signature) = &self.signature {
+            writeln!(f, "Signature: {signature}")?;
+        }
+        if let Some(bet_round) = &self.bet_round {
+            writeln!(f, "Bet round: {bet_round}")?;
+        }
+        if let Some(challenge) = &self.challenge {
+            writeln!(f, "Challenge: {challenge}")?;
+        }
+        if let Some(pool) = &self.pool {
+            writeln!(f, "Pool round: {pool}")?;
+        }
+        if let Some(units) = self.units_consumed {
+            writeln!(f, "Compute units: {units}")?;
+        }
+        for line in &self.logs {
+            writeln!(f, "  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct VaultReport {
+    pub program_id: String,
+    pub vault: String,
+    pub authority: String,
+    pub is_paused: bool,
+    pub balance_lamports: u64,
+    pub min_bet_lamports: u64,
+    pub max_bet_lamports: u64,
+    pub max_exposure_bps: u16,
+    pub house_edge_bps: u16,
+    /// Cut of each settled challenge's pot the vault takes.
+    pub rake_bps: u16,
+    pub total_volume: u64,
+    pub total_bets: u64,
+    pub total_wins: u64,
+    pub imported_principal_lamports: u64,
+    /// Part of the balance that players have deposited or won into their
+    /// balances; it isn't bankroll.
+    pub player_liabilities_lamports: u64,
+    /// Part of the balance set aside for open batches and won streak steps;
+    /// it isn't bankroll either.
+    pub reserved_payouts_lamports: u64,
+    /// `None` until `set-vrf-key` registers one; bets can't settle before.
+    pub vrf_key: Option<String>,
+    /// Oracle signatures a round needs; 0 settles with the VRF key instead.
+    pub oracle_threshold: u8,
+    pub oracles: Vec<String>,
+}
+
+impl VaultReport {
+    pub fn new(program_id: &Pubkey, state: &VaultState) -> Self {
+        let vault = &state.vault;
+        Self {
+            program_id: program_id.to_string(),
+            vault: state.address.to_string(),
+            authority: vault.authority.to_string(),
+            is_paused: vault.is_paused,
+            balance_lamports: state.lamports,
+            min_bet_lamports: vault.min_bet_lamports,
+            max_bet_lamports: state.max_bet(),
+            max_exposure_bps: vault.max_exposure_bps,
+            house_edge_bps: vault.house_edge_bps,
+            rake_bps: vault.rake_bps,
+            total_volume: vault.total_volume,
+            total_bets: vault.total_bets,
+            total_wins: vault.total_wins,
+            imported_principal_lamports: vault.imported_principal,
+            player_liabilities_lamports: vault.player_liabilities,
+            reserved_payouts_lamports: vault.reserved_payouts,
+            vrf_key: (vault.vrf_public_key != [0; 32])
+                .then(|| Pubkey::from(vault.vrf_public_key).to_string()),
+            oracle_threshold: vault.oracle_threshold,
+            oracles: vault.oracles().iter().map(Pubkey::to_string).collect(),
+        }
+    }
+}
+
+impl fmt::Display for VaultReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Program ID: {}", self.program_id)?;
+        writeln!(f, "Vault: {}", self.vault)?;
+        writeln!(f, "Authority: {}", self.authority)?;
+        writeln!(
+            f,
+            "Status: {}",
+            if self.is_paused { "paused" } else { "active" }
+        )?;
+        writeln!(f, "Balance: {} SOL", lamports_to_sol(self.balance_lamports))?;
+        writeln!(
+            f,
+            "Owed to players: {} SOL",
+            lamports_to_sol(self.player_liabilities_lamports)
+        )?;
+        writeln!(
+            f,
+            "Reserved for payouts: {} SOL",
+            lamports_to_sol(self.reserved_payouts_lamports)
+        )?;
+        writeln!(f, "Min bet: {} SOL", lamports_to_sol(self.min_bet_lamports))?;
+        writeln!(f, "Max bet: {} SOL", lamports_to_sol(self.max_bet_lamports))?;
+        writeln!(f, "Max exposure: {}%", self.max_exposure_bps as f64 / 100.0)?;
+        writeln!(f, "House edge: {}%", self.house_edge_bps as f64 / 100.0)?;
+        writeln!(f, "Challenge rake: {}%", self.rake_bps as f64 / 100.0)?;
+        writeln!(
+            f,
+            "Total volume: {} SOL",
+            lamports_to_sol(self.total_volume)
+        )?;
+        writeln!(f, "Total bets: {}", self.total_bets)?;
+        writeln!(f, "Total wins: {}", self.total_wins)?;
+        writeln!(
+            f,
+            "Imported principal: {} SOL",
+            lamports_to_sol(self.imported_principal_lamports)
+        )?;
+        writeln!(
+            f,
+            "VRF key: {}",
+            self.vrf_key.as_deref().unwrap_or("not set")
+        )?;
+        if self.oracles.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            f,
+            "Oracles: {} of {}",
+            self.oracle_threshold,
+            self.oracles.len()
+        )?;
+        for oracle in &self.oracles {
+            writeln!(f, "  {oracle}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuoteReport {
+    pub stake_lamports: u64,
+    /// Paid out on a win, stake included.
+    pub payout_lamports: u64,
+    pub max_bet_lamports: u64,
+    pub house_edge_bps: u16,
+}
+
+impl From<BetQuote> for QuoteReport {
+    fn from(quote: BetQuote) -> Self {
+        Self {
+            stake_lamports: quote.stake,
+            payout_lamports: quote.payout,
+            max_bet_lamports: quote.max_bet,
+            house_edge_bps: quote.house_edge_bps,
+        }
+    }
+}
+
+impl fmt::Display for QuoteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Stake: {} SOL", lamports_to_sol(self.stake_lamports))?;
+        writeln!(f, "Payout: {} SOL", lamports_to_sol(self.payout_lamports))?;
+        writeln!(f, "Max bet: {} SOL", lamports_to_sol(self.max_bet_lamports))?;
+        writeln!(f, "House edge: {}%", self.house_edge_bps as f64 / 100.0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceReport {
+    pub player: String,
+    pub balance_lamports: u64,
+}
+
+impl fmt::Display for BalanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Player: {}", self.player)?;
+        writeln!(f, "Balance: {} SOL", lamports_to_sol(self.balance_lamports))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolReport {
+    pub round_id: u64,
+    pub pool: String,
+    pub phase: &'static str,
+    pub lock_slot: u64,
+    pub fee_bps: u16,
+    pub alive_lamports: u64,
+    pub dead_lamports: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winning_side: Option<&'static str>,
+}
+
+impl PoolReport {
+    pub fn new(address: &Pubkey, pool: &PoolRound, slot: u64) -> Self {
+        let side = |side| match side {
+            Side::Alive => "alive",
+            Side::Dead => "dead",
+        };
+        Self {
+            round_id: pool.round_id,
+            pool: address.to_string(),
+            phase: match pool.phase(slot) {
+                PoolPhase::Open => "open",
+                PoolPhase::Locked => "locked",
+                PoolPhase::Resolved => "resolved",
+                PoolPhase::Expired => "expired",
+            },
+            lock_slot: pool.lock_slot,
+            fee_bps: pool.fee_bps,
+            alive_lamports: pool.alive_total,
+            dead_lamports: pool.dead_total,
+            winning_side: pool.is_resolved.then(|| side(pool.winning_side)),
+        }
+    }
+}
+
+impl fmt::Display for PoolReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pool round {}: {}", self.round_id, self.pool)?;
+        writeln!(f, "Phase: {}", self.phase)?;
+        writeln!(f, "Betting closes at slot: {}", self.lock_slot)?;
+        writeln!(f, "Fee: {}%", self.fee_bps as f64 / 100.0)?;
+        writeln!(
+            f,
+            "Alive pool: {} SOL",
+            lamports_to_sol(self.alive_lamports)
+        )?;
+        writeln!(f, "Dead pool: {} SOL", lamports_to_sol(self.dead_lamports))?;
+        if let Some(side) = self.winning_side {
+            writeln!(f, "Box opened: {side}")?;
+        }
+        Ok(())
+    }
+}
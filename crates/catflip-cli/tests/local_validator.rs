@@ -0,0 +1,1913 @@
+use anchor_lang::error::ErrorCode;
+use catflip::errors::CatflipError;
+use catflip::instructions::{BetGuards, Randomness};
+use catflip::slot_hashes::SlotHash;
+use catflip::state::{BatchPlan, BetBatch, BetRound, Session, Vault};
+use catflip_cli::{
+    cli::{Command, Progression, SessionPermission, Side},
+    commands::{run, Context},
+    output::Report,
+};
+use catflip_client::{
+    beacons, instructions,
+    pda::{
+        bet_batch_address, bet_round_address, pool_address, pool_position_address, session_address,
+        vault_address,
+    },
+    state::{
+        fetch_bet_batch, fetch_bet_round, fetch_challenge, fetch_player_balance, fetch_pool,
+        fetch_pool_position, fetch_session, fetch_slot_hash, fetch_vault,
+    },
+    testing::{program_test, TestChain},
+    views, vrf, Chain, ClientError,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    hash::hashv,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{write_keypair_file, Keypair, Signer},
+    system_instruction, system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+async fn start() -> (ProgramTestContext, Context<TestChain>) {
+    let test_context = program_test().start_with_context().await;
+    let ctx = Context {
+        chain: TestChain::new(test_context.banks_client.clone()),
+        signer: Keypair::from_bytes(&test_context.payer.to_bytes()).unwrap(),
+        program_id: catflip::ID,
+        dry_run: false,
+    };
+    (test_context, ctx)
+}
+
+async fn init_and_fund(ctx: &Context<TestChain>) {
+    run(
+        ctx,
+        Command::Init {
+            min_bet: LAMPORTS_PER_SOL / 1000,
+            max_exposure_bps: 1000,
+            house_edge_bps: 200,
+        },
+    )
+    .await
+    .unwrap();
+    run(
+        ctx,
+        Command::Fund {
+            amount: 10 * LAMPORTS_PER_SOL,
+        },
+    )
+    .await
+    .unwrap();
+}
+
+/// Writes `key` to a keypair file, for commands that take a path.
+fn keypair_file(key: &Keypair) -> String {
+    let path = std::env::temp_dir().join(format!("catflip-cli-test-{}.json", key.pubkey()));
+    write_keypair_file(key, &path).unwrap();
+    path.to_str().unwrap().to_owned()
+}
+
+fn unguarded_bet(amount: u64) -> Command {
+    Command::Bet {
+        amount,
+        min_payout: None,
+        max_edge_bps: None,
+        last_valid_slot: None,
+        from_balance: false,
+        on_behalf_of: None,
+    }
+}
+
+fn bet_round(report: &Report) -> Pubkey {
+    match report {
+        Report::Transaction(report) => report.bet_round.as_ref().unwrap().parse().unwrap(),
+        _ => panic!("expected a transaction report"),
+    }
+}
+
+fn assert_custom_error(err: anyhow::Error, expected: impl Into<u32>) {
+    let code = match err
+        .downcast_ref::<ClientError>()
+        .and_then(ClientError::transaction_error)
+    {
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => code,
+        other => panic!("expected a custom program error, got {other:?}"),
+    };
+    assert_eq!(code, expected.into());
+}
+
+#[tokio::test]
+async fn operator_flow() {
+    let (_test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    let vault = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(vault.address, vault_address(&ctx.program_id).0);
+    assert!(vault.lamports > 10 * LAMPORTS_PER_SOL);
+    assert_eq!(vault.max_bet(), vault.lamports / 10);
+
+    run(&ctx, Command::Pause).await.unwrap();
+    let err = run(&ctx, unguarded_bet(LAMPORTS_PER_SOL / 100))
+        .await
+        .unwrap_err();
+    assert_custom_error(err, CatflipError::GamePaused);
+    run(&ctx, Command::Resume).await.unwrap();
+
+    run(
+        &ctx,
+        Command::UpdateConfig {
+            min_bet: Some(LAMPORTS_PER_SOL / 100),
+            max_exposure_bps: None,
+            house_edge_bps: None,
+            rake_bps: None,
+        },
+    )
+    .await
+    .unwrap();
+    let vault = fetch_vault(&ctx.chain, &ctx.program_id)
+        .await
+        .unwrap()
+        .vault;
+    assert_eq!(vault.min_bet_lamports, LAMPORTS_PER_SOL / 100);
+    assert_eq!(vault.max_exposure_bps, 1000);
+
+    let err = run(&ctx, unguarded_bet(LAMPORTS_PER_SOL / 1000))
+        .await
+        .unwrap_err();
+    assert_custom_error(err, CatflipError::BetBelowMinimum);
+
+    let err = run(
+        &ctx,
+        Command::UpdateConfig {
+            min_bet: None,
+            max_exposure_bps: None,
+            house_edge_bps: Some(10_000),
+            rake_bps: None,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::InvalidConfig);
+
+    let report = run(&ctx, Command::Stats).await.unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["total_bets"], 0);
+    assert_eq!(json["is_paused"], false);
+    assert_eq!(json["min_bet_lamports"], LAMPORTS_PER_SOL / 100);
+}
+
+#[tokio::test]
+async fn quote_and_vault_status_read_live_state() {
+    let (_test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    let state = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    let status = views::vault_status(&ctx.chain, &ctx.program_id, &ctx.signer.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(status.balance, state.lamports);
+    assert_eq!(status.max_bet, state.max_bet());
+    assert_eq!(
+        status.free_liquidity,
+        state.lamports - Rent::default().minimum_balance(Vault::SIZE)
+    );
+    assert_eq!(status.min_bet, LAMPORTS_PER_SOL / 1000);
+    assert_eq!(status.payout_multiplier_bps, 19_600);
+    assert!(!status.is_paused);
+
+    let report = run(
+        &ctx,
+        Command::Quote {
+            amount: LAMPORTS_PER_SOL / 10,
+        },
+    )
+    .await
+    .unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["payout_lamports"], 196_000_000);
+    assert_eq!(json["max_bet_lamports"], state.max_bet());
+
+    let report = run(&ctx, unguarded_bet(LAMPORTS_PER_SOL / 10))
+        .await
+        .unwrap();
+    let bet = fetch_bet_round(&ctx.chain, &bet_round(&report))
+        .await
+        .unwrap();
+    assert_eq!(json["payout_lamports"], bet.potential_payout);
+
+    // A quote fails exactly where the bet would.
+    let rejected = [
+        (LAMPORTS_PER_SOL / 10_000, CatflipError::BetBelowMinimum),
+        (5 * LAMPORTS_PER_SOL, CatflipError::BetExceedsMaxExposure),
+    ];
+    for (amount, expected) in rejected {
+        let err = run(&ctx, Command::Quote { amount }).await.unwrap_err();
+        assert_custom_error(err, expected);
+    }
+    run(&ctx, Command::Pause).await.unwrap();
+    let err = run(
+        &ctx,
+        Command::Quote {
+            amount: LAMPORTS_PER_SOL / 10,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::GamePaused);
+    let status = views::vault_status(&ctx.chain, &ctx.program_id, &ctx.signer.pubkey())
+        .await
+        .unwrap();
+    assert!(status.is_paused);
+    assert_eq!(status.total_bets, 1);
+}
+
+#[tokio::test]
+async fn bet_guards_reject_worse_terms() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    test_context.warp_to_slot(100).unwrap();
+
+    let stake = LAMPORTS_PER_SOL / 10;
+    let quote = views::quote_bet(&ctx.chain, &ctx.program_id, &ctx.signer.pubkey(), stake)
+        .await
+        .unwrap();
+    // The edge goes up between the quote and the bet.
+    run(
+        &ctx,
+        Command::UpdateConfig {
+            min_bet: None,
+            max_exposure_bps: None,
+            house_edge_bps: Some(300),
+            rake_bps: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let slot = ctx.chain.get_slot().await.unwrap();
+    let guarded = |min_payout, max_edge_bps, last_valid_slot| Command::Bet {
+        amount: stake,
+        min_payout,
+        max_edge_bps,
+        last_valid_slot,
+        from_balance: false,
+        on_behalf_of: None,
+    };
+    let rejected = [
+        (
+            guarded(None, Some(quote.house_edge_bps), None),
+            CatflipError::HouseEdgeAboveMaximum,
+        ),
+        (
+            guarded(Some(quote.payout), None, None),
+            CatflipError::PayoutBelowMinimum,
+        ),
+        (
+            guarded(None, None, Some(slot - 1)),
+            CatflipError::BetExpired,
+        ),
+    ];
+    for (command, expected) in rejected {
+        let err = run(&ctx, command).await.unwrap_err();
+        assert_custom_error(err, expected);
+    }
+
+    let report = run(&ctx, guarded(Some(194_000_000), Some(300), Some(slot)))
+        .await
+        .unwrap();
+    let bet = fetch_bet_round(&ctx.chain, &bet_round(&report))
+        .await
+        .unwrap();
+    assert_eq!(bet.potential_payout, 194_000_000);
+}
+
+#[tokio::test]
+async fn player_balance_ledger() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let player = ctx.signer.pubkey();
+    let balance = || async {
+        let balance = fetch_player_balance(&ctx.chain, &ctx.program_id, &player)
+            .await
+            .unwrap()
+            .unwrap()
+            .lamports;
+        let owed = fetch_vault(&ctx.chain, &ctx.program_id)
+            .await
+            .unwrap()
+            .vault
+            .player_liabilities;
+        // One player, so the vault owes exactly their balance.
+        assert_eq!(balance, owed);
+        balance
+    };
+
+    let stake = LAMPORTS_PER_SOL / 10;
+    let bet_from_balance = Command::Bet {
+        amount: stake,
+        min_payout: None,
+        max_edge_bps: None,
+        last_valid_slot: None,
+        from_balance: true,
+        on_behalf_of: None,
+    };
+
+    // Without a deposit there is no balance to bet from.
+    let before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    let err = run(&ctx, bet_from_balance.clone()).await.unwrap_err();
+    assert_custom_error(err, ErrorCode::AccountNotInitialized);
+    assert!(fetch_player_balance(&ctx.chain, &ctx.program_id, &player)
+        .await
+        .unwrap()
+        .is_none());
+    let state = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(state.lamports, before.lamports);
+    assert_eq!(state.vault.total_bets, before.vault.total_bets);
+
+    run(
+        &ctx,
+        Command::Deposit {
+            amount: LAMPORTS_PER_SOL,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(balance().await, LAMPORTS_PER_SOL);
+    let state = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(state.lamports - before.lamports, LAMPORTS_PER_SOL);
+
+    // Deposits are owed to the player, not bankroll.
+    let status = views::vault_status(&ctx.chain, &ctx.program_id, &player)
+        .await
+        .unwrap();
+    assert_eq!(status.max_bet, before.max_bet());
+    assert_eq!(state.max_bet(), before.max_bet());
+    assert_eq!(status.player_liabilities, LAMPORTS_PER_SOL);
+    let err = run(
+        &ctx,
+        Command::Withdraw {
+            amount: status.free_liquidity + 1,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::InsufficientVaultBalance);
+
+    let report = run(&ctx, bet_from_balance.clone()).await.unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert!(bet.from_balance);
+    assert_eq!(balance().await, LAMPORTS_PER_SOL - stake);
+
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    let wallet_before = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    run(
+        &ctx,
+        Command::Settle {
+            round,
+            vrf_keypair: Some(keypair_file(&vrf_key)),
+            oracle_keypair: vec![],
+        },
+    )
+    .await
+    .unwrap();
+    let settled = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    let mut expected = LAMPORTS_PER_SOL - stake;
+    if settled.is_winner {
+        expected += settled.potential_payout;
+    }
+    assert_eq!(balance().await, expected);
+    // The payout, if any, went to the balance; the wallet only paid the fee.
+    let wallet_after = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    assert!(wallet_after.lamports < wallet_before.lamports);
+
+    // A round staked from the balance can't be settled or refunded to the
+    // wallet.
+    test_context.warp_to_slot(bet.slot + 1).unwrap();
+    let report = run(&ctx, bet_from_balance).await.unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    let as_wallet_bet = BetRound {
+        from_balance: false,
+        ..bet.clone()
+    };
+    let randomness = vrf::prove_round(&vrf_key, &round, &bet);
+    let ix = instructions::fulfill_randomness(&ctx.program_id, &round, &as_wallet_bet, randomness);
+    let err = send(&ctx, &[ix]).await.unwrap_err();
+    assert_custom_error(err, CatflipError::PlayerBalanceRequired);
+    test_context
+        .warp_to_slot(bet.slot + BetRound::TIMEOUT_SLOTS + 1)
+        .unwrap();
+    // Past the timeout the randomness no longer settles the round.
+    let randomness = vrf::prove_round(&vrf_key, &round, &bet);
+    let ix = instructions::fulfill_randomness(&ctx.program_id, &round, &bet, randomness);
+    let err = send(&ctx, &[ix]).await.unwrap_err();
+    assert_custom_error(err, CatflipError::RoundTimedOut);
+    let ix = instructions::refund_timeout(&ctx.program_id, &round, &as_wallet_bet);
+    let err = send(&ctx, &[ix]).await.unwrap_err();
+    assert_custom_error(err, CatflipError::PlayerBalanceRequired);
+    run(&ctx, Command::Refund { round }).await.unwrap();
+    assert_eq!(balance().await, expected);
+
+    let err = run(
+        &ctx,
+        Command::WithdrawBalance {
+            amount: expected + 1,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::InsufficientPlayerBalance);
+
+    // Players can always leave, even while the vault is paused.
+    run(&ctx, Command::Pause).await.unwrap();
+    let wallet_before = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    run(&ctx, Command::WithdrawBalance { amount: expected })
+        .await
+        .unwrap();
+    assert_eq!(balance().await, 0);
+    let wallet_after = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    assert!(wallet_after.lamports > wallet_before.lamports);
+    let report = run(&ctx, Command::Balance { player: None }).await.unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["balance_lamports"], 0);
+}
+
+#[tokio::test]
+async fn session_key_bets_for_player() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let player = ctx.signer.pubkey();
+    let session_key = Keypair::new();
+    let session_ctx = Context {
+        chain: ctx.chain.clone(),
+        signer: Keypair::from_bytes(&session_key.to_bytes()).unwrap(),
+        program_id: ctx.program_id,
+        dry_run: false,
+    };
+    // The key pays its own fees and the rounds' rent.
+    send(
+        &ctx,
+        &[system_instruction::transfer(
+            &player,
+            &session_key.pubkey(),
+            LAMPORTS_PER_SOL / 10,
+        )],
+    )
+    .await
+    .unwrap();
+    run(
+        &ctx,
+        Command::Deposit {
+            amount: LAMPORTS_PER_SOL,
+        },
+    )
+    .await
+    .unwrap();
+
+    let stake = LAMPORTS_PER_SOL / 10;
+    let session_bet = |from_balance| Command::Bet {
+        amount: stake,
+        min_payout: None,
+        max_edge_bps: None,
+        last_valid_slot: None,
+        from_balance,
+        on_behalf_of: Some(player),
+    };
+    let session = || async {
+        fetch_session(&ctx.chain, &ctx.program_id, &player, &session_key.pubkey())
+            .await
+            .unwrap()
+    };
+
+    // No session yet.
+    assert!(run(&session_ctx, session_bet(true)).await.is_err());
+
+    run(
+        &ctx,
+        Command::CreateSession {
+            key: session_key.pubkey(),
+            expires_in: 100,
+            max_stake: 2 * stake,
+            allow: vec![SessionPermission::BetFromBalance],
+        },
+    )
+    .await
+    .unwrap();
+    let created = session().await.unwrap();
+    assert_eq!(created.allowed, Session::BET_FROM_BALANCE);
+    assert_eq!(created.max_stake, 2 * stake);
+
+    let err = run(&session_ctx, session_bet(false)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::SessionNotAllowed);
+
+    let mut slot = 0;
+    for placed in 1..=2 {
+        let report = run(&session_ctx, session_bet(true)).await.unwrap();
+        let bet = fetch_bet_round(&ctx.chain, &bet_round(&report))
+            .await
+            .unwrap();
+        assert_eq!(bet.player, player);
+        assert!(bet.from_balance);
+        assert_eq!(session().await.unwrap().staked, placed * stake);
+        slot = bet.slot + 1;
+        test_context.warp_to_slot(slot).unwrap();
+    }
+    let balance = fetch_player_balance(&ctx.chain, &ctx.program_id, &player)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(balance.lamports, LAMPORTS_PER_SOL - 2 * stake);
+    let err = run(&session_ctx, session_bet(true)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::SessionStakeExceeded);
+
+    run(
+        &ctx,
+        Command::RevokeSession {
+            key: session_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(session().await.is_none());
+    assert!(run(&session_ctx, session_bet(true)).await.is_err());
+
+    // Wallet bets are staked from what the session took from the wallet.
+    let wallet_before = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    run(
+        &ctx,
+        Command::CreateSession {
+            key: session_key.pubkey(),
+            expires_in: 10,
+            max_stake: 2 * stake,
+            allow: vec![SessionPermission::Bet],
+        },
+    )
+    .await
+    .unwrap();
+    let wallet_after = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    assert!(wallet_before.lamports - wallet_after.lamports > 2 * stake);
+
+    let vault_before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    let report = run(&session_ctx, session_bet(false)).await.unwrap();
+    let bet = fetch_bet_round(&ctx.chain, &bet_round(&report))
+        .await
+        .unwrap();
+    assert_eq!(bet.player, player);
+    assert!(!bet.from_balance);
+    let vault_after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(vault_after.lamports - vault_before.lamports, stake);
+
+    test_context.warp_to_slot(slot + 20).unwrap();
+    let err = run(&session_ctx, session_bet(false)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::SessionExpired);
+
+    // Revoking hands back the unused stake with the rent.
+    let address = session_address(&ctx.program_id, &player, &session_key.pubkey()).0;
+    let held = ctx.chain.get_account(&address).await.unwrap().unwrap();
+    assert_eq!(
+        held.lamports,
+        Rent::default().minimum_balance(Session::SIZE) + stake
+    );
+    let wallet_before = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    run(
+        &ctx,
+        Command::RevokeSession {
+            key: session_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    let wallet_after = ctx.chain.get_account(&player).await.unwrap().unwrap();
+    assert!(wallet_after.lamports - wallet_before.lamports > stake);
+    assert!(ctx.chain.get_account(&address).await.unwrap().is_none());
+}
+
+fn batch(stake: u64, flips: u8, progression: Progression, stop_loss: Option<u64>) -> Command {
+    Command::Batch {
+        stake,
+        flips,
+        progression,
+        stop_loss,
+        take_profit: None,
+    }
+}
+
+#[tokio::test]
+async fn batch_settles_every_flip_from_one_seed() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    let stake = LAMPORTS_PER_SOL / 100;
+
+    let invalid = [
+        batch(stake, 0, Progression::Flat, None),
+        batch(stake, BatchPlan::MAX_FLIPS + 1, Progression::Flat, None),
+        batch(stake, 10, Progression::Flat, Some(stake - 1)),
+    ];
+    for command in invalid {
+        let err = run(&ctx, command).await.unwrap_err();
+        assert_custom_error(err, CatflipError::InvalidBatch);
+    }
+    // Unbounded doubling reaches stakes far beyond the max bet.
+    let err = run(&ctx, batch(stake, 20, Progression::Martingale, None))
+        .await
+        .unwrap_err();
+    assert_custom_error(err, CatflipError::BetExceedsMaxExposure);
+
+    let before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    let report = run(&ctx, batch(stake, 10, Progression::Flat, None))
+        .await
+        .unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert_eq!(bet.flips, 10);
+    assert_eq!(bet.stake_lamports, 10 * stake);
+    let win = before.vault.potential_payout(stake).unwrap() - stake;
+    assert_eq!(bet.potential_payout, 10 * stake + 10 * win);
+    let placed = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(placed.lamports - before.lamports, bet.stake_lamports);
+    assert_eq!(placed.vault.reserved_payouts, bet.potential_payout);
+    // The reserve isn't bankroll.
+    assert_eq!(
+        placed.max_bet(),
+        (placed.lamports - bet.potential_payout) / 10
+    );
+
+    // The round's randomness decides every flip.
+    let randomness = vrf::prove_round(&vrf_key, &round, &bet);
+    let Randomness::Vrf { output, .. } = &randomness else {
+        unreachable!()
+    };
+    let entropy: [u8; 32] = output[..32].try_into().unwrap();
+    let mut expected_payout = bet.stake_lamports;
+    let mut expected_wins = 0u32;
+    for index in 0..10 {
+        if BetRound::is_winning_roll(BetBatch::roll(&entropy, index)) {
+            expected_payout += win;
+            expected_wins |= 1 << index;
+        } else {
+            expected_payout -= stake;
+        }
+    }
+    let as_single_bet = BetRound {
+        flips: 0,
+        ..bet.clone()
+    };
+    let ix = instructions::fulfill_randomness(&ctx.program_id, &round, &as_single_bet, randomness);
+    let err = send(&ctx, &[ix]).await.unwrap_err();
+    assert_custom_error(err, CatflipError::BatchRequired);
+
+    run(
+        &ctx,
+        Command::Settle {
+            round,
+            vrf_keypair: Some(keypair_file(&vrf_key)),
+            oracle_keypair: vec![],
+        },
+    )
+    .await
+    .unwrap();
+    let results = fetch_bet_batch(&ctx.chain, &ctx.program_id, &round)
+        .await
+        .unwrap();
+    assert_eq!(results.flips_played, 10);
+    assert_eq!(results.wins, expected_wins);
+    assert_eq!(results.payout, expected_payout);
+    let settled = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(placed.lamports - settled.lamports, expected_payout);
+    assert_eq!(settled.vault.reserved_payouts, 0);
+    assert_eq!(settled.vault.total_bets, before.vault.total_bets + 10);
+    assert_eq!(
+        settled.vault.total_wins,
+        before.vault.total_wins + expected_wins.count_ones() as u64
+    );
+
+    // A stop-loss caps what the player pays up front; refunds return it.
+    test_context.warp_to_slot(bet.slot + 1).unwrap();
+    let stop_loss = 7 * stake;
+    let report = run(
+        &ctx,
+        batch(stake, 10, Progression::Martingale, Some(stop_loss)),
+    )
+    .await
+    .unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert!(bet.stake_lamports <= stop_loss);
+    test_context
+        .warp_to_slot(bet.slot + BetRound::TIMEOUT_SLOTS + 1)
+        .unwrap();
+    let vault_before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    run(&ctx, Command::Refund { round }).await.unwrap();
+    let vault_after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(
+        vault_before.lamports - vault_after.lamports,
+        bet.stake_lamports
+    );
+    assert_eq!(vault_after.vault.reserved_payouts, 0);
+    let batch_address = bet_batch_address(&ctx.program_id, &round).0;
+    assert!(ctx
+        .chain
+        .get_account(&batch_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn batch_settles_at_the_edge_it_was_placed_at() {
+    let (_test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    let stake = LAMPORTS_PER_SOL / 100;
+
+    let before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    let report = run(&ctx, batch(stake, 10, Progression::Flat, None))
+        .await
+        .unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    let placed = fetch_bet_batch(&ctx.chain, &ctx.program_id, &round)
+        .await
+        .unwrap();
+    assert_eq!(placed.house_edge_bps, before.vault.house_edge_bps);
+
+    // A higher edge after placement must not shrink the batch's wins.
+    run(
+        &ctx,
+        Command::UpdateConfig {
+            min_bet: None,
+            max_exposure_bps: None,
+            house_edge_bps: Some(before.vault.house_edge_bps + 100),
+            rake_bps: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let win = before.vault.potential_payout(stake).unwrap() - stake;
+    let randomness = vrf::prove_round(&vrf_key, &round, &bet);
+    let Randomness::Vrf { output, .. } = &randomness else {
+        unreachable!()
+    };
+    let entropy: [u8; 32] = output[..32].try_into().unwrap();
+    let mut expected_payout = bet.stake_lamports;
+    for index in 0..10 {
+        if BetRound::is_winning_roll(BetBatch::roll(&entropy, index)) {
+            expected_payout += win;
+        } else {
+            expected_payout -= stake;
+        }
+    }
+
+    settle_with_vrf(&ctx, round, &vrf_key).await;
+    let settled = fetch_bet_batch(&ctx.chain, &ctx.program_id, &round)
+        .await
+        .unwrap();
+    assert_eq!(settled.payout, expected_payout);
+    let vault = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(vault.vault.reserved_payouts, 0);
+}
+
+/// Starts a streak that wins its opening bet, returning the round.
+async fn won_streak(
+    test_context: &mut ProgramTestContext,
+    ctx: &Context<TestChain>,
+    vrf_key: &Keypair,
+    stake: u64,
+) -> Pubkey {
+    let slot = ctx.chain.get_slot().await.unwrap();
+    test_context.warp_to_slot(slot + 1).unwrap();
+    let round = bet_round_address(&ctx.program_id, &ctx.signer.pubkey(), slot + 1).0;
+    let ix = instructions::start_streak(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        slot + 1,
+        stake,
+        vrf::winning_seed(vrf_key, &round, 1),
+        BetGuards::default(),
+    );
+    send(ctx, &[ix]).await.unwrap();
+    settle_with_vrf(ctx, round, vrf_key).await;
+    round
+}
+
+async fn settle_with_vrf(ctx: &Context<TestChain>, round: Pubkey, vrf_key: &Keypair) {
+    run(
+        ctx,
+        Command::Settle {
+            round,
+            vrf_keypair: Some(keypair_file(vrf_key)),
+            oracle_keypair: vec![],
+        },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn streak_doubles_until_cashed_out() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    let stake = LAMPORTS_PER_SOL / 1000;
+    let funded = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+
+    // Nothing to cash out before the opening bet has won.
+    let report = run(&ctx, Command::Streak { amount: stake }).await.unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert_eq!(bet.streak, 1);
+    let err = run(&ctx, Command::CashOut { round }).await.unwrap_err();
+    assert_custom_error(err, CatflipError::StreakNotWon);
+    test_context.warp_to_slot(bet.slot + 1).unwrap();
+
+    // Every won step stays in the box, reserved, and is the next step's
+    // stake, up to the streak's maximum length.
+    let round = won_streak(&mut test_context, &ctx, &vrf_key, stake).await;
+    for step in 2..=BetRound::MAX_STREAK {
+        let won = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+        assert!(won.is_settled && won.is_winner);
+        let vault = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+        assert_eq!(vault.vault.reserved_payouts, won.potential_payout);
+
+        let ix = instructions::continue_streak(
+            &ctx.program_id,
+            &ctx.signer.pubkey(),
+            &round,
+            vrf::winning_seed(&vrf_key, &round, step),
+            BetGuards::default(),
+        );
+        send(&ctx, &[ix]).await.unwrap();
+        let next = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+        assert_eq!(next.streak, step);
+        assert!(!next.is_settled);
+        assert_eq!(next.stake_lamports, won.potential_payout);
+        assert_eq!(
+            next.potential_payout,
+            vault.vault.potential_payout(won.potential_payout).unwrap()
+        );
+        let vault = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+        assert_eq!(vault.vault.reserved_payouts, 0);
+        settle_with_vrf(&ctx, round, &vrf_key).await;
+    }
+    let err = run(
+        &ctx,
+        Command::OpenAgain {
+            round,
+            max_edge_bps: None,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::StreakLimitReached);
+
+    let won = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    let before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    run(&ctx, Command::CashOut { round }).await.unwrap();
+    let after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(before.lamports - after.lamports, won.potential_payout);
+    assert_eq!(after.vault.reserved_payouts, 0);
+    assert_eq!(
+        after.vault.total_bets,
+        funded.vault.total_bets + 1 + BetRound::MAX_STREAK as u64
+    );
+    assert!(ctx.chain.get_account(&round).await.unwrap().is_none());
+
+    // Each step is quoted like a new bet, so doubling stops at the max bet.
+    let round = won_streak(&mut test_context, &ctx, &vrf_key, LAMPORTS_PER_SOL * 6 / 10).await;
+    let err = run(
+        &ctx,
+        Command::OpenAgain {
+            round,
+            max_edge_bps: None,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::BetExceedsMaxExposure);
+    run(&ctx, Command::CashOut { round }).await.unwrap();
+
+    // A step that never settles times out from when it was opened, and
+    // refunds the payout it staked.
+    let round = won_streak(&mut test_context, &ctx, &vrf_key, stake).await;
+    let won = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    test_context.warp_to_slot(won.slot + 100).unwrap();
+    run(
+        &ctx,
+        Command::OpenAgain {
+            round,
+            max_edge_bps: None,
+        },
+    )
+    .await
+    .unwrap();
+    let open = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert_eq!(open.reopened_slot, won.slot + 100);
+    assert_eq!(
+        open.timeout_slot(),
+        open.reopened_slot + BetRound::TIMEOUT_SLOTS
+    );
+    test_context
+        .warp_to_slot(won.slot + BetRound::TIMEOUT_SLOTS + 1)
+        .unwrap();
+    let err = run(&ctx, Command::Refund { round }).await.unwrap_err();
+    assert_custom_error(err, CatflipError::BetNotTimedOut);
+    test_context.warp_to_slot(open.timeout_slot() + 1).unwrap();
+    let before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    run(&ctx, Command::Refund { round }).await.unwrap();
+    let after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(before.lamports - after.lamports, won.potential_payout);
+    assert_eq!(after.vault.reserved_payouts, 0);
+}
+
+#[tokio::test]
+async fn streak_step_does_not_replay_a_winning_seed() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+
+    // A seed that wins the opening bet but would lose the second step.
+    let slot = ctx.chain.get_slot().await.unwrap() + 1;
+    test_context.warp_to_slot(slot).unwrap();
+    let round = bet_round_address(&ctx.program_id, &ctx.signer.pubkey(), slot).0;
+    let seed = loop {
+        let seed = vrf::winning_seed(&vrf_key, &round, 1);
+        if !vrf::wins(&vrf_key, &round, &seed, 2) {
+            break seed;
+        }
+    };
+    let ix = instructions::start_streak(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        slot,
+        LAMPORTS_PER_SOL / 1000,
+        seed,
+        BetGuards::default(),
+    );
+    send(&ctx, &[ix]).await.unwrap();
+    settle_with_vrf(&ctx, round, &vrf_key).await;
+    let won = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert!(won.is_settled && won.is_winner);
+
+    // The same seed on the next step is a different VRF input.
+    let ix = instructions::continue_streak(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &round,
+        seed,
+        BetGuards::default(),
+    );
+    send(&ctx, &[ix]).await.unwrap();
+    let replayed = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert_eq!(replayed.player_seed, won.player_seed);
+    assert_ne!(replayed.vrf_input(&round), won.vrf_input(&round));
+    settle_with_vrf(&ctx, round, &vrf_key).await;
+    let lost = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert!(lost.is_settled && !lost.is_winner);
+    let err = run(&ctx, Command::CashOut { round }).await.unwrap_err();
+    assert_custom_error(err, CatflipError::StreakNotWon);
+}
+
+fn challenge(report: &Report) -> Pubkey {
+    match report {
+        Report::Transaction(report) => report.challenge.as_ref().unwrap().parse().unwrap(),
+        _ => panic!("expected a transaction report"),
+    }
+}
+
+#[tokio::test]
+async fn challenge_pays_the_winner_less_rake() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    run(
+        &ctx,
+        Command::UpdateConfig {
+            min_bet: None,
+            max_exposure_bps: None,
+            house_edge_bps: None,
+            rake_bps: Some(500),
+        },
+    )
+    .await
+    .unwrap();
+    let opponent = Keypair::new();
+    let opponent_ctx = Context {
+        chain: ctx.chain.clone(),
+        signer: Keypair::from_bytes(&opponent.to_bytes()).unwrap(),
+        program_id: ctx.program_id,
+        dry_run: false,
+    };
+    send(
+        &ctx,
+        &[system_instruction::transfer(
+            &ctx.signer.pubkey(),
+            &opponent.pubkey(),
+            LAMPORTS_PER_SOL,
+        )],
+    )
+    .await
+    .unwrap();
+    let stake = LAMPORTS_PER_SOL / 10;
+    let open = || Command::Challenge {
+        amount: stake,
+        side: Side::Alive,
+    };
+    let settle = |challenge| Command::SettleChallenge {
+        challenge,
+        vrf_keypair: Some(keypair_file(&vrf_key)),
+        oracle_keypair: vec![],
+    };
+    let lamports = |address: Pubkey| {
+        let chain = ctx.chain.clone();
+        async move {
+            chain
+                .get_account(&address)
+                .await
+                .unwrap()
+                .map_or(0, |account| account.lamports)
+        }
+    };
+
+    // Until it is matched, only the creator can take it back.
+    let address = challenge(&run(&ctx, open()).await.unwrap());
+    let opened = fetch_challenge(&ctx.chain, &address).await.unwrap();
+    assert_eq!(opened.stake, stake);
+    assert!(!opened.is_matched());
+    let err = run(&ctx, settle(address)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::ChallengeNotMatched);
+    let err = run(&ctx, Command::MatchChallenge { challenge: address })
+        .await
+        .unwrap_err();
+    assert_custom_error(err, CatflipError::CannotMatchOwnChallenge);
+    let err = run(
+        &opponent_ctx,
+        Command::CancelChallenge { challenge: address },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::Unauthorized);
+    run(&ctx, Command::CancelChallenge { challenge: address })
+        .await
+        .unwrap();
+    assert_eq!(lamports(address).await, 0);
+
+    // Matched, the box decides and the vault keeps the rake.
+    test_context.warp_to_slot(opened.slot + 1).unwrap();
+    let address = challenge(&run(&ctx, open()).await.unwrap());
+    run(
+        &opponent_ctx,
+        Command::MatchChallenge { challenge: address },
+    )
+    .await
+    .unwrap();
+    let err = run(
+        &opponent_ctx,
+        Command::MatchChallenge { challenge: address },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::ChallengeAlreadyMatched);
+    let matched = fetch_challenge(&ctx.chain, &address).await.unwrap();
+    assert_eq!(matched.opponent, opponent.pubkey());
+    assert_eq!(matched.rake_bps, 500);
+
+    // Raising the rake now doesn't reach the escrowed pot.
+    run(
+        &ctx,
+        Command::UpdateConfig {
+            min_bet: None,
+            max_exposure_bps: None,
+            house_edge_bps: None,
+            rake_bps: Some(9_000),
+        },
+    )
+    .await
+    .unwrap();
+    // The box can't settle before the slot hash it mixes in exists.
+    let err = run(&ctx, settle(address)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::RandomnessNotReady);
+    test_context
+        .warp_to_slot(matched.entropy_slot() + 1)
+        .unwrap();
+    let SlotHash::Found(slot_hash) = fetch_slot_hash(&ctx.chain, matched.entropy_slot())
+        .await
+        .unwrap()
+    else {
+        panic!("the entropy slot should be hashed");
+    };
+    let Randomness::Vrf { output, .. } =
+        vrf::prove_challenge(&vrf_key, &address, &matched, &slot_hash)
+    else {
+        unreachable!()
+    };
+    let side = catflip::state::Side::of_roll(u64::from_le_bytes(output[..8].try_into().unwrap()));
+    let opponent_wins = side != matched.creator_side;
+    let (opponent_before, vault_before) = (
+        lamports(opponent.pubkey()).await,
+        fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap(),
+    );
+    run(&ctx, settle(address)).await.unwrap();
+    let rake = 2 * stake * 500 / 10_000;
+    let vault_after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(vault_after.lamports - vault_before.lamports, rake);
+    let won = if opponent_wins { 2 * stake - rake } else { 0 };
+    assert_eq!(lamports(opponent.pubkey()).await - opponent_before, won);
+    assert_eq!(lamports(address).await, 0);
+
+    // A matched challenge that never settles returns both stakes once it
+    // times out, whoever asks.
+    test_context
+        .warp_to_slot(matched.entropy_slot() + 2)
+        .unwrap();
+    let address = challenge(&run(&ctx, open()).await.unwrap());
+    run(
+        &opponent_ctx,
+        Command::MatchChallenge { challenge: address },
+    )
+    .await
+    .unwrap();
+    let matched = fetch_challenge(&ctx.chain, &address).await.unwrap();
+    let err = run(&ctx, Command::CancelChallenge { challenge: address })
+        .await
+        .unwrap_err();
+    assert_custom_error(err, CatflipError::BetNotTimedOut);
+    test_context
+        .warp_to_slot(matched.timeout_slot() + 1)
+        .unwrap();
+    let err = run(&ctx, settle(address)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::RoundTimedOut);
+    let opponent_before = lamports(opponent.pubkey()).await;
+    run(&ctx, Command::CancelChallenge { challenge: address })
+        .await
+        .unwrap();
+    assert_eq!(lamports(opponent.pubkey()).await - opponent_before, stake);
+    assert_eq!(lamports(address).await, 0);
+}
+
+async fn pool_phase(ctx: &Context<TestChain>, round_id: u64) -> &'static str {
+    match run(ctx, Command::Pool { round_id }).await.unwrap() {
+        Report::Pool(report) => report.phase,
+        _ => panic!("expected a pool report"),
+    }
+}
+
+#[tokio::test]
+async fn pool_round_splits_the_losing_side() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    run(
+        &ctx,
+        Command::UpdateConfig {
+            min_bet: None,
+            max_exposure_bps: None,
+            house_edge_bps: None,
+            rake_bps: Some(500),
+        },
+    )
+    .await
+    .unwrap();
+    let players: Vec<Keypair> = (0..2).map(|_| Keypair::new()).collect();
+    let player_ctxs: Vec<_> = players
+        .iter()
+        .map(|player| Context {
+            chain: ctx.chain.clone(),
+            signer: Keypair::from_bytes(&player.to_bytes()).unwrap(),
+            program_id: ctx.program_id,
+            dry_run: false,
+        })
+        .collect();
+    let transfers: Vec<_> = players
+        .iter()
+        .map(|player| {
+            system_instruction::transfer(&ctx.signer.pubkey(), &player.pubkey(), LAMPORTS_PER_SOL)
+        })
+        .collect();
+    send(&ctx, &transfers).await.unwrap();
+    let lamports = |address: Pubkey| {
+        let chain = ctx.chain.clone();
+        async move {
+            chain
+                .get_account(&address)
+                .await
+                .unwrap()
+                .map_or(0, |account| account.lamports)
+        }
+    };
+    let join = |round_id, amount, side| Command::JoinPool {
+        round_id,
+        amount,
+        side,
+    };
+    let resolve = |round_id| Command::ResolvePool {
+        round_id,
+        vrf_keypair: Some(keypair_file(&vrf_key)),
+        oracle_keypair: vec![],
+    };
+    let open = |round_id, betting_slots| Command::OpenPool {
+        round_id,
+        betting_slots,
+    };
+    let stake = LAMPORTS_PER_SOL / 10;
+
+    // Only the authority opens rounds.
+    let err = run(&player_ctxs[0], open(1, 20)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::Unauthorized);
+    run(&ctx, open(1, 20)).await.unwrap();
+    assert_eq!(pool_phase(&ctx, 1).await, "open");
+
+    run(&ctx, join(1, 3 * stake, Side::Alive)).await.unwrap();
+    run(&player_ctxs[0], join(1, stake, Side::Dead))
+        .await
+        .unwrap();
+    run(&player_ctxs[1], join(1, 3 * stake, Side::Dead))
+        .await
+        .unwrap();
+    // A position stays on one side and grows with each stake.
+    let err = run(&player_ctxs[0], join(1, stake, Side::Alive))
+        .await
+        .unwrap_err();
+    assert_custom_error(err, CatflipError::PositionSideMismatch);
+    run(&player_ctxs[0], join(1, 2 * stake, Side::Dead))
+        .await
+        .unwrap();
+    let position = fetch_pool_position(&ctx.chain, &ctx.program_id, 1, &players[0].pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(position.amount, 3 * stake);
+
+    // Betting has to close before the box opens.
+    let err = run(&ctx, resolve(1)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::PoolPhaseMismatch);
+    let pool = fetch_pool(&ctx.chain, &ctx.program_id, 1).await.unwrap();
+    assert_eq!((pool.alive_total, pool.dead_total), (3 * stake, 6 * stake));
+    test_context.warp_to_slot(pool.lock_slot).unwrap();
+    assert_eq!(pool_phase(&ctx, 1).await, "locked");
+    let err = run(&ctx, join(1, stake, Side::Alive)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::PoolNotOpen);
+
+    // Nor before the slot hash it mixes in exists.
+    let err = run(&ctx, resolve(1)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::RandomnessNotReady);
+    test_context.warp_to_slot(pool.entropy_slot() + 1).unwrap();
+    let SlotHash::Found(slot_hash) = fetch_slot_hash(&ctx.chain, pool.entropy_slot())
+        .await
+        .unwrap()
+    else {
+        panic!("the entropy slot should be hashed");
+    };
+
+    let address = pool_address(&ctx.program_id, 1).0;
+    let Randomness::Vrf { output, .. } = vrf::prove_pool(&vrf_key, &address, &pool, &slot_hash)
+    else {
+        unreachable!()
+    };
+    let side = catflip::state::Side::of_roll(u64::from_le_bytes(output[..8].try_into().unwrap()));
+    let vault_before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    run(&ctx, resolve(1)).await.unwrap();
+    assert_eq!(pool_phase(&ctx, 1).await, "resolved");
+    let pool = fetch_pool(&ctx.chain, &ctx.program_id, 1).await.unwrap();
+    assert_eq!(pool.winning_side, side);
+    let fee = pool.total(side.opposite()) * 500 / 10_000;
+    assert_eq!(pool.fee, fee);
+    let vault_after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(vault_after.lamports - vault_before.lamports, fee);
+
+    // Anyone can claim for a player; winners split the losing pool less
+    // the fee, pro rata.
+    let pot = 9 * stake - fee;
+    let mut paid = 0;
+    for player in &players {
+        let position = pool_position_address(&ctx.program_id, &address, &player.pubkey()).0;
+        let rent = lamports(position).await;
+        let before = lamports(player.pubkey()).await;
+        run(
+            &ctx,
+            Command::ClaimPool {
+                round_id: 1,
+                player: Some(player.pubkey()),
+            },
+        )
+        .await
+        .unwrap();
+        let payout = lamports(player.pubkey()).await - before - rent;
+        let expected = match side {
+            catflip::state::Side::Dead => pot / 2,
+            catflip::state::Side::Alive => 0,
+        };
+        assert_eq!(payout, expected);
+        paid += payout;
+        assert_eq!(lamports(position).await, 0);
+    }
+    let payout = pool.payout(catflip::state::Side::Alive, 3 * stake);
+    assert_eq!(paid + payout, pot);
+
+    // The last claim closes the round and returns its rent, with any
+    // rounding dust, to the authority that opened it.
+    let authority = ctx.signer.pubkey();
+    let position = pool_position_address(&ctx.program_id, &address, &authority).0;
+    let left = lamports(address).await - payout;
+    let before = lamports(authority).await + lamports(position).await;
+    run(
+        &player_ctxs[0],
+        Command::ClaimPool {
+            round_id: 1,
+            player: Some(authority),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(lamports(address).await, 0);
+    assert_eq!(lamports(authority).await, before + payout + left);
+
+    // A round nobody resolves refunds its positions once it expires, and
+    // can't be resolved after that.
+    run(&ctx, open(2, 5)).await.unwrap();
+    run(&player_ctxs[0], join(2, stake, Side::Alive))
+        .await
+        .unwrap();
+    let pool = fetch_pool(&ctx.chain, &ctx.program_id, 2).await.unwrap();
+    test_context.warp_to_slot(pool.lock_slot).unwrap();
+    let refund = Command::RefundPool {
+        round_id: 2,
+        player: Some(players[0].pubkey()),
+    };
+    let err = run(&ctx, refund.clone()).await.unwrap_err();
+    assert_custom_error(err, CatflipError::BetNotTimedOut);
+    test_context.warp_to_slot(pool.timeout_slot() + 1).unwrap();
+    assert_eq!(pool_phase(&ctx, 2).await, "expired");
+    let err = run(&ctx, resolve(2)).await.unwrap_err();
+    assert_custom_error(err, CatflipError::PoolPhaseMismatch);
+    let before = lamports(players[0].pubkey()).await;
+    run(&ctx, refund).await.unwrap();
+    assert!(lamports(players[0].pubkey()).await - before > stake);
+    assert_eq!(lamports(pool_address(&ctx.program_id, 2).0).await, 0);
+
+    // With one side empty there's no one to win from: every position gets
+    // its stake back and the vault takes nothing.
+    run(&ctx, open(3, 5)).await.unwrap();
+    run(&player_ctxs[1], join(3, stake, Side::Dead))
+        .await
+        .unwrap();
+    let pool = fetch_pool(&ctx.chain, &ctx.program_id, 3).await.unwrap();
+    test_context.warp_to_slot(pool.entropy_slot() + 1).unwrap();
+    run(&ctx, resolve(3)).await.unwrap();
+    let pool = fetch_pool(&ctx.chain, &ctx.program_id, 3).await.unwrap();
+    assert_eq!(pool.fee, 0);
+    assert_eq!(pool.payout(catflip::state::Side::Dead, stake), stake);
+
+    // A round nobody joined closes as soon as it resolves.
+    run(&ctx, open(4, 5)).await.unwrap();
+    let pool = fetch_pool(&ctx.chain, &ctx.program_id, 4).await.unwrap();
+    test_context.warp_to_slot(pool.entropy_slot() + 1).unwrap();
+    run(&ctx, resolve(4)).await.unwrap();
+    assert_eq!(lamports(pool_address(&ctx.program_id, 4).0).await, 0);
+}
+
+#[tokio::test]
+async fn migrate_in_carries_over_legacy_stats() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+    let before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+
+    let legacy_house = Pubkey::new_unique();
+    let migrate_in = Command::MigrateIn {
+        amount: 2 * LAMPORTS_PER_SOL,
+        legacy_house,
+        total_bets: 40,
+        total_volume: 5 * LAMPORTS_PER_SOL,
+    };
+    run(&ctx, migrate_in.clone()).await.unwrap();
+    let after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(after.lamports, before.lamports + 2 * LAMPORTS_PER_SOL);
+    assert_eq!(after.vault.imported_principal, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(after.vault.total_bets, 40);
+    assert_eq!(after.vault.total_volume, 5 * LAMPORTS_PER_SOL);
+
+    let report = run(&ctx, Command::Stats).await.unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["imported_principal_lamports"], 2 * LAMPORTS_PER_SOL);
+
+    // The same house can't be counted twice; another one can be imported.
+    let again = Command::MigrateIn {
+        amount: LAMPORTS_PER_SOL,
+        legacy_house,
+        total_bets: 40,
+        total_volume: 5 * LAMPORTS_PER_SOL,
+    };
+    let err = run(&ctx, again).await.unwrap_err();
+    assert_custom_error(err, CatflipError::LegacyHouseAlreadyImported);
+    run(
+        &ctx,
+        Command::MigrateIn {
+            amount: LAMPORTS_PER_SOL,
+            legacy_house: Pubkey::new_unique(),
+            total_bets: 2,
+            total_volume: LAMPORTS_PER_SOL,
+        },
+    )
+    .await
+    .unwrap();
+    let after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(after.vault.imported_principal, 3 * LAMPORTS_PER_SOL);
+    assert_eq!(after.vault.total_bets, 42);
+
+    let stranger = Keypair::new();
+    test_context.set_account(
+        &stranger.pubkey(),
+        &AccountSharedData::new(10 * LAMPORTS_PER_SOL, 0, &system_program::ID),
+    );
+    let stranger_ctx = Context {
+        signer: stranger,
+        ..ctx
+    };
+    let err = run(&stranger_ctx, migrate_in).await.unwrap_err();
+    assert_custom_error(err, CatflipError::Unauthorized);
+}
+
+#[tokio::test]
+async fn dry_run_does_not_touch_state() {
+    let (_test_context, mut ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    ctx.dry_run = true;
+    let report = run(&ctx, unguarded_bet(LAMPORTS_PER_SOL / 10))
+        .await
+        .unwrap();
+    let round = bet_round(&report);
+    let Report::Transaction(tx) = &report else {
+        unreachable!()
+    };
+    assert!(tx.simulated);
+    assert!(tx.signature.is_none());
+    assert!(tx.error.is_none(), "{:?}", tx.logs);
+    assert!(tx.logs.iter().any(|line| line.contains("BetPlaced")));
+    assert!(ctx.chain.get_account(&round).await.unwrap().is_none());
+
+    let report = run(
+        &ctx,
+        Command::Withdraw {
+            amount: 1_000 * LAMPORTS_PER_SOL,
+        },
+    )
+    .await
+    .unwrap();
+    assert!(report.is_failure());
+
+    let vault = fetch_vault(&ctx.chain, &ctx.program_id)
+        .await
+        .unwrap()
+        .vault;
+    assert_eq!(vault.total_bets, 0);
+}
+
+#[tokio::test]
+async fn bet_settle_and_withdraw() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    let report = run(&ctx, unguarded_bet(LAMPORTS_PER_SOL / 10))
+        .await
+        .unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert!(!bet.is_settled);
+    assert_eq!(bet.stake_lamports, LAMPORTS_PER_SOL / 10);
+
+    let vrf_key = Keypair::new();
+    let settle = Command::Settle {
+        round,
+        vrf_keypair: Some(keypair_file(&vrf_key)),
+        oracle_keypair: vec![],
+    };
+    // Nothing settles until the house has registered its VRF key.
+    let err = run(&ctx, settle.clone()).await.unwrap_err();
+    assert_custom_error(err, CatflipError::InvalidVrfAccount);
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let impostor = Command::Settle {
+        round,
+        vrf_keypair: Some(keypair_file(&Keypair::new())),
+        oracle_keypair: vec![],
+    };
+    let err = run(&ctx, impostor).await.unwrap_err();
+    assert_custom_error(err, CatflipError::InvalidVrfProof);
+
+    run(&ctx, settle.clone()).await.unwrap();
+    let settled = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert!(settled.is_settled);
+    let (output, _) = vrf::prove(&vrf_key, &bet.vrf_input(&round));
+    let roll = u64::from_le_bytes(output[..8].try_into().unwrap());
+    assert_eq!(settled.is_winner, BetRound::is_winning_roll(roll));
+
+    // An identical transaction under the same blockhash would just be
+    // deduplicated by the bank.
+    test_context.last_blockhash = ctx.chain.get_latest_blockhash().await.unwrap();
+    test_context.get_new_latest_blockhash().await.unwrap();
+    let err = run(&ctx, settle).await.unwrap_err();
+    assert_custom_error(err, CatflipError::BetAlreadySettled);
+
+    let before = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(before.vault.total_bets, 1);
+    run(
+        &ctx,
+        Command::Withdraw {
+            amount: LAMPORTS_PER_SOL,
+        },
+    )
+    .await
+    .unwrap();
+    let after = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(before.lamports - after.lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn refund_after_timeout() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    let report = run(&ctx, unguarded_bet(LAMPORTS_PER_SOL / 10))
+        .await
+        .unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+
+    let err = run(&ctx, Command::Refund { round }).await.unwrap_err();
+    assert_custom_error(err, CatflipError::BetNotTimedOut);
+
+    let vault_before = fetch_vault(&ctx.chain, &ctx.program_id)
+        .await
+        .unwrap()
+        .lamports;
+    test_context
+        .warp_to_slot(bet.slot + catflip::state::BetRound::TIMEOUT_SLOTS + 1)
+        .unwrap();
+    run(&ctx, Command::Refund { round }).await.unwrap();
+
+    assert!(ctx.chain.get_account(&round).await.unwrap().is_none());
+    let vault_after = fetch_vault(&ctx.chain, &ctx.program_id)
+        .await
+        .unwrap()
+        .lamports;
+    assert_eq!(vault_before - vault_after, bet.stake_lamports);
+}
+
+#[tokio::test]
+async fn set_vrf_key_grows_vaults_created_before_it() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    // A vault as initialized before the key was stored.
+    const LEGACY_SIZE: usize = 110;
+    let vault = vault_address(&ctx.program_id).0;
+    let account = ctx.chain.get_account(&vault).await.unwrap().unwrap();
+    let mut legacy = AccountSharedData::new(account.lamports, LEGACY_SIZE, &ctx.program_id);
+    legacy.set_data_from_slice(&account.data()[..LEGACY_SIZE]);
+    test_context.set_account(&vault, &legacy);
+    let signer_before = ctx
+        .chain
+        .get_account(&ctx.signer.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+
+    // Small-order points make every proof trivially forgeable.
+    let mut identity = [0u8; 32];
+    identity[0] = 1;
+    let err = run(
+        &ctx,
+        Command::SetVrfKey {
+            key: Pubkey::from(identity),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::InvalidVrfAccount);
+
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    let grown = ctx.chain.get_account(&vault).await.unwrap().unwrap();
+    assert_eq!(grown.data.len(), Vault::SIZE);
+    let rent = Rent::default();
+    assert_eq!(
+        grown.lamports - account.lamports,
+        rent.minimum_balance(Vault::SIZE) - rent.minimum_balance(LEGACY_SIZE)
+    );
+    let signer_after = ctx
+        .chain
+        .get_account(&ctx.signer.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    assert!(signer_after < signer_before);
+
+    let state = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(state.vault.vrf_public_key, vrf_key.pubkey().to_bytes());
+    assert_eq!(state.vault.total_volume, 0);
+    let report = run(&ctx, Command::Stats).await.unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["vrf_key"], vrf_key.pubkey().to_string());
+
+    let stranger = Keypair::new();
+    test_context.set_account(
+        &stranger.pubkey(),
+        &AccountSharedData::new(LAMPORTS_PER_SOL, 0, &system_program::ID),
+    );
+    let stranger_ctx = Context {
+        signer: stranger,
+        ..ctx
+    };
+    let err = run(
+        &stranger_ctx,
+        Command::SetVrfKey {
+            key: Pubkey::new_unique(),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::Unauthorized);
+}
+
+fn settle_with_oracles(round: Pubkey, oracles: &[&Keypair]) -> Command {
+    Command::Settle {
+        round,
+        vrf_keypair: None,
+        oracle_keypair: oracles.iter().map(|oracle| keypair_file(oracle)).collect(),
+    }
+}
+
+async fn send(ctx: &Context<TestChain>, ixs: &[Instruction]) -> anyhow::Result<()> {
+    let blockhash = ctx.chain.get_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&ctx.signer.pubkey()),
+        &[&ctx.signer],
+        blockhash,
+    );
+    ctx.chain.send_transaction(&tx).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn settle_from_oracle_beacons() {
+    let (_test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    let oracles: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let keys: Vec<Pubkey> = oracles.iter().map(Keypair::pubkey).collect();
+    let invalid = [
+        (keys.clone(), 4),
+        (keys.clone(), 0),
+        (vec![keys[0], keys[0]], 1),
+        (vec![Pubkey::new_unique(); Vault::MAX_ORACLES + 1], 1),
+    ];
+    for (oracles, threshold) in invalid {
+        let err = run(&ctx, Command::SetOracles { threshold, oracles })
+            .await
+            .unwrap_err();
+        assert_custom_error(err, CatflipError::InvalidConfig);
+    }
+    run(
+        &ctx,
+        Command::SetOracles {
+            threshold: 2,
+            oracles: keys.clone(),
+        },
+    )
+    .await
+    .unwrap();
+    let report = run(&ctx, Command::Stats).await.unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["oracle_threshold"], 2);
+    assert_eq!(json["oracles"][2], keys[2].to_string());
+
+    let report = run(&ctx, unguarded_bet(LAMPORTS_PER_SOL / 10))
+        .await
+        .unwrap();
+    let round = bet_round(&report);
+    let bet = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+
+    // With oracles registered, VRF proofs no longer settle anything.
+    let vrf_key = Keypair::new();
+    run(
+        &ctx,
+        Command::SetVrfKey {
+            key: vrf_key.pubkey(),
+        },
+    )
+    .await
+    .unwrap();
+    let err = run(
+        &ctx,
+        Command::Settle {
+            round,
+            vrf_keypair: Some(keypair_file(&vrf_key)),
+            oracle_keypair: vec![],
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::RandomnessSourceMismatch);
+
+    // Only the first two oracles count: any other pair would let the
+    // submitter choose between outcomes.
+    let stranger = Keypair::new();
+    let rejected = [
+        settle_with_oracles(round, &[&oracles[0]]),
+        settle_with_oracles(round, &[&oracles[0], &oracles[0]]),
+        settle_with_oracles(round, &[&oracles[0], &stranger]),
+        settle_with_oracles(round, &[&oracles[0], &oracles[2]]),
+        settle_with_oracles(round, &[&oracles[1], &oracles[2]]),
+        settle_with_oracles(round, &[&oracles[0], &oracles[1], &oracles[2]]),
+    ];
+    for command in rejected {
+        let err = run(&ctx, command).await.unwrap_err();
+        assert_custom_error(err, CatflipError::InvalidOracleBeacons);
+    }
+
+    // Beacons for another round, over the round's address without the
+    // player's seed, and no beacons at all.
+    let other = bet.vrf_input(&Pubkey::new_unique());
+    let address_only = round.to_bytes();
+    let fulfill =
+        instructions::fulfill_randomness(&ctx.program_id, &round, &bet, Randomness::Oracles);
+    for message in [&other[..], &address_only[..]] {
+        let signed: Vec<_> = oracles[..2]
+            .iter()
+            .map(|oracle| beacons::sign(oracle, message))
+            .collect();
+        let err = send(
+            &ctx,
+            &[beacons::instruction(message, &signed), fulfill.clone()],
+        )
+        .await
+        .unwrap_err();
+        assert_custom_error(err, CatflipError::InvalidOracleBeacons);
+    }
+    let err = send(&ctx, &[fulfill]).await.unwrap_err();
+    assert_custom_error(err, CatflipError::InvalidOracleBeacons);
+
+    // Submission order doesn't change the outcome.
+    run(
+        &ctx,
+        settle_with_oracles(round, &[&oracles[1], &oracles[0]]),
+    )
+    .await
+    .unwrap();
+    let settled = fetch_bet_round(&ctx.chain, &round).await.unwrap();
+    assert!(settled.is_settled);
+    let alpha = bet.vrf_input(&round);
+    let first = beacons::sign(&oracles[0], &alpha).1;
+    let second = beacons::sign(&oracles[1], &alpha).1;
+    let entropy = hashv(&[first.as_ref(), second.as_ref()]).to_bytes();
+    let roll = u64::from_le_bytes(entropy[..8].try_into().unwrap());
+    assert_eq!(settled.is_winner, BetRound::is_winning_roll(roll));
+}
+
+#[tokio::test]
+async fn set_oracles_grows_vaults_from_the_vrf_release() {
+    let (mut test_context, ctx) = start().await;
+    init_and_fund(&ctx).await;
+
+    // A vault as it was once the VRF key was stored, before oracles.
+    const VRF_RELEASE_SIZE: usize = 142;
+    let vault = vault_address(&ctx.program_id).0;
+    let account = ctx.chain.get_account(&vault).await.unwrap().unwrap();
+    let mut legacy = AccountSharedData::new(account.lamports, VRF_RELEASE_SIZE, &ctx.program_id);
+    legacy.set_data_from_slice(&account.data()[..VRF_RELEASE_SIZE]);
+    test_context.set_account(&vault, &legacy);
+
+    let stranger = Keypair::new();
+    test_context.set_account(
+        &stranger.pubkey(),
+        &AccountSharedData::new(LAMPORTS_PER_SOL, 0, &system_program::ID),
+    );
+    let oracles = vec![Pubkey::new_unique()];
+    let stranger_ctx = Context {
+        chain: ctx.chain.clone(),
+        signer: stranger,
+        program_id: ctx.program_id,
+        dry_run: false,
+    };
+    let err = run(
+        &stranger_ctx,
+        Command::SetOracles {
+            threshold: 1,
+            oracles: oracles.clone(),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, CatflipError::Unauthorized);
+
+    run(
+        &ctx,
+        Command::SetOracles {
+            threshold: 1,
+            oracles: oracles.clone(),
+        },
+    )
+    .await
+    .unwrap();
+    let grown = ctx.chain.get_account(&vault).await.unwrap().unwrap();
+    assert_eq!(grown.data.len(), Vault::SIZE);
+    let state = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert_eq!(state.vault.oracles(), &oracles[..]);
+    assert_eq!(state.vault.oracle_threshold, 1);
+
+    // An empty set hands settlement back to the VRF key.
+    run(
+        &ctx,
+        Command::SetOracles {
+            threshold: 0,
+            oracles: vec![],
+        },
+    )
+    .await
+    .unwrap();
+    let state = fetch_vault(&ctx.chain, &ctx.program_id).await.unwrap();
+    assert!(state.vault.oracles().is_empty());
+    assert_eq!(state.vault.oracle_threshold, 0);
+}
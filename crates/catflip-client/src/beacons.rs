@@ -0,0 +1,104 @@
+//! Oracle beacons for vaults that settle with `set_oracles`.
+//!
+//! Each oracle signs the round's VRF input (`vrf_input` on the bet round,
+//! challenge or pool) with its own key; the signatures travel in one Ed25519
+//! precompile instruction that goes right before the settling instruction
+//! with `Randomness::Oracles`. Only the first `oracle_threshold` registered
+//! oracles are accepted.
+//!
+//! Oracles run as their own services, each holding only its own key;
+//! [`fetch`] asks one for its beacon over HTTP.
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+
+use crate::{ClientError, Result};
+
+/// What an oracle service answers to `GET /beacons/<round>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Beacon {
+    pub oracle: String,
+    pub signature: String,
+}
+
+/// `oracle`'s beacon for the round whose VRF input is `alpha`.
+pub fn sign(oracle: &Keypair, alpha: &[u8]) -> (Pubkey, Signature) {
+    (oracle.pubkey(), oracle.sign_message(alpha))
+}
+
+/// Asks the oracle service at `url` for its beacon for the round at `round`,
+/// whose VRF input is `alpha`. The signature is checked here so a broken
+/// oracle fails its own request rather than the whole settlement.
+pub async fn fetch(
+    http: &reqwest::Client,
+    url: &str,
+    round: &Pubkey,
+    alpha: &[u8],
+) -> Result<(Pubkey, Signature)> {
+    let oracle_error = |err: String| ClientError::Oracle(url.to_string(), err);
+    let beacon: Beacon = http
+        .get(format!("{}/beacons/{round}", url.trim_end_matches('/')))
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| oracle_error(err.to_string()))?
+        .json()
+        .await
+        .map_err(|err| oracle_error(err.to_string()))?;
+    let oracle: Pubkey = beacon
+        .oracle
+        .parse()
+        .map_err(|_| oracle_error(format!("invalid oracle key {}", beacon.oracle)))?;
+    let signature: Signature = beacon
+        .signature
+        .parse()
+        .map_err(|_| oracle_error(format!("invalid signature {}", beacon.signature)))?;
+    if !signature.verify(oracle.as_ref(), alpha) {
+        return Err(oracle_error(format!("{oracle} did not sign round {round}")));
+    }
+    Ok((oracle, signature))
+}
+
+/// The Ed25519 precompile instruction carrying `beacons` over `alpha`. The
+/// input is stored once and every signature points at it.
+pub fn instruction(alpha: &[u8], beacons: &[(Pubkey, Signature)]) -> Instruction {
+    const OFFSETS_LEN: usize = 14;
+    let message_offset = 2 + beacons.len() * OFFSETS_LEN;
+    let keys_offset = message_offset + alpha.len();
+    let signatures_offset = keys_offset + beacons.len() * 32;
+
+    let mut data = vec![beacons.len() as u8, 0];
+    for index in 0..beacons.len() {
+        let offsets = [
+            signatures_offset + index * 64,
+            u16::MAX as usize,
+            keys_offset + index * 32,
+            u16::MAX as usize,
+            message_offset,
+            alpha.len(),
+            u16::MAX as usize,
+        ];
+        for offset in offsets {
+            data.extend(u16::try_from(offset).unwrap().to_le_bytes());
+        }
+    }
+    data.extend(alpha);
+    for (oracle, _) in beacons {
+        data.extend(oracle.as_ref());
+    }
+    for (_, signature) in beacons {
+        data.extend(signature.as_ref());
+    }
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: vec![],
+        data,
+    }
+}
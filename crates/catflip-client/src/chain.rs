@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+    transaction_context::TransactionReturnData,
+};
+
+use crate::Result;
+
+/// Result of running a transaction through the bank without committing it.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    pub units_consumed: u64,
+    /// What the last instruction to call `set_return_data` left behind.
+    pub return_data: Option<TransactionReturnData>,
+}
+
+/// The handful of cluster calls the catflip tools need.
+#[async_trait]
+pub trait Chain: Send + Sync {
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>>;
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+
+    async fn get_slot(&self) -> Result<u64>;
+
+    async fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    async fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation>;
+
+    /// Sends the transaction and waits until it is confirmed.
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature>;
+}
+
+#[async_trait]
+impl Chain for RpcClient {
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .get_account_with_commitment(address, self.commitment())
+            .await?
+            .value)
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            with_context: None,
+        };
+        Ok(self
+            .get_program_accounts_with_config(program_id, config)
+            .await?)
+    }
+
+    async fn get_slot(&self) -> Result<u64> {
+        Ok(RpcClient::get_slot(self).await?)
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_latest_blockhash(self).await?)
+    }
+
+    async fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation> {
+        let result = RpcClient::simulate_transaction(self, transaction)
+            .await?
+            .value;
+        Ok(Simulation {
+            err: result.err,
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed.unwrap_or_default(),
+            return_data: result.return_data.and_then(|return_data| {
+                Some(TransactionReturnData {
+                    program_id: return_data.program_id.parse().ok()?,
+                    data: STANDARD.decode(return_data.data.0).ok()?,
+                })
+            }),
+        })
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        Ok(self.send_and_confirm_transaction(transaction).await?)
+    }
+}
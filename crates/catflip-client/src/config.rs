@@ -0,0 +1,68 @@
+use solana_cli_config::{Config, CONFIG_FILE};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair},
+};
+use std::str::FromStr;
+
+use crate::{ClientError, Result};
+
+/// Connection settings resolved from explicit flags, falling back to the
+/// Solana CLI config the same way `solana` itself does.
+pub struct Settings {
+    pub url: String,
+    pub keypair: Keypair,
+    pub commitment: CommitmentConfig,
+}
+
+impl Settings {
+    pub fn load(
+        config_file: Option<&str>,
+        url: Option<&str>,
+        keypair: Option<&str>,
+    ) -> Result<Self> {
+        let config = load_config(config_file);
+
+        let url = normalize_url(url.unwrap_or(&config.json_rpc_url));
+        let keypair_path = keypair.unwrap_or(&config.keypair_path);
+        let keypair = read_keypair(keypair_path)?;
+        let commitment = CommitmentConfig::from_str(&config.commitment).unwrap_or_default();
+
+        Ok(Self {
+            url,
+            keypair,
+            commitment,
+        })
+    }
+}
+
+pub fn read_keypair(path: &str) -> Result<Keypair> {
+    read_keypair_file(path)
+        .map_err(|err| ClientError::Config(format!("reading keypair {path}: {err}")))
+}
+
+/// RPC URL and commitment for read-only tools that never sign anything.
+pub fn rpc_settings(config_file: Option<&str>, url: Option<&str>) -> (String, CommitmentConfig) {
+    let config = load_config(config_file);
+    let url = normalize_url(url.unwrap_or(&config.json_rpc_url));
+    let commitment = CommitmentConfig::from_str(&config.commitment).unwrap_or_default();
+    (url, commitment)
+}
+
+fn load_config(config_file: Option<&str>) -> Config {
+    match config_file.or(CONFIG_FILE.as_deref()) {
+        Some(path) => Config::load(path).unwrap_or_default(),
+        None => Config::default(),
+    }
+}
+
+fn normalize_url(url_or_moniker: &str) -> String {
+    match url_or_moniker {
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "l" | "localhost" => "http://localhost:8899",
+        url => url,
+    }
+    .to_string()
+}
@@ -0,0 +1,63 @@
+use solana_sdk::{pubkey::Pubkey, transaction::TransactionError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error(transparent)]
+    Rpc(Box<solana_client::client_error::ClientError>),
+
+    #[cfg(feature = "program-test")]
+    #[error(transparent)]
+    Banks(Box<solana_banks_client::BanksClientError>),
+
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("account {0} could not be decoded: {1}")]
+    InvalidAccountData(Pubkey, String),
+
+    #[error("return data could not be decoded: {0}")]
+    InvalidReturnData(String),
+
+    #[error("transaction failed: {0}")]
+    Transaction(TransactionError),
+
+    #[error("oracle {0}: {1}")]
+    Oracle(String, String),
+
+    #[error("{0}")]
+    Config(String),
+}
+
+impl ClientError {
+    /// The on-chain error behind this failure, if the transaction reached the
+    /// runtime at all.
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        match self {
+            ClientError::Rpc(err) => err.get_transaction_error(),
+            #[cfg(feature = "program-test")]
+            ClientError::Banks(err) => match err.as_ref() {
+                solana_banks_client::BanksClientError::TransactionError(err)
+                | solana_banks_client::BanksClientError::SimulationError { err, .. } => {
+                    Some(err.clone())
+                }
+                _ => None,
+            },
+            ClientError::Transaction(err) => Some(err.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+#[cfg(feature = "program-test")]
+impl From<solana_banks_client::BanksClientError> for ClientError {
+    fn from(err: solana_banks_client::BanksClientError) -> Self {
+        ClientError::Banks(Box::new(err))
+    }
+}
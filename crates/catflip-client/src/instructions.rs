@@ -0,0 +1,720 @@
+//! Instruction builders for every catflip entrypoint.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use catflip::{
+    instructions::{BetGuards, Randomness},
+    state::{BatchPlan, BetRound, Challenge, Side},
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program, sysvar};
+
+use crate::pda::{
+    bet_batch_address, bet_round_address, challenge_address, legacy_import_address,
+    player_balance_address, pool_address, pool_position_address, session_address, vault_address,
+};
+
+fn instruction(
+    program_id: &Pubkey,
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn initialize(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    min_bet_lamports: u64,
+    max_exposure_bps: u16,
+    house_edge_bps: u16,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::Initialize {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::Initialize {
+            min_bet_lamports,
+            max_exposure_bps,
+            house_edge_bps,
+        },
+    )
+}
+
+pub fn fund_vault(program_id: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::FundVault {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::FundVault { amount },
+    )
+}
+
+pub fn withdraw(program_id: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::Withdraw {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+        },
+        catflip::instruction::Withdraw { amount },
+    )
+}
+
+/// Moves `amount` lamports from the authority into the vault as imported
+/// principal and adds the lifetime stats of the legacy house whose vault is
+/// `legacy_house` to the vault's. Each legacy house can be imported once.
+pub fn migrate_in(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    legacy_house: &Pubkey,
+    amount: u64,
+    total_bets: u64,
+    total_volume: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::MigrateIn {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+            legacy_house: *legacy_house,
+            legacy_import: legacy_import_address(program_id, legacy_house).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::MigrateIn {
+            amount,
+            total_bets,
+            total_volume,
+        },
+    )
+}
+
+pub fn set_pause(program_id: &Pubkey, authority: &Pubkey, is_paused: bool) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SetPause {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+        },
+        catflip::instruction::SetPause { is_paused },
+    )
+}
+
+pub fn set_limits(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    min_bet_lamports: u64,
+    max_exposure_bps: u16,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SetLimits {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+        },
+        catflip::instruction::SetLimits {
+            min_bet_lamports,
+            max_exposure_bps,
+        },
+    )
+}
+
+pub fn set_edge(program_id: &Pubkey, authority: &Pubkey, house_edge_bps: u16) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SetEdge {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+        },
+        catflip::instruction::SetEdge { house_edge_bps },
+    )
+}
+
+pub fn set_rake(program_id: &Pubkey, authority: &Pubkey, rake_bps: u16) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SetRake {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+        },
+        catflip::instruction::SetRake { rake_bps },
+    )
+}
+
+pub fn set_vrf_key(program_id: &Pubkey, authority: &Pubkey, vrf_key: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SetVrfKey {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::SetVrfKey {
+            vrf_public_key: vrf_key.to_bytes(),
+        },
+    )
+}
+
+/// Beacons from the first `threshold` of `oracles` settle bets from then on;
+/// no oracles and a zero threshold go back to the VRF key.
+pub fn set_oracles(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    oracles: &[Pubkey],
+    threshold: u8,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SetOracles {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::SetOracles {
+            oracles: oracles.to_vec(),
+            threshold,
+        },
+    )
+}
+
+/// `slot` must be the slot the transaction executes in, see
+/// [`bet_round_address`]. `player_seed` goes into the round's VRF input, see
+/// [`crate::vrf::player_seed`]. `guards` can come from a
+/// [`crate::views::quote_bet`] taken just before.
+pub fn bet(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    slot: u64,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Instruction {
+    place_bet(
+        program_id,
+        player,
+        slot,
+        amount_lamports,
+        player_seed,
+        guards,
+        false,
+    )
+}
+
+/// [`bet`], staked from the player's balance instead of their wallet.
+pub fn bet_from_balance(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    slot: u64,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Instruction {
+    place_bet(
+        program_id,
+        player,
+        slot,
+        amount_lamports,
+        player_seed,
+        guards,
+        true,
+    )
+}
+
+fn place_bet(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    slot: u64,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+    from_balance: bool,
+) -> Instruction {
+    // The Switchboard accounts are not read by the program yet; the system
+    // program stands in for all of them.
+    let placeholder = system_program::ID;
+    instruction(
+        program_id,
+        catflip::accounts::Bet {
+            player: *player,
+            vault: vault_address(program_id).0,
+            bet_round: bet_round_address(program_id, player, slot).0,
+            player_balance: from_balance.then(|| player_balance_address(program_id, player).0),
+            vrf: placeholder,
+            oracle_queue: placeholder,
+            queue_authority: placeholder,
+            data_buffer: placeholder,
+            permission: placeholder,
+            escrow: placeholder,
+            payer_wallet: placeholder,
+            payer_authority: placeholder,
+            recent_blockhashes: placeholder,
+            program_state: placeholder,
+            token_program: placeholder,
+            system_program: system_program::ID,
+            switchboard_program: placeholder,
+        },
+        catflip::instruction::Bet {
+            amount_lamports,
+            player_seed,
+            guards,
+        },
+    )
+}
+
+/// Lets `session_key` bet for `player`; `allowed` combines the permission
+/// bits on [`catflip::state::Session`]. With `Session::BET` the player's
+/// wallet pays `max_stake` into the session now.
+pub fn create_session(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    session_key: &Pubkey,
+    expires_slot: u64,
+    max_stake: u64,
+    allowed: u8,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::CreateSession {
+            player: *player,
+            session: session_address(program_id, player, session_key).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::CreateSession {
+            session_key: *session_key,
+            expires_slot,
+            max_stake,
+            allowed,
+        },
+    )
+}
+
+pub fn revoke_session(program_id: &Pubkey, player: &Pubkey, session_key: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::RevokeSession {
+            player: *player,
+            session: session_address(program_id, player, session_key).0,
+        },
+        catflip::instruction::RevokeSession {},
+    )
+}
+
+/// [`bet`] for `player`, signed by their session key. `from_balance` stakes
+/// from the player's balance, otherwise from the wallet stake held by the
+/// session.
+#[allow(clippy::too_many_arguments)]
+pub fn session_bet(
+    program_id: &Pubkey,
+    session_key: &Pubkey,
+    player: &Pubkey,
+    slot: u64,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+    from_balance: bool,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SessionBet {
+            session_key: *session_key,
+            player: *player,
+            session: session_address(program_id, player, session_key).0,
+            vault: vault_address(program_id).0,
+            bet_round: bet_round_address(program_id, player, slot).0,
+            player_balance: from_balance.then(|| player_balance_address(program_id, player).0),
+            system_program: system_program::ID,
+        },
+        catflip::instruction::SessionBet {
+            amount_lamports,
+            player_seed,
+            guards,
+        },
+    )
+}
+
+/// Every flip of `plan` as one round at [`bet_round_address`] for `slot`,
+/// settled and refunded like a single bet.
+pub fn place_batch(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    slot: u64,
+    plan: BatchPlan,
+    player_seed: [u8; 32],
+) -> Instruction {
+    let bet_round = bet_round_address(program_id, player, slot).0;
+    instruction(
+        program_id,
+        catflip::accounts::PlaceBatch {
+            player: *player,
+            vault: vault_address(program_id).0,
+            bet_round,
+            bet_batch: bet_batch_address(program_id, &bet_round).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::PlaceBatch { plan, player_seed },
+    )
+}
+
+/// Opens a streak with a bet of `amount_lamports` from the player's wallet.
+pub fn start_streak(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    slot: u64,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::StartStreak {
+            player: *player,
+            vault: vault_address(program_id).0,
+            bet_round: bet_round_address(program_id, player, slot).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::StartStreak {
+            amount_lamports,
+            player_seed,
+            guards,
+        },
+    )
+}
+
+/// Stakes the payout of a won streak step at `round` on the next step.
+pub fn continue_streak(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    round: &Pubkey,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::ContinueStreak {
+            player: *player,
+            vault: vault_address(program_id).0,
+            bet_round: *round,
+        },
+        catflip::instruction::ContinueStreak {
+            player_seed,
+            guards,
+        },
+    )
+}
+
+pub fn cash_out(program_id: &Pubkey, player: &Pubkey, round: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::CashOut {
+            player: *player,
+            vault: vault_address(program_id).0,
+            bet_round: *round,
+        },
+        catflip::instruction::CashOut {},
+    )
+}
+
+pub fn open_challenge(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    slot: u64,
+    stake: u64,
+    side: Side,
+    player_seed: [u8; 32],
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::OpenChallenge {
+            creator: *creator,
+            vault: vault_address(program_id).0,
+            challenge: challenge_address(program_id, creator, slot).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::OpenChallenge {
+            stake,
+            side,
+            player_seed,
+        },
+    )
+}
+
+pub fn match_challenge(
+    program_id: &Pubkey,
+    opponent: &Pubkey,
+    challenge: &Pubkey,
+    player_seed: [u8; 32],
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::MatchChallenge {
+            opponent: *opponent,
+            vault: vault_address(program_id).0,
+            challenge: *challenge,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::MatchChallenge { player_seed },
+    )
+}
+
+/// Oracle beacons for a challenge are signed over its `vrf_input`, as for a
+/// round; see [`crate::state::challenge_vrf_input`].
+pub fn settle_challenge(
+    program_id: &Pubkey,
+    address: &Pubkey,
+    challenge: &Challenge,
+    randomness: Randomness,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::SettleChallenge {
+            vault: vault_address(program_id).0,
+            challenge: *address,
+            creator: challenge.creator,
+            opponent: challenge.opponent,
+            instructions: sysvar::instructions::ID,
+            slot_hashes: sysvar::slot_hashes::ID,
+        },
+        catflip::instruction::SettleChallenge { randomness },
+    )
+}
+
+/// `caller` is the creator for an unmatched challenge, anyone once a matched
+/// one has timed out.
+pub fn cancel_challenge(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    address: &Pubkey,
+    challenge: &Challenge,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::CancelChallenge {
+            caller: *caller,
+            creator: challenge.creator,
+            opponent: challenge.is_matched().then_some(challenge.opponent),
+            challenge: *address,
+        },
+        catflip::instruction::CancelChallenge {},
+    )
+}
+
+/// Opens pool round `round_id` for `betting_slots` slots (authority only).
+pub fn open_pool(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    round_id: u64,
+    betting_slots: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::OpenPool {
+            authority: *authority,
+            vault: vault_address(program_id).0,
+            pool: pool_address(program_id, round_id).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::OpenPool {
+            round_id,
+            betting_slots,
+        },
+    )
+}
+
+pub fn join_pool(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    round_id: u64,
+    side: Side,
+    amount: u64,
+    player_seed: [u8; 32],
+) -> Instruction {
+    let pool = pool_address(program_id, round_id).0;
+    instruction(
+        program_id,
+        catflip::accounts::JoinPool {
+            player: *player,
+            vault: vault_address(program_id).0,
+            pool,
+            position: pool_position_address(program_id, &pool, player).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::JoinPool {
+            side,
+            amount,
+            player_seed,
+        },
+    )
+}
+
+/// Oracle beacons for a pool round are signed over its `vrf_input`, as for a
+/// round; see [`crate::state::pool_vrf_input`]. `authority` is the vault
+/// authority, which gets the rent back of a round nobody joined.
+pub fn resolve_pool(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    round_id: u64,
+    randomness: Randomness,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::ResolvePool {
+            vault: vault_address(program_id).0,
+            pool: pool_address(program_id, round_id).0,
+            authority: *authority,
+            instructions: sysvar::instructions::ID,
+            slot_hashes: sysvar::slot_hashes::ID,
+        },
+        catflip::instruction::ResolvePool { randomness },
+    )
+}
+
+/// `authority` is the vault authority, which gets the round's rent back
+/// when this is its last position.
+pub fn claim_position(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    player: &Pubkey,
+    round_id: u64,
+) -> Instruction {
+    let pool = pool_address(program_id, round_id).0;
+    instruction(
+        program_id,
+        catflip::accounts::ClaimPosition {
+            player: *player,
+            vault: vault_address(program_id).0,
+            authority: *authority,
+            pool,
+            position: pool_position_address(program_id, &pool, player).0,
+        },
+        catflip::instruction::ClaimPosition {},
+    )
+}
+
+/// `authority` as for [`claim_position`].
+pub fn refund_position(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    player: &Pubkey,
+    round_id: u64,
+) -> Instruction {
+    let pool = pool_address(program_id, round_id).0;
+    instruction(
+        program_id,
+        catflip::accounts::RefundPosition {
+            player: *player,
+            vault: vault_address(program_id).0,
+            authority: *authority,
+            pool,
+            position: pool_position_address(program_id, &pool, player).0,
+        },
+        catflip::instruction::RefundPosition {},
+    )
+}
+
+/// Read-only; simulate it and decode the return data, see
+/// [`crate::views::quote_bet`].
+pub fn quote_bet(program_id: &Pubkey, stake: u64) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::QuoteBet {
+            vault: vault_address(program_id).0,
+        },
+        catflip::instruction::QuoteBet { stake },
+    )
+}
+
+/// Read-only, like [`quote_bet`].
+pub fn vault_status(program_id: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::VaultStatus {
+            vault: vault_address(program_id).0,
+        },
+        catflip::instruction::VaultStatus {},
+    )
+}
+
+/// For [`Randomness::Vrf`] see [`crate::vrf::prove_round`]. For
+/// [`Randomness::Oracles`] the transaction also needs
+/// [`crate::beacons::instruction`] right before this one.
+pub fn fulfill_randomness(
+    program_id: &Pubkey,
+    round: &Pubkey,
+    bet_round: &BetRound,
+    randomness: Randomness,
+) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::FulfillRandomness {
+            vault: vault_address(program_id).0,
+            bet_round: *round,
+            player: bet_round.player,
+            player_balance: round_player_balance(program_id, bet_round),
+            bet_batch: round_bet_batch(program_id, round, bet_round),
+            instructions: sysvar::instructions::ID,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::FulfillRandomness { randomness },
+    )
+}
+
+pub fn refund_timeout(program_id: &Pubkey, round: &Pubkey, bet_round: &BetRound) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::RefundTimeout {
+            player: bet_round.player,
+            vault: vault_address(program_id).0,
+            bet_round: *round,
+            player_balance: round_player_balance(program_id, bet_round),
+            bet_batch: round_bet_batch(program_id, round, bet_round),
+            system_program: system_program::ID,
+        },
+        catflip::instruction::RefundTimeout {},
+    )
+}
+
+/// The balance a round's payout or refund goes to, if it was staked from one.
+fn round_player_balance(program_id: &Pubkey, bet_round: &BetRound) -> Option<Pubkey> {
+    bet_round
+        .from_balance
+        .then(|| player_balance_address(program_id, &bet_round.player).0)
+}
+
+/// The batch account of `round`, if it is a batch.
+fn round_bet_batch(program_id: &Pubkey, round: &Pubkey, bet_round: &BetRound) -> Option<Pubkey> {
+    (bet_round.flips > 0).then(|| bet_batch_address(program_id, round).0)
+}
+
+/// Opens the player's balance on first use.
+pub fn deposit(program_id: &Pubkey, player: &Pubkey, amount: u64) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::Deposit {
+            player: *player,
+            vault: vault_address(program_id).0,
+            player_balance: player_balance_address(program_id, player).0,
+            system_program: system_program::ID,
+        },
+        catflip::instruction::Deposit { amount },
+    )
+}
+
+pub fn withdraw_balance(program_id: &Pubkey, player: &Pubkey, amount: u64) -> Instruction {
+    instruction(
+        program_id,
+        catflip::accounts::WithdrawBalance {
+            player: *player,
+            vault: vault_address(program_id).0,
+            player_balance: player_balance_address(program_id, player).0,
+        },
+        catflip::instruction::WithdrawBalance { amount },
+    )
+}
@@ -0,0 +1,22 @@
+//! Client-side helpers shared by the off-chain catflip tools.
+//!
+//! Everything here talks to the cluster through the [`Chain`] trait so the
+//! same code paths run against a JSON-RPC node and, with the `program-test`
+//! feature, against an in-process `solana-program-test` bank.
+
+pub mod beacons;
+pub mod chain;
+pub mod config;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+pub mod state;
+#[cfg(feature = "program-test")]
+pub mod testing;
+pub mod views;
+pub mod vrf;
+
+pub use chain::{Chain, Simulation};
+pub use error::ClientError;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,50 @@
+use solana_sdk::pubkey::Pubkey;
+
+pub fn vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault"], program_id)
+}
+
+/// Bet rounds are keyed by the slot the bet lands in, so callers have to
+/// predict it; `Chain::get_slot` right before sending is what the tools use.
+pub fn bet_round_address(program_id: &Pubkey, player: &Pubkey, slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bet", player.as_ref(), &slot.to_le_bytes()], program_id)
+}
+
+/// A batch's plan and results sit next to its round.
+pub fn bet_batch_address(program_id: &Pubkey, round: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"batch", round.as_ref()], program_id)
+}
+
+pub fn player_balance_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"balance", player.as_ref()], program_id)
+}
+
+pub fn session_address(program_id: &Pubkey, player: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"session", player.as_ref(), session_key.as_ref()],
+        program_id,
+    )
+}
+
+/// Challenges are keyed by their creator and the slot they were opened in,
+/// like bet rounds.
+pub fn challenge_address(program_id: &Pubkey, creator: &Pubkey, slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"challenge", creator.as_ref(), &slot.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Marks a legacy house, by its vault address, as imported by `migrate_in`.
+pub fn legacy_import_address(program_id: &Pubkey, legacy_house: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"import", legacy_house.as_ref()], program_id)
+}
+
+/// Pool rounds are numbered by the authority that opens them.
+pub fn pool_address(program_id: &Pubkey, round_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool", &round_id.to_le_bytes()], program_id)
+}
+
+pub fn pool_position_address(program_id: &Pubkey, pool: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"position", pool.as_ref(), player.as_ref()], program_id)
+}
@@ -0,0 +1,286 @@
+//! Typed account fetching for catflip state.
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use catflip::{
+    slot_hashes::{self, SlotHash},
+    state::{
+        BetBatch, BetRound, Challenge, PlayerBalance, PoolPosition, PoolRound, Session, Vault,
+    },
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{account::Account, pubkey::Pubkey, sysvar};
+
+use crate::{
+    pda::{
+        bet_batch_address, player_balance_address, pool_address, pool_position_address,
+        session_address, vault_address,
+    },
+    Chain, ClientError, Result,
+};
+
+pub fn decode<T: AccountDeserialize>(address: &Pubkey, account: &Account) -> Result<T> {
+    T::try_deserialize(&mut account.data.as_slice())
+        .map_err(|err| ClientError::InvalidAccountData(*address, err.to_string()))
+}
+
+/// The vault account together with the lamports it holds.
+pub struct VaultState {
+    pub address: Pubkey,
+    pub vault: Vault,
+    pub lamports: u64,
+}
+
+impl VaultState {
+    /// Largest stake `bet` currently accepts, mirroring the exposure check
+    /// in `bet.rs`. Lamports owed to player balances or reserved for batches
+    /// aren't bankroll.
+    pub fn max_bet(&self) -> u64 {
+        let bankroll = self.vault.bankroll(self.lamports);
+        (bankroll as u128 * self.vault.max_exposure_bps as u128 / 10_000) as u64
+    }
+}
+
+pub async fn fetch_vault(chain: &impl Chain, program_id: &Pubkey) -> Result<VaultState> {
+    let address = vault_address(program_id).0;
+    let account = chain
+        .get_account(&address)
+        .await?
+        .ok_or(ClientError::AccountNotFound(address))?;
+    Ok(VaultState {
+        address,
+        vault: decode(&address, &account)?,
+        lamports: account.lamports,
+    })
+}
+
+pub async fn fetch_bet_round(chain: &impl Chain, address: &Pubkey) -> Result<BetRound> {
+    let account = chain
+        .get_account(address)
+        .await?
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    decode(address, &account)
+}
+
+pub async fn fetch_challenge(chain: &impl Chain, address: &Pubkey) -> Result<Challenge> {
+    let account = chain
+        .get_account(address)
+        .await?
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    decode(address, &account)
+}
+
+/// Where `target` stands in the chain's `SlotHashes` sysvar, looked up the
+/// way the program looks it up.
+pub async fn fetch_slot_hash(chain: &impl Chain, target: u64) -> Result<SlotHash> {
+    let address = sysvar::slot_hashes::ID;
+    let account = chain
+        .get_account(&address)
+        .await?
+        .ok_or(ClientError::AccountNotFound(address))?;
+    Ok(slot_hashes::find(&account.data, target))
+}
+
+/// The VRF input of the matched challenge at `address`, or `None` while the
+/// hash of its entropy slot isn't in `SlotHashes`.
+pub async fn challenge_vrf_input(
+    chain: &impl Chain,
+    address: &Pubkey,
+    challenge: &Challenge,
+) -> Result<Option<[u8; 64]>> {
+    Ok(
+        match fetch_slot_hash(chain, challenge.entropy_slot()).await? {
+            SlotHash::Found(hash) => Some(challenge.vrf_input(address, &hash)),
+            SlotHash::Pending | SlotHash::AgedOut => None,
+        },
+    )
+}
+
+/// The VRF input of the locked pool round at `address`, or `None` while the
+/// hash of its entropy slot isn't in `SlotHashes`.
+pub async fn pool_vrf_input(
+    chain: &impl Chain,
+    address: &Pubkey,
+    pool: &PoolRound,
+) -> Result<Option<[u8; 64]>> {
+    Ok(match fetch_slot_hash(chain, pool.entropy_slot()).await? {
+        SlotHash::Found(hash) => Some(pool.vrf_input(address, &hash)),
+        SlotHash::Pending | SlotHash::AgedOut => None,
+    })
+}
+
+/// The plan and results of the batch round at `round`.
+pub async fn fetch_bet_batch(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    round: &Pubkey,
+) -> Result<BetBatch> {
+    let address = bet_batch_address(program_id, round).0;
+    let account = chain
+        .get_account(&address)
+        .await?
+        .ok_or(ClientError::AccountNotFound(address))?;
+    decode(&address, &account)
+}
+
+pub async fn fetch_pool(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    round_id: u64,
+) -> Result<PoolRound> {
+    let address = pool_address(program_id, round_id).0;
+    let account = chain
+        .get_account(&address)
+        .await?
+        .ok_or(ClientError::AccountNotFound(address))?;
+    decode(&address, &account)
+}
+
+/// The player's position in pool round `round_id`, or `None` if they have
+/// none or it was claimed or refunded.
+pub async fn fetch_pool_position(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    round_id: u64,
+    player: &Pubkey,
+) -> Result<Option<PoolPosition>> {
+    let pool = pool_address(program_id, round_id).0;
+    let address = pool_position_address(program_id, &pool, player).0;
+    chain
+        .get_account(&address)
+        .await?
+        .map(|account| decode(&address, &account))
+        .transpose()
+}
+
+/// The player's balance, or `None` before their first deposit.
+pub async fn fetch_player_balance(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    player: &Pubkey,
+) -> Result<Option<PlayerBalance>> {
+    let address = player_balance_address(program_id, player).0;
+    chain
+        .get_account(&address)
+        .await?
+        .map(|account| decode(&address, &account))
+        .transpose()
+}
+
+/// The session `session_key` holds for `player`, or `None` if there is none
+/// or it was revoked.
+pub async fn fetch_session(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    player: &Pubkey,
+    session_key: &Pubkey,
+) -> Result<Option<Session>> {
+    let address = session_address(program_id, player, session_key).0;
+    chain
+        .get_account(&address)
+        .await?
+        .map(|account| decode(&address, &account))
+        .transpose()
+}
+
+/// Byte offset of `BetRound::is_settled`: discriminator, player, stake,
+/// potential payout, timestamp, slot and the player's seed come first.
+pub const BET_ROUND_IS_SETTLED_OFFSET: usize = 8 + 32 + 8 + 8 + 8 + 8 + 32;
+
+/// All bet rounds that have not been settled yet. Refunded rounds are closed
+/// and therefore never show up here. Accounts that match the filters but
+/// don't decode are logged and skipped, so one bad account can't stall
+/// every other round.
+pub async fn fetch_open_bet_rounds(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+) -> Result<Vec<(Pubkey, BetRound)>> {
+    let filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &BetRound::DISCRIMINATOR)),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            BET_ROUND_IS_SETTLED_OFFSET,
+            &[0],
+        )),
+    ];
+    Ok(chain
+        .get_program_accounts(program_id, filters)
+        .await?
+        .into_iter()
+        .filter_map(|(address, account)| match decode(&address, &account) {
+            Ok(bet_round) => Some((address, bet_round)),
+            Err(err) => {
+                log::warn!("skipping bet round {address}: {err}");
+                None
+            }
+        })
+        .collect())
+}
+
+/// All matched challenges, settled ones being closed. Accounts that don't
+/// decode are logged and skipped, as for bet rounds.
+pub async fn fetch_matched_challenges(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+) -> Result<Vec<(Pubkey, Challenge)>> {
+    let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+        0,
+        &Challenge::DISCRIMINATOR,
+    ))];
+    Ok(chain
+        .get_program_accounts(program_id, filters)
+        .await?
+        .into_iter()
+        .filter_map(
+            |(address, account)| match decode::<Challenge>(&address, &account) {
+                Ok(challenge) => challenge.is_matched().then_some((address, challenge)),
+                Err(err) => {
+                    log::warn!("skipping challenge {address}: {err}");
+                    None
+                }
+            },
+        )
+        .collect())
+}
+
+/// Every pool round, in any phase. Rounds that fail to decode are logged
+/// and skipped, as for challenges.
+pub async fn fetch_pools(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+) -> Result<Vec<(Pubkey, PoolRound)>> {
+    let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+        0,
+        &PoolRound::DISCRIMINATOR,
+    ))];
+    Ok(chain
+        .get_program_accounts(program_id, filters)
+        .await?
+        .into_iter()
+        .filter_map(
+            |(address, account)| match decode::<PoolRound>(&address, &account) {
+                Ok(pool) => Some((address, pool)),
+                Err(err) => {
+                    log::warn!("skipping pool round {address}: {err}");
+                    None
+                }
+            },
+        )
+        .collect())
+}
+
+/// Every position still open in the pool round at `pool`.
+pub async fn fetch_pool_positions(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    pool: &Pubkey,
+) -> Result<Vec<PoolPosition>> {
+    let filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &PoolPosition::DISCRIMINATOR)),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, pool.as_ref())),
+    ];
+    chain
+        .get_program_accounts(program_id, filters)
+        .await?
+        .iter()
+        .map(|(address, account)| decode(address, account))
+        .collect()
+}
@@ -0,0 +1,136 @@
+//! In-process validator support for integration tests.
+
+use async_trait::async_trait;
+use solana_banks_client::BanksClient;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::Transaction,
+};
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+};
+
+use crate::{Chain, Result, Simulation};
+
+// Anchor's generated `entry` ties the account slice and account lifetimes
+// together, which `processor!` cannot express. Tests are short-lived, so the
+// slice is simply leaked to satisfy it.
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    catflip::entry(program_id, accounts, instruction_data)
+}
+
+/// A `ProgramTest` with the catflip program loaded as a builtin.
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program("catflip", catflip::ID, processor!(process_instruction));
+    program_test
+}
+
+/// [`Chain`] over a `BanksClient`.
+///
+/// The banks interface cannot scan accounts, so `get_program_accounts` is
+/// answered from every account the chain has sent a transaction for, with the
+/// RPC filters applied exactly as the JSON-RPC server would. Clones share
+/// that view, like two clients of the same validator.
+#[derive(Clone)]
+pub struct TestChain {
+    pub banks_client: BanksClient,
+    touched: Arc<Mutex<BTreeSet<Pubkey>>>,
+}
+
+impl TestChain {
+    pub fn new(banks_client: BanksClient) -> Self {
+        Self {
+            banks_client,
+            touched: Arc::default(),
+        }
+    }
+
+    /// Makes accounts written outside this chain visible to
+    /// `get_program_accounts`.
+    pub fn track(&self, transaction: &Transaction) {
+        self.touched
+            .lock()
+            .unwrap()
+            .extend(transaction.message.account_keys.iter().copied());
+    }
+
+    fn client(&self) -> BanksClient {
+        self.banks_client.clone()
+    }
+}
+
+#[async_trait]
+impl Chain for TestChain {
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.client().get_account(*address).await?)
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let candidates: Vec<Pubkey> = self.touched.lock().unwrap().iter().copied().collect();
+        let mut accounts = Vec::new();
+        for address in candidates {
+            let Some(account) = self.client().get_account(address).await? else {
+                continue;
+            };
+            let shared = AccountSharedData::from(account.clone());
+            if account.owner == *program_id && filters.iter().all(|filter| filter.allows(&shared)) {
+                accounts.push((address, account));
+            }
+        }
+        Ok(accounts)
+    }
+
+    async fn get_slot(&self) -> Result<u64> {
+        // Bet rounds are seeded with the bank's clock slot, not the root slot.
+        Ok(self.client().get_sysvar::<Clock>().await?.slot)
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.client().get_latest_blockhash().await?)
+    }
+
+    async fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation> {
+        let result = self
+            .client()
+            .simulate_transaction(transaction.clone())
+            .await?;
+        let (logs, units_consumed, return_data) = result
+            .simulation_details
+            .map(|details| (details.logs, details.units_consumed, details.return_data))
+            .unwrap_or_default();
+        Ok(Simulation {
+            err: result.result.and_then(|result| result.err()),
+            logs,
+            units_consumed,
+            return_data,
+        })
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        self.track(transaction);
+        self.client()
+            .process_transaction(transaction.clone())
+            .await?;
+        Ok(transaction.signatures[0])
+    }
+}
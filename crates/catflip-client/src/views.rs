@@ -0,0 +1,67 @@
+//! Answers from catflip's read-only instructions.
+//!
+//! `quote_bet` and `vault_status` compute their answer from live state and
+//! hand it back as return data, so they are simulated rather than sent. The
+//! simulation still charges its fee payer, which must therefore exist.
+
+use anchor_lang::AnchorDeserialize;
+use catflip::instructions::{BetQuote, VaultView};
+use solana_sdk::{
+    instruction::Instruction, message::Message, pubkey::Pubkey, transaction::Transaction,
+};
+
+use crate::{instructions, Chain, ClientError, Result};
+
+/// What `bet` would do with `stake` right now. Fails with the error `bet`
+/// would fail with.
+pub async fn quote_bet(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    stake: u64,
+) -> Result<BetQuote> {
+    view(
+        chain,
+        program_id,
+        payer,
+        instructions::quote_bet(program_id, stake),
+    )
+    .await
+}
+
+/// The vault's limits and liquidity.
+pub async fn vault_status(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    payer: &Pubkey,
+) -> Result<VaultView> {
+    view(
+        chain,
+        program_id,
+        payer,
+        instructions::vault_status(program_id),
+    )
+    .await
+}
+
+async fn view<T: AnchorDeserialize>(
+    chain: &impl Chain,
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    instruction: Instruction,
+) -> Result<T> {
+    let blockhash = chain.get_latest_blockhash().await?;
+    let message = Message::new_with_blockhash(&[instruction], Some(payer), &blockhash);
+    let simulation = chain
+        .simulate_transaction(&Transaction::new_unsigned(message))
+        .await?;
+    if let Some(err) = simulation.err {
+        return Err(ClientError::Transaction(err));
+    }
+    let return_data = simulation
+        .return_data
+        .filter(|return_data| return_data.program_id == *program_id)
+        .ok_or_else(|| ClientError::InvalidReturnData("no return data".into()))?;
+    T::try_from_slice(&return_data.data)
+        .map_err(|err| ClientError::InvalidReturnData(err.to_string()))
+}
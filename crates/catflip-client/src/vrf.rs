@@ -0,0 +1,113 @@
+//! ECVRF proving for the house's settlement key.
+//!
+//! VRF keys are ordinary ed25519 keypairs: the secret scalar and nonce prefix
+//! are expanded from the seed exactly as RFC 8032 does, so a Solana keypair
+//! file doubles as a VRF key and its pubkey is the key `set_vrf_key`
+//! registers. Hashing to the curve, the challenge and the output come from
+//! the program's own verifier so the two sides can't drift apart.
+
+use catflip::{
+    instructions::Randomness,
+    state::{BetRound, Challenge, PoolRound},
+    vrf,
+};
+use curve25519_dalek::{
+    constants::ED25519_BASEPOINT_POINT, edwards::CompressedEdwardsY, scalar::Scalar,
+};
+use sha2::{Digest, Sha512};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// RFC 9381 `ECVRF_prove`: the output (`beta`) and proof (`pi`) for `alpha`.
+pub fn prove(key: &Keypair, alpha: &[u8]) -> ([u8; vrf::OUTPUT_LEN], [u8; vrf::PROOF_LEN]) {
+    let public_key = key.pubkey().to_bytes();
+    let expanded = Sha512::digest(&key.to_bytes()[..32]);
+    let mut secret = [0u8; 32];
+    secret.copy_from_slice(&expanded[..32]);
+    secret[0] &= 248;
+    secret[31] &= 127;
+    secret[31] |= 64;
+    let x = Scalar::from_bytes_mod_order(secret);
+
+    let h = vrf::hash_to_curve(&public_key, alpha)
+        .expect("a hash to the curve is found within 256 attempts");
+    let h_point = CompressedEdwardsY(h)
+        .decompress()
+        .expect("hash_to_curve returns a valid point");
+    let gamma = (x * h_point).compress().to_bytes();
+
+    let nonce: [u8; 64] = Sha512::new()
+        .chain_update(&expanded[32..])
+        .chain_update(h)
+        .finalize()
+        .into();
+    let k = Scalar::from_bytes_mod_order_wide(&nonce);
+    let k_b = (k * ED25519_BASEPOINT_POINT).compress().to_bytes();
+    let k_h = (k * h_point).compress().to_bytes();
+
+    let c = vrf::challenge(&[&public_key, &h, &gamma, &k_b, &k_h]);
+    let mut c_wide = [0u8; 32];
+    c_wide[..16].copy_from_slice(&c);
+    let s = k + Scalar::from_bytes_mod_order(c_wide) * x;
+
+    let mut proof = [0u8; vrf::PROOF_LEN];
+    proof[..32].copy_from_slice(&gamma);
+    proof[32..48].copy_from_slice(&c);
+    proof[48..].copy_from_slice(s.as_bytes());
+    let output = vrf::proof_to_hash(&gamma).expect("gamma is a valid point");
+    (output, proof)
+}
+
+/// The randomness `fulfill_randomness` takes for the round at `round`.
+pub fn prove_round(key: &Keypair, round: &Pubkey, bet_round: &BetRound) -> Randomness {
+    let (output, proof) = prove(key, &bet_round.vrf_input(round));
+    Randomness::Vrf { output, proof }
+}
+
+/// The randomness `settle_challenge` takes for the challenge at `address`,
+/// with `slot_hash` the hash for its entropy slot.
+pub fn prove_challenge(
+    key: &Keypair,
+    address: &Pubkey,
+    challenge: &Challenge,
+    slot_hash: &[u8; 32],
+) -> Randomness {
+    let (output, proof) = prove(key, &challenge.vrf_input(address, slot_hash));
+    Randomness::Vrf { output, proof }
+}
+
+/// The randomness `resolve_pool` takes for the pool round at `address`, with
+/// `slot_hash` the hash for its entropy slot.
+pub fn prove_pool(
+    key: &Keypair,
+    address: &Pubkey,
+    pool: &PoolRound,
+    slot_hash: &[u8; 32],
+) -> Randomness {
+    let (output, proof) = prove(key, &pool.vrf_input(address, slot_hash));
+    Randomness::Vrf { output, proof }
+}
+
+/// A fresh seed for `bet`. The player picks it, so a house that knows its
+/// own VRF outputs still can't tell which of them a future round will use.
+pub fn player_seed() -> [u8; 32] {
+    rand::random()
+}
+
+/// Whether `key` settles step `streak` of the round at `round` as a win
+/// when it is played with `seed`; `streak` is zero outside streaks.
+pub fn wins(key: &Keypair, round: &Pubkey, seed: &[u8; 32], streak: u8) -> bool {
+    let (output, _) = prove(key, &BetRound::step_vrf_input(round, seed, streak));
+    BetRound::is_winning_roll(u64::from_le_bytes(output[..8].try_into().unwrap()))
+}
+
+/// A seed that `key` settles as a win for step `streak` of the round at
+/// `round`. Only the holder of the house's VRF key can find one; benches and
+/// tests use it to reach what happens after a win, like a streak's next step.
+pub fn winning_seed(key: &Keypair, round: &Pubkey, streak: u8) -> [u8; 32] {
+    loop {
+        let seed = player_seed();
+        if wins(key, round, &seed, streak) {
+            return seed;
+        }
+    }
+}
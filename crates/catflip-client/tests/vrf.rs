@@ -0,0 +1,93 @@
+//! Known-answer tests for the ECVRF prover and the program's verifier.
+
+use catflip::vrf;
+use catflip_client::vrf::prove;
+use solana_sdk::signer::{keypair::keypair_from_seed, Signer};
+
+/// ECVRF-EDWARDS25519-SHA512-TAI vectors from RFC 9381 appendix B.3:
+/// secret key, public key, alpha, pi and beta.
+const VECTORS: [[&str; 5]; 3] = [
+    [
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805",
+        "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae",
+    ],
+    [
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "f3141cd382dc42909d19ec5110469e4feae18300e94f304590abdced48aed5933bf0864a62558b3ed7f2fea45c92a465301b3bbf5e3e54ddf2d935be3b67926da3ef39226bbc355bdc9850112c8f4b02",
+        "eb4440665d3891d668e7e0fcaf587f1b4bd7fbfe99d0eb2211ccec90496310eb5e33821bc613efb94db5e5b54c70a848a0bef4553a41befc57663b56373a5031",
+    ],
+    [
+        "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "9bc0f79119cc5604bf02d23b4caede71393cedfbb191434dd016d30177ccbf8096bb474e53895c362d8628ee9f9ea3c0e52c7a5c691b6c18c9979866568add7a2d41b00b05081ed0f58ee5e31b3a970e",
+        "645427e5d00c62a23fb703732fa5d892940935942101e456ecca7bb217c61c452118fec1219202a0edcf038bb6373241578be7217ba85a2687f7a0310b2df19f",
+    ],
+];
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&text[index..index + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn proofs_match_the_rfc_vectors() {
+    for [secret, public, alpha, pi, beta] in VECTORS {
+        let key = keypair_from_seed(&hex(secret)).unwrap();
+        assert_eq!(key.pubkey().to_bytes().to_vec(), hex(public));
+
+        let (output, proof) = prove(&key, &hex(alpha));
+        assert_eq!(proof.to_vec(), hex(pi), "pi for alpha {alpha:?}");
+        assert_eq!(output.to_vec(), hex(beta), "beta for alpha {alpha:?}");
+    }
+}
+
+#[test]
+fn the_verifier_accepts_the_rfc_vectors_and_nothing_else() {
+    for [_, public, alpha, pi, beta] in VECTORS {
+        let public_key: [u8; 32] = hex(public).try_into().unwrap();
+        let proof: [u8; vrf::PROOF_LEN] = hex(pi).try_into().unwrap();
+        assert!(vrf::is_valid_public_key(&public_key));
+        assert_eq!(
+            vrf::verify(&public_key, &hex(alpha), &proof).map(|output| output.to_vec()),
+            Some(hex(beta))
+        );
+
+        assert_eq!(vrf::verify(&public_key, b"another input", &proof), None);
+        // Every part of the proof is bound: Gamma, c and s.
+        for index in [0, 40, 60] {
+            let mut tampered = proof;
+            tampered[index] ^= 1;
+            assert_eq!(vrf::verify(&public_key, &hex(alpha), &tampered), None);
+        }
+    }
+
+    let [_, other, alpha, pi, _] = VECTORS[1];
+    let (_, proof) = prove(
+        &keypair_from_seed(&hex(VECTORS[0][0])).unwrap(),
+        &hex(alpha),
+    );
+    assert_ne!(proof.to_vec(), hex(pi));
+    let other: [u8; 32] = hex(other).try_into().unwrap();
+    assert_eq!(vrf::verify(&other, &hex(alpha), &proof), None);
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let mut identity = [0u8; 32];
+    identity[0] = 1;
+    assert!(!vrf::is_valid_public_key(&identity));
+    // The point of order 2, (0, -1).
+    let mut order_two = [0xff; 32];
+    order_two[0] = 0xec;
+    order_two[31] = 0x7f;
+    assert!(!vrf::is_valid_public_key(&order_two));
+    assert!(!vrf::is_valid_public_key(&[0; 32]));
+}
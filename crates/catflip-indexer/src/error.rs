@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error(transparent)]
+    Rpc(Box<solana_client::client_error::ClientError>),
+
+    #[error(transparent)]
+    Client(Box<catflip_client::ClientError>),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("malformed transaction: {0}")]
+    Transaction(String),
+}
+
+impl From<solana_client::client_error::ClientError> for IndexerError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        Self::Rpc(Box::new(err))
+    }
+}
+
+impl From<catflip_client::ClientError> for IndexerError {
+    fn from(err: catflip_client::ClientError) -> Self {
+        Self::Client(Box::new(err))
+    }
+}
@@ -0,0 +1,81 @@
+use catflip_client::{state::fetch_vault, ClientError};
+use log::{info, warn};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    logs::{self, Programs},
+    source::{self, TransactionLogs},
+    vault::VaultSnapshot,
+    Result, Store,
+};
+
+pub struct Indexer {
+    pub store: Store,
+    pub programs: Programs,
+}
+
+impl Indexer {
+    pub fn new(store: Store, programs: Programs) -> Self {
+        Self { store, programs }
+    }
+
+    /// Decodes and stores one transaction. Returns `false` if it was already
+    /// indexed.
+    pub fn ingest(&mut self, tx: &TransactionLogs) -> Result<bool> {
+        let events = logs::decode(&tx.logs, &self.programs);
+        if tx.failed && !events.is_empty() {
+            warn!("{}: ignoring events from failed transaction", tx.signature);
+        }
+        self.store.record(tx, &events)
+    }
+
+    /// Pulls everything new for each configured program since the last sync,
+    /// then snapshots the catflip vault. Returns how many transactions were
+    /// newly indexed.
+    pub async fn sync(&mut self, rpc: &RpcClient) -> Result<usize> {
+        let program_ids: Vec<Pubkey> = std::iter::once(self.programs.catflip)
+            .chain(self.programs.catflip_minimal)
+            .collect();
+
+        let mut indexed = 0;
+        for program_id in program_ids {
+            let key = program_id.to_string();
+            let cursor = self.store.cursor(&key)?;
+            let transactions = source::fetch_since(rpc, &program_id, cursor.as_deref()).await?;
+
+            for tx in &transactions {
+                if self.ingest(tx)? {
+                    indexed += 1;
+                }
+            }
+            if let Some(newest) = transactions.last() {
+                self.store.set_cursor(&key, &newest.signature)?;
+                info!("{key}: indexed up to {}", newest.signature);
+            }
+        }
+
+        self.snapshot_vault(rpc).await?;
+        Ok(indexed)
+    }
+
+    async fn snapshot_vault(&mut self, rpc: &RpcClient) -> Result<()> {
+        let program_id = self.programs.catflip;
+        let slot = rpc.get_slot().await?;
+        let state = match fetch_vault(rpc, &program_id).await {
+            Ok(state) => state,
+            Err(ClientError::AccountNotFound(address)) => {
+                warn!("vault {address} does not exist yet");
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let observed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+
+        self.store
+            .record_vault(&VaultSnapshot::new(&program_id, &state, slot, observed_at))
+    }
+}
@@ -0,0 +1,25 @@
+//! Indexes catflip bets from transaction logs into SQLite.
+//!
+//! Transactions come either from RPC or from recorded `getTransaction`
+//! results. catflip is decoded from its Anchor events and catflip-minimal from
+//! its `Bet result:` log line; catflip-ultra logs nothing and can't be indexed
+//! this way. Each bet becomes one row in `bets`, and every figure in
+//! [`stats`] is a query over those rows, so a database rebuilt from the same
+//! transactions in any order reports the same numbers.
+
+pub mod error;
+pub mod indexer;
+pub mod logs;
+pub mod source;
+pub mod stats;
+pub mod store;
+pub mod vault;
+
+pub use error::IndexerError;
+pub use indexer::Indexer;
+pub use logs::Programs;
+pub use stats::{GameStats, Stats, StatsFilter, WindowStats};
+pub use store::Store;
+pub use vault::VaultSnapshot;
+
+pub type Result<T> = std::result::Result<T, IndexerError>;
@@ -0,0 +1,175 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use catflip::instructions::{BetPlaced, BetRefunded, BetSettled};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+/// Program ids whose logs are trusted. Any program can print `Program log:`
+/// lines that look like ours, so a line only counts if the runtime attributes
+/// it to one of these.
+#[derive(Clone, Debug)]
+pub struct Programs {
+    pub catflip: Pubkey,
+    pub catflip_minimal: Option<Pubkey>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Program {
+    Catflip,
+    CatflipMinimal,
+}
+
+impl Program {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Program::Catflip => "catflip",
+            Program::CatflipMinimal => "catflip-minimal",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BetEvent {
+    Placed {
+        round: Pubkey,
+        player: Pubkey,
+        stake: u64,
+        timestamp: i64,
+    },
+    Settled {
+        round: Pubkey,
+        player: Pubkey,
+        stake: u64,
+        is_winner: bool,
+        payout: u64,
+        timestamp: i64,
+    },
+    Refunded {
+        round: Pubkey,
+        player: Pubkey,
+        stake: u64,
+        timestamp: i64,
+    },
+    /// A bet placed and resolved in the same instruction, as catflip-minimal
+    /// does. It has no round account and no on-chain timestamp.
+    Resolved {
+        player: Pubkey,
+        stake: u64,
+        is_winner: bool,
+        payout: u64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgramEvent {
+    pub program: Program,
+    pub event: BetEvent,
+}
+
+/// Decodes the bet events in a transaction's log messages, in emission order.
+///
+/// catflip is read from its Anchor events only; the `msg!` lines it prints
+/// alongside them carry the same data and would double count. catflip-minimal
+/// has no events, so its `Bet result:` line is parsed instead.
+pub fn decode(logs: &[String], programs: &Programs) -> Vec<ProgramEvent> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() == Some(&programs.catflip) {
+                events.extend(decode_anchor_event(data).map(|event| ProgramEvent {
+                    program: Program::Catflip,
+                    event,
+                }));
+            }
+        } else if let Some(message) = rest.strip_prefix("log: ") {
+            if stack.last().is_some() && stack.last() == programs.catflip_minimal.as_ref() {
+                events.extend(parse_bet_result(message).map(|event| ProgramEvent {
+                    program: Program::CatflipMinimal,
+                    event,
+                }));
+            }
+        } else if let Some((id, status)) = rest.split_once(' ') {
+            let Ok(id) = Pubkey::from_str(id) else {
+                continue;
+            };
+            if status.starts_with("invoke [") {
+                stack.push(id);
+            } else if status == "success" || status.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+
+    events
+}
+
+fn decode_anchor_event(data: &str) -> Option<BetEvent> {
+    // Anchor emits one base64 chunk per event.
+    let bytes = STANDARD.decode(data.split_whitespace().next()?).ok()?;
+    if bytes.len() < 8 {
+        return None;
+    }
+    let (discriminator, mut payload) = bytes.split_at(8);
+
+    if discriminator == BetPlaced::DISCRIMINATOR {
+        let e = BetPlaced::deserialize(&mut payload).ok()?;
+        Some(BetEvent::Placed {
+            round: e.round_pubkey,
+            player: e.player,
+            stake: e.stake,
+            timestamp: e.timestamp,
+        })
+    } else if discriminator == BetSettled::DISCRIMINATOR {
+        let e = BetSettled::deserialize(&mut payload).ok()?;
+        Some(BetEvent::Settled {
+            round: e.round_pubkey,
+            player: e.player,
+            stake: e.stake,
+            is_winner: e.is_winner,
+            payout: e.payout,
+            timestamp: e.timestamp,
+        })
+    } else if discriminator == BetRefunded::DISCRIMINATOR {
+        let e = BetRefunded::deserialize(&mut payload).ok()?;
+        Some(BetEvent::Refunded {
+            round: e.round_pubkey,
+            player: e.player,
+            stake: e.stake,
+            timestamp: e.timestamp,
+        })
+    } else {
+        None
+    }
+}
+
+/// Parses `Bet result: player=<pubkey>, stake=<u64>, winner=<bool>, payout=<u64>`.
+fn parse_bet_result(message: &str) -> Option<BetEvent> {
+    let fields = message.strip_prefix("Bet result: ")?;
+    let mut player = None;
+    let mut stake = None;
+    let mut is_winner = None;
+    let mut payout = None;
+
+    for field in fields.split(", ") {
+        match field.trim().split_once('=')? {
+            ("player", value) => player = Pubkey::from_str(value).ok(),
+            ("stake", value) => stake = value.parse().ok(),
+            ("winner", value) => is_winner = value.parse().ok(),
+            ("payout", value) => payout = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    Some(BetEvent::Resolved {
+        player: player?,
+        stake: stake?,
+        is_winner: is_winner?,
+        payout: payout?,
+    })
+}
@@ -0,0 +1,146 @@
+use catflip_client::config::rpc_settings;
+use catflip_indexer::{source, stats, Indexer, Programs, StatsFilter, Store};
+use clap::{Parser, Subcommand};
+use log::{error, info};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::{path::PathBuf, time::Duration};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "catflip-indexer",
+    version,
+    about = "Index catflip bets from transaction logs into SQLite"
+)]
+struct Args {
+    /// SQLite database to write to; created if missing
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "CATFLIP_INDEXER_DB",
+        default_value = "catflip.db"
+    )]
+    db: PathBuf,
+
+    #[arg(long, default_value_t = catflip::ID)]
+    program_id: Pubkey,
+
+    /// catflip-minimal deployment to index alongside catflip
+    #[arg(long, value_name = "PUBKEY")]
+    minimal_program_id: Option<Pubkey>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Index recorded getTransaction results from JSON files
+    Ingest {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Follow the programs over RPC
+    Sync {
+        /// Solana CLI config file to take the RPC URL from
+        #[arg(short = 'C', long, value_name = "PATH")]
+        config: Option<String>,
+
+        /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+        #[arg(
+            short = 'u',
+            long,
+            value_name = "URL_OR_MONIKER",
+            env = "CATFLIP_INDEXER_URL"
+        )]
+        url: Option<String>,
+
+        /// Seconds between polls
+        #[arg(long, default_value_t = 10)]
+        poll_interval: u64,
+
+        /// Sync once and exit instead of following
+        #[arg(long)]
+        once: bool,
+    },
+    /// Print aggregate stats as JSON
+    Stats {
+        #[arg(long)]
+        program: Option<String>,
+        #[arg(long)]
+        game: Option<String>,
+        #[arg(long)]
+        player: Option<Pubkey>,
+        /// Unix timestamp, inclusive
+        #[arg(long)]
+        since: Option<i64>,
+        /// Unix timestamp, exclusive
+        #[arg(long)]
+        until: Option<i64>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    let programs = Programs {
+        catflip: args.program_id,
+        catflip_minimal: args.minimal_program_id,
+    };
+    let mut indexer = Indexer::new(Store::open(&args.db)?, programs);
+
+    match args.command {
+        Command::Ingest { files } => {
+            let mut indexed = 0;
+            for path in files {
+                for tx in source::load_fixture(&path)? {
+                    if indexer.ingest(&tx)? {
+                        indexed += 1;
+                    }
+                }
+            }
+            info!("indexed {indexed} new transactions");
+        }
+        Command::Sync {
+            config,
+            url,
+            poll_interval,
+            once,
+        } => {
+            let (url, commitment) = rpc_settings(config.as_deref(), url.as_deref());
+            let rpc = RpcClient::new_with_commitment(url, commitment);
+            loop {
+                match indexer.sync(&rpc).await {
+                    Ok(indexed) => info!("indexed {indexed} new transactions"),
+                    Err(err) if !once => error!("sync failed: {err}"),
+                    Err(err) => return Err(err.into()),
+                }
+                if once {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+            }
+        }
+        Command::Stats {
+            program,
+            game,
+            player,
+            since,
+            until,
+        } => {
+            let filter = StatsFilter {
+                program,
+                game,
+                player: player.map(|player| player.to_string()),
+                since,
+                until,
+            };
+            let stats = stats::stats(indexer.store.connection(), &filter)?;
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        }
+    }
+
+    Ok(())
+}
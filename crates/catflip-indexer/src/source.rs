@@ -0,0 +1,121 @@
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    EncodedTransaction, UiTransactionEncoding,
+};
+use std::{fs, path::Path, str::FromStr};
+
+use crate::{IndexerError, Result};
+
+/// The parts of a confirmed transaction the indexer needs.
+#[derive(Clone, Debug)]
+pub struct TransactionLogs {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Failed transactions still carry logs, including events emitted before
+    /// the failure, but none of their effects landed.
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+impl TryFrom<EncodedConfirmedTransactionWithStatusMeta> for TransactionLogs {
+    type Error = IndexerError;
+
+    fn try_from(tx: EncodedConfirmedTransactionWithStatusMeta) -> Result<Self> {
+        let signature = match &tx.transaction.transaction {
+            EncodedTransaction::Json(ui) => ui.signatures.first().cloned(),
+            encoded => encoded
+                .decode()
+                .and_then(|tx| tx.signatures.first().map(Signature::to_string)),
+        }
+        .ok_or_else(|| IndexerError::Transaction("missing signature".to_string()))?;
+
+        let meta = tx.transaction.meta.ok_or_else(|| {
+            IndexerError::Transaction(format!("{signature}: missing status meta"))
+        })?;
+        let logs = match meta.log_messages {
+            OptionSerializer::Some(logs) => logs,
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            signature,
+            slot: tx.slot,
+            block_time: tx.block_time,
+            failed: meta.err.is_some(),
+            logs,
+        })
+    }
+}
+
+/// Reads a recorded `getTransaction` result, or a JSON array of them.
+pub fn load_fixture(path: &Path) -> Result<Vec<TransactionLogs>> {
+    let text = fs::read_to_string(path)?;
+    let value: serde_json::Value = serde_json::from_str(&text)?;
+    let transactions: Vec<EncodedConfirmedTransactionWithStatusMeta> = match value {
+        serde_json::Value::Array(_) => serde_json::from_value(value)?,
+        _ => vec![serde_json::from_value(value)?],
+    };
+    transactions.into_iter().map(TryInto::try_into).collect()
+}
+
+/// Fetches every transaction touching `program_id` newer than `until`, oldest
+/// first. With no `until` the program's whole history is walked.
+pub async fn fetch_since(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    until: Option<&str>,
+) -> Result<Vec<TransactionLogs>> {
+    let until = until
+        .map(Signature::from_str)
+        .transpose()
+        .map_err(|err| IndexerError::Transaction(format!("invalid cursor signature: {err}")))?;
+
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: None,
+                    commitment: Some(rpc.commitment()),
+                },
+            )
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature).map_err(|err| {
+            IndexerError::Transaction(format!("invalid signature from RPC: {err}"))
+        })?);
+        signatures.extend(page.into_iter().map(|status| status.signature));
+    }
+
+    let mut transactions = Vec::with_capacity(signatures.len());
+    for signature in signatures.iter().rev() {
+        let signature = Signature::from_str(signature).map_err(|err| {
+            IndexerError::Transaction(format!("invalid signature from RPC: {err}"))
+        })?;
+        let tx = rpc
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(rpc.commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?;
+        transactions.push(tx.try_into()?);
+    }
+
+    Ok(transactions)
+}
@@ -0,0 +1,150 @@
+use rusqlite::{named_params, Connection, Row};
+use serde::Serialize;
+
+use crate::Result;
+
+/// Narrows a stats query. Time bounds apply to when a bet resolved, or when it
+/// was placed if it is still open, and are unix timestamps (`until` exclusive).
+#[derive(Clone, Debug, Default)]
+pub struct StatsFilter {
+    pub program: Option<String>,
+    pub game: Option<String>,
+    pub player: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+/// Aggregates over the `bets` table. Volume, payouts and everything derived
+/// from them count won and lost bets only; refunds and open bets move no
+/// value between player and house.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Stats {
+    pub bets: u64,
+    pub open: u64,
+    pub refunded: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub volume: u64,
+    pub payouts: u64,
+    /// Volume minus payouts, from the house's side.
+    pub house_pnl: i64,
+    /// Payouts over volume; `None` until something has resolved.
+    pub rtp: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GameStats {
+    pub program: String,
+    pub game: String,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+/// Stats for the `bucket`-second window starting at `start`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WindowStats {
+    pub start: i64,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+const AGGREGATES: &str = "
+    COUNT(*),
+    COUNT(*) FILTER (WHERE outcome IS NULL),
+    COUNT(*) FILTER (WHERE outcome = 'refunded'),
+    COUNT(*) FILTER (WHERE outcome = 'won'),
+    COUNT(*) FILTER (WHERE outcome = 'lost'),
+    COALESCE(SUM(stake) FILTER (WHERE outcome IN ('won', 'lost')), 0),
+    COALESCE(SUM(payout) FILTER (WHERE outcome IN ('won', 'lost')), 0)";
+
+const FILTER: &str = "
+    (:program IS NULL OR program = :program)
+    AND (:game IS NULL OR game = :game)
+    AND (:player IS NULL OR player = :player)
+    AND (:since IS NULL OR COALESCE(resolved_at, placed_at) >= :since)
+    AND (:until IS NULL OR COALESCE(resolved_at, placed_at) < :until)";
+
+pub fn stats(conn: &Connection, filter: &StatsFilter) -> Result<Stats> {
+    let sql = format!("SELECT {AGGREGATES} FROM bets WHERE {FILTER}");
+    Ok(conn.query_row(
+        &sql,
+        named_params! {
+            ":program": filter.program,
+            ":game": filter.game,
+            ":player": filter.player,
+            ":since": filter.since,
+            ":until": filter.until,
+        },
+        |row| stats_from_row(row, 0),
+    )?)
+}
+
+/// [`stats`] split by program and game.
+pub fn by_game(conn: &Connection, filter: &StatsFilter) -> Result<Vec<GameStats>> {
+    let sql = format!(
+        "SELECT program, game, {AGGREGATES} FROM bets WHERE {FILTER}
+         GROUP BY program, game ORDER BY program, game"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":program": filter.program,
+            ":game": filter.game,
+            ":player": filter.player,
+            ":since": filter.since,
+            ":until": filter.until,
+        },
+        |row| {
+            Ok(GameStats {
+                program: row.get(0)?,
+                game: row.get(1)?,
+                stats: stats_from_row(row, 2)?,
+            })
+        },
+    )?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// [`stats`] in consecutive `bucket`-second windows, oldest first. Windows
+/// with no bets are left out.
+pub fn series(conn: &Connection, filter: &StatsFilter, bucket: i64) -> Result<Vec<WindowStats>> {
+    let sql = format!(
+        "SELECT COALESCE(resolved_at, placed_at) / :bucket * :bucket AS start, {AGGREGATES}
+         FROM bets WHERE COALESCE(resolved_at, placed_at) IS NOT NULL AND {FILTER}
+         GROUP BY start ORDER BY start"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":bucket": bucket,
+            ":program": filter.program,
+            ":game": filter.game,
+            ":player": filter.player,
+            ":since": filter.since,
+            ":until": filter.until,
+        },
+        |row| {
+            Ok(WindowStats {
+                start: row.get(0)?,
+                stats: stats_from_row(row, 1)?,
+            })
+        },
+    )?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+fn stats_from_row(row: &Row, first: usize) -> rusqlite::Result<Stats> {
+    let volume: u64 = row.get(first + 5)?;
+    let payouts: u64 = row.get(first + 6)?;
+    Ok(Stats {
+        bets: row.get(first)?,
+        open: row.get(first + 1)?,
+        refunded: row.get(first + 2)?,
+        wins: row.get(first + 3)?,
+        losses: row.get(first + 4)?,
+        volume,
+        payouts,
+        house_pnl: volume as i64 - payouts as i64,
+        rtp: (volume > 0).then(|| payouts as f64 / volume as f64),
+    })
+}
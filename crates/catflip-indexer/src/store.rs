@@ -0,0 +1,324 @@
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::Path;
+
+use crate::{
+    logs::{BetEvent, ProgramEvent},
+    source::TransactionLogs,
+    vault::VaultSnapshot,
+    Result,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature  TEXT PRIMARY KEY,
+    slot       INTEGER NOT NULL,
+    block_time INTEGER,
+    failed     INTEGER NOT NULL
+);
+
+-- One row per bet. catflip bets are keyed by their round account and filled
+-- in by up to two transactions; catflip-minimal bets resolve in one and are
+-- keyed by signature and position.
+CREATE TABLE IF NOT EXISTS bets (
+    bet_id             TEXT PRIMARY KEY,
+    program            TEXT NOT NULL,
+    game               TEXT NOT NULL,
+    player             TEXT NOT NULL,
+    stake              INTEGER NOT NULL,
+    placed_signature   TEXT,
+    placed_slot        INTEGER,
+    placed_at          INTEGER,
+    resolved_signature TEXT,
+    resolved_slot      INTEGER,
+    resolved_at        INTEGER,
+    outcome            TEXT CHECK (outcome IN ('won', 'lost', 'refunded')),
+    payout             INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS bets_player ON bets (player);
+CREATE INDEX IF NOT EXISTS bets_resolved_at ON bets (resolved_at);
+
+CREATE TABLE IF NOT EXISTS cursors (
+    program_id TEXT PRIMARY KEY,
+    signature  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS vault_snapshots (
+    program_id       TEXT NOT NULL,
+    slot             INTEGER NOT NULL,
+    observed_at      INTEGER NOT NULL,
+    address          TEXT NOT NULL,
+    lamports         INTEGER NOT NULL,
+    max_bet          INTEGER NOT NULL,
+    is_paused        INTEGER NOT NULL,
+    min_bet_lamports INTEGER NOT NULL,
+    max_exposure_bps INTEGER NOT NULL,
+    house_edge_bps   INTEGER NOT NULL,
+    total_volume     INTEGER NOT NULL,
+    total_bets       INTEGER NOT NULL,
+    total_wins       INTEGER NOT NULL,
+    PRIMARY KEY (program_id, slot)
+);
+";
+
+/// Every catflip variant currently plays the same double-or-nothing flip.
+const COIN_FLIP: &str = "coinflip";
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn into_connection(self) -> Connection {
+        self.conn
+    }
+
+    /// Records a transaction and applies its events. Returns `false` without
+    /// touching anything if the signature was already ingested.
+    ///
+    /// Each event only writes the columns it owns, so transactions can arrive
+    /// in any order and still produce the same rows.
+    pub fn record(&mut self, tx: &TransactionLogs, events: &[ProgramEvent]) -> Result<bool> {
+        let db = self.conn.transaction()?;
+        let inserted = db.execute(
+            "INSERT INTO transactions (signature, slot, block_time, failed)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (signature) DO NOTHING",
+            params![tx.signature, tx.slot, tx.block_time, tx.failed],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        if !tx.failed {
+            for (position, event) in events.iter().enumerate() {
+                apply(&db, tx, position, event)?;
+            }
+        }
+
+        db.commit()?;
+        Ok(true)
+    }
+
+    pub fn cursor(&self, program_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT signature FROM cursors WHERE program_id = ?1",
+                [program_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn set_cursor(&self, program_id: &str, signature: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO cursors (program_id, signature) VALUES (?1, ?2)
+             ON CONFLICT (program_id) DO UPDATE SET signature = excluded.signature",
+            [program_id, signature],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_vault(&self, snapshot: &VaultSnapshot) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO vault_snapshots (program_id, slot, observed_at, address, lamports,
+                                          max_bet, is_paused, min_bet_lamports, max_exposure_bps,
+                                          house_edge_bps, total_volume, total_bets, total_wins)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT (program_id, slot) DO NOTHING",
+            params![
+                snapshot.program_id,
+                snapshot.slot,
+                snapshot.observed_at,
+                snapshot.address,
+                snapshot.lamports,
+                snapshot.max_bet,
+                snapshot.is_paused,
+                snapshot.min_bet_lamports,
+                snapshot.max_exposure_bps,
+                snapshot.house_edge_bps,
+                snapshot.total_volume,
+                snapshot.total_bets,
+                snapshot.total_wins,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn apply(
+    db: &Transaction,
+    tx: &TransactionLogs,
+    position: usize,
+    event: &ProgramEvent,
+) -> Result<()> {
+    let program = event.program.as_str();
+
+    match &event.event {
+        BetEvent::Placed {
+            round,
+            player,
+            stake,
+            timestamp,
+        } => {
+            db.execute(
+                "INSERT INTO bets (bet_id, program, game, player, stake,
+                                   placed_signature, placed_slot, placed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (bet_id) DO UPDATE SET
+                     placed_signature = excluded.placed_signature,
+                     placed_slot = excluded.placed_slot,
+                     placed_at = excluded.placed_at",
+                params![
+                    round.to_string(),
+                    program,
+                    COIN_FLIP,
+                    player.to_string(),
+                    stake,
+                    tx.signature,
+                    tx.slot,
+                    timestamp,
+                ],
+            )?;
+        }
+        BetEvent::Settled {
+            round,
+            player,
+            stake,
+            is_winner,
+            payout,
+            timestamp,
+        } => {
+            resolve(
+                db,
+                &round.to_string(),
+                program,
+                &player.to_string(),
+                *stake,
+                tx,
+                Some(*timestamp),
+                outcome(*is_winner),
+                *payout,
+            )?;
+        }
+        BetEvent::Refunded {
+            round,
+            player,
+            stake,
+            timestamp,
+        } => {
+            resolve(
+                db,
+                &round.to_string(),
+                program,
+                &player.to_string(),
+                *stake,
+                tx,
+                Some(*timestamp),
+                "refunded",
+                *stake,
+            )?;
+        }
+        BetEvent::Resolved {
+            player,
+            stake,
+            is_winner,
+            payout,
+        } => {
+            let bet_id = format!("{}:{position}", tx.signature);
+            db.execute(
+                "INSERT INTO bets (bet_id, program, game, player, stake,
+                                   placed_signature, placed_slot, placed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (bet_id) DO NOTHING",
+                params![
+                    bet_id,
+                    program,
+                    COIN_FLIP,
+                    player.to_string(),
+                    stake,
+                    tx.signature,
+                    tx.slot,
+                    tx.block_time,
+                ],
+            )?;
+            resolve(
+                db,
+                &bet_id,
+                program,
+                &player.to_string(),
+                *stake,
+                tx,
+                tx.block_time,
+                outcome(*is_winner),
+                *payout,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn resolve(
+    db: &Transaction,
+    bet_id: &str,
+    program: &str,
+    player: &str,
+    stake: u64,
+    tx: &TransactionLogs,
+    resolved_at: Option<i64>,
+    outcome: &str,
+    payout: u64,
+) -> Result<()> {
+    db.execute(
+        "INSERT INTO bets (bet_id, program, game, player, stake,
+                           resolved_signature, resolved_slot, resolved_at, outcome, payout)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT (bet_id) DO UPDATE SET
+             resolved_signature = excluded.resolved_signature,
+             resolved_slot = excluded.resolved_slot,
+             resolved_at = excluded.resolved_at,
+             outcome = excluded.outcome,
+             payout = excluded.payout",
+        params![
+            bet_id,
+            program,
+            COIN_FLIP,
+            player,
+            stake,
+            tx.signature,
+            tx.slot,
+            resolved_at,
+            outcome,
+            payout,
+        ],
+    )?;
+    Ok(())
+}
+
+fn outcome(is_winner: bool) -> &'static str {
+    if is_winner {
+        "won"
+    } else {
+        "lost"
+    }
+}
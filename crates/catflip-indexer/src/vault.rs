@@ -0,0 +1,75 @@
+use catflip_client::state::VaultState;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::Result;
+
+/// The catflip vault as read at `slot`. Sync records one per pass so readers
+/// of the database can show current limits and liquidity without RPC.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct VaultSnapshot {
+    pub program_id: String,
+    pub slot: u64,
+    /// Unix time the snapshot was taken at, by the indexer's clock.
+    pub observed_at: i64,
+    pub address: String,
+    pub lamports: u64,
+    pub max_bet: u64,
+    pub is_paused: bool,
+    pub min_bet_lamports: u64,
+    pub max_exposure_bps: u16,
+    pub house_edge_bps: u16,
+    pub total_volume: u64,
+    pub total_bets: u64,
+    pub total_wins: u64,
+}
+
+impl VaultSnapshot {
+    pub fn new(program_id: &Pubkey, state: &VaultState, slot: u64, observed_at: i64) -> Self {
+        Self {
+            program_id: program_id.to_string(),
+            slot,
+            observed_at,
+            address: state.address.to_string(),
+            lamports: state.lamports,
+            max_bet: state.max_bet(),
+            is_paused: state.vault.is_paused,
+            min_bet_lamports: state.vault.min_bet_lamports,
+            max_exposure_bps: state.vault.max_exposure_bps,
+            house_edge_bps: state.vault.house_edge_bps,
+            total_volume: state.vault.total_volume,
+            total_bets: state.vault.total_bets,
+            total_wins: state.vault.total_wins,
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            program_id: row.get("program_id")?,
+            slot: row.get("slot")?,
+            observed_at: row.get("observed_at")?,
+            address: row.get("address")?,
+            lamports: row.get("lamports")?,
+            max_bet: row.get("max_bet")?,
+            is_paused: row.get("is_paused")?,
+            min_bet_lamports: row.get("min_bet_lamports")?,
+            max_exposure_bps: row.get("max_exposure_bps")?,
+            house_edge_bps: row.get("house_edge_bps")?,
+            total_volume: row.get("total_volume")?,
+            total_bets: row.get("total_bets")?,
+            total_wins: row.get("total_wins")?,
+        })
+    }
+}
+
+/// Most recent snapshot for `program_id`.
+pub fn latest(conn: &Connection, program_id: &str) -> Result<Option<VaultSnapshot>> {
+    Ok(conn
+        .query_row(
+            "SELECT * FROM vault_snapshots WHERE program_id = ?1 ORDER BY slot DESC LIMIT 1",
+            [program_id],
+            VaultSnapshot::from_row,
+        )
+        .optional()?)
+}
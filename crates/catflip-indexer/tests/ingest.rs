@@ -0,0 +1,180 @@
+use catflip_indexer::{
+    logs::{self, BetEvent, Program},
+    source::{self, TransactionLogs},
+    stats, Indexer, Programs, Stats, StatsFilter, Store,
+};
+use solana_sdk::{pubkey, pubkey::Pubkey};
+use std::{fs, path::Path};
+
+const MINIMAL: Pubkey = pubkey!("2wCZy4ecVjYhFx9Wr5tQCv1AfWXm3s2XWFsoGrd73Htg");
+const ALICE: &str = "GgBuCkRrrVhzSiPtLHz7mJ2Kn8u67gL82a3J2PBNM8RX";
+const BOB: &str = "3yyVBQSeSTMNuBQyzMi1QD5NLbasNFn5miaHMPAnHDzj";
+const ROUND_1: &str = "AQgiWKV9A8zPiFvy5D6iRxLHTnv36sKixCdXa9eNuNNe";
+
+fn programs() -> Programs {
+    Programs {
+        catflip: catflip::ID,
+        catflip_minimal: Some(MINIMAL),
+    }
+}
+
+fn fixtures() -> Vec<TransactionLogs> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .flat_map(|path| source::load_fixture(path).unwrap())
+        .collect()
+}
+
+fn index(transactions: &[TransactionLogs]) -> Indexer {
+    let mut indexer = Indexer::new(Store::open_in_memory().unwrap(), programs());
+    for tx in transactions {
+        assert!(indexer.ingest(tx).unwrap());
+    }
+    indexer
+}
+
+fn rows(indexer: &Indexer) -> Vec<(String, String, u64, Option<String>, u64)> {
+    let conn = indexer.store.connection();
+    let mut stmt = conn
+        .prepare("SELECT bet_id, player, stake, outcome, payout FROM bets ORDER BY bet_id")
+        .unwrap();
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    rows
+}
+
+fn all_stats(indexer: &Indexer) -> Stats {
+    stats::stats(indexer.store.connection(), &StatsFilter::default()).unwrap()
+}
+
+#[test]
+fn decodes_anchor_events_and_minimal_logs() {
+    let transactions = fixtures();
+
+    let placed = logs::decode(&transactions[0].logs, &programs());
+    assert_eq!(placed.len(), 1, "msg! duplicates must not be counted");
+    assert_eq!(placed[0].program, Program::Catflip);
+    assert!(matches!(
+        placed[0].event,
+        BetEvent::Placed { round, stake: 100_000_000, .. } if round.to_string() == ROUND_1
+    ));
+
+    let minimal = logs::decode(&transactions[7].logs, &programs());
+    assert_eq!(minimal.len(), 1);
+    assert_eq!(minimal[0].program, Program::CatflipMinimal);
+    assert!(matches!(
+        minimal[0].event,
+        BetEvent::Resolved {
+            is_winner: true,
+            payout: 196_000_000,
+            ..
+        }
+    ));
+
+    // Lookalike lines from another program, including after a CPI into
+    // catflip-minimal returns, are not ours.
+    assert!(logs::decode(&transactions[9].logs, &programs()).is_empty());
+}
+
+#[test]
+fn indexes_fixtures_into_normalized_rows() {
+    let indexer = index(&fixtures());
+
+    let rows = rows(&indexer);
+    assert_eq!(rows.len(), 5, "{rows:#?}");
+    let round_1 = rows.iter().find(|row| row.0 == ROUND_1).unwrap();
+    assert_eq!(round_1.1, ALICE);
+    assert_eq!(round_1.3.as_deref(), Some("won"));
+    assert_eq!(round_1.4, 196_000_000);
+    assert!(rows.iter().all(|row| row.3.is_some()));
+
+    assert_eq!(
+        all_stats(&indexer),
+        Stats {
+            bets: 5,
+            open: 0,
+            refunded: 1,
+            wins: 2,
+            losses: 2,
+            volume: 550_000_000,
+            payouts: 392_000_000,
+            house_pnl: 158_000_000,
+            rtp: Some(392.0 / 550.0),
+        }
+    );
+
+    let bob = stats::stats(
+        indexer.store.connection(),
+        &StatsFilter {
+            player: Some(BOB.to_string()),
+            ..StatsFilter::default()
+        },
+    )
+    .unwrap();
+    assert_eq!((bob.bets, bob.refunded, bob.losses), (2, 1, 1));
+    assert_eq!(bob.house_pnl, 300_000_000);
+
+    let catflip_only = stats::stats(
+        indexer.store.connection(),
+        &StatsFilter {
+            program: Some("catflip".to_string()),
+            ..StatsFilter::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(catflip_only.bets, 3);
+    assert_eq!(catflip_only.volume, 150_000_000);
+}
+
+#[test]
+fn reingesting_is_a_no_op() {
+    let transactions = fixtures();
+    let mut indexer = index(&transactions);
+    let before = (rows(&indexer), all_stats(&indexer));
+
+    for tx in &transactions {
+        assert!(!indexer.ingest(tx).unwrap());
+    }
+    assert_eq!((rows(&indexer), all_stats(&indexer)), before);
+}
+
+#[test]
+fn ingestion_order_does_not_matter() {
+    let transactions = fixtures();
+    let forward = index(&transactions);
+
+    let mut reversed = transactions.clone();
+    reversed.reverse();
+    let backward = index(&reversed);
+
+    assert_eq!(rows(&forward), rows(&backward));
+    assert_eq!(all_stats(&forward), all_stats(&backward));
+}
+
+#[test]
+fn unsettled_rounds_stay_open() {
+    let transactions = fixtures();
+    let indexer = index(&transactions[..2]);
+
+    let stats = all_stats(&indexer);
+    assert_eq!((stats.bets, stats.open), (2, 2));
+    assert_eq!(stats.volume, 0);
+    assert_eq!(stats.rtp, None);
+}
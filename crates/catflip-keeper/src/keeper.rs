@@ -0,0 +1,360 @@
+use catflip::{
+    errors::CatflipError,
+    state::{BetRound, Challenge, PoolPhase, PoolRound},
+};
+use catflip_client::{
+    instructions,
+    state::{
+        challenge_vrf_input, fetch_matched_challenges, fetch_open_bet_rounds, fetch_pool_positions,
+        fetch_pools, fetch_vault, pool_vrf_input,
+    },
+    Chain, ClientError, Result,
+};
+use log::{info, warn};
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{Backoff, RandomnessSource};
+
+/// Claims or refunds of one pool round sent together. Each takes two
+/// accounts of its own on top of the shared ones, and resolving with oracle
+/// beacons takes two instructions.
+const POSITIONS_PER_TRANSACTION: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Settle,
+    Refund,
+}
+
+/// Something on chain waiting for the keeper.
+enum Pending {
+    Round(BetRound),
+    Challenge(Challenge),
+    Pool(PoolRound),
+}
+
+impl Pending {
+    /// What to do with it at `slot`, if anything yet.
+    ///
+    /// Once a round has timed out the player is owed their stake back;
+    /// settling it late would let whoever cranks pick the moment. Challenges
+    /// and pool rounds wait for the hash of their entropy slot, which only
+    /// exists once that slot is over. Settling a resolved pool round claims
+    /// its positions, and refunding an expired one refunds them.
+    fn action(&self, slot: u64) -> Option<Action> {
+        match self {
+            Pending::Round(bet_round) if slot > bet_round.timeout_slot() => Some(Action::Refund),
+            Pending::Round(_) => Some(Action::Settle),
+            Pending::Challenge(challenge) if slot > challenge.timeout_slot() => {
+                Some(Action::Refund)
+            }
+            Pending::Challenge(challenge) => {
+                (slot > challenge.entropy_slot()).then_some(Action::Settle)
+            }
+            Pending::Pool(pool) => match pool.phase(slot) {
+                PoolPhase::Open => None,
+                PoolPhase::Locked => (slot > pool.entropy_slot()).then_some(Action::Settle),
+                PoolPhase::Resolved => (pool.open_positions > 0).then_some(Action::Settle),
+                PoolPhase::Expired => (pool.open_positions > 0).then_some(Action::Refund),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Retry {
+    attempts: u32,
+    not_before: Instant,
+}
+
+/// What a single pass over the open rounds, matched challenges and pool
+/// rounds did.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TickReport {
+    pub settled: Vec<Pubkey>,
+    pub refunded: Vec<Pubkey>,
+    /// Rounds that failed this pass and will be retried after a backoff.
+    pub failed: Vec<Pubkey>,
+    /// Rounds skipped because their backoff has not elapsed yet.
+    pub deferred: Vec<Pubkey>,
+}
+
+impl TickReport {
+    pub fn is_idle(&self) -> bool {
+        self.settled.is_empty()
+            && self.refunded.is_empty()
+            && self.failed.is_empty()
+            && self.deferred.is_empty()
+    }
+}
+
+pub struct Keeper<C> {
+    chain: C,
+    payer: Keypair,
+    program_id: Pubkey,
+    randomness: RandomnessSource,
+    backoff: Backoff,
+    retries: HashMap<Pubkey, Retry>,
+}
+
+impl<C: Chain> Keeper<C> {
+    pub fn new(
+        chain: C,
+        payer: Keypair,
+        program_id: Pubkey,
+        randomness: RandomnessSource,
+        backoff: Backoff,
+    ) -> Self {
+        Self {
+            chain,
+            payer,
+            program_id,
+            randomness,
+            backoff,
+            retries: HashMap::new(),
+        }
+    }
+
+    /// Settles every open round, matched challenge and locked pool round
+    /// that is still within its timeout and refunds every one past it. One
+    /// whose entropy slot hasn't been hashed yet is left for a later pass.
+    /// Positions of resolved and expired pool rounds are claimed or refunded,
+    /// a pass after resolving.
+    pub async fn tick(&mut self) -> Result<TickReport> {
+        let slot = self.chain.get_slot().await?;
+        let mut pending: Vec<(Pubkey, Pending)> =
+            fetch_open_bet_rounds(&self.chain, &self.program_id)
+                .await?
+                .into_iter()
+                .map(|(address, bet_round)| (address, Pending::Round(bet_round)))
+                .collect();
+        pending.extend(
+            fetch_matched_challenges(&self.chain, &self.program_id)
+                .await?
+                .into_iter()
+                .map(|(address, challenge)| (address, Pending::Challenge(challenge))),
+        );
+        pending.extend(
+            fetch_pools(&self.chain, &self.program_id)
+                .await?
+                .into_iter()
+                .map(|(address, pool)| (address, Pending::Pool(pool))),
+        );
+        self.retries
+            .retain(|address, _| pending.iter().any(|(open, _)| open == address));
+
+        let mut report = TickReport::default();
+        let now = Instant::now();
+        for (address, item) in pending {
+            if self
+                .retries
+                .get(&address)
+                .is_some_and(|retry| retry.not_before > now)
+            {
+                report.deferred.push(address);
+                continue;
+            }
+
+            let Some(action) = item.action(slot) else {
+                continue;
+            };
+
+            match self.execute(action, &address, &item).await {
+                Ok(()) => {
+                    self.retries.remove(&address);
+                    match action {
+                        Action::Settle => report.settled.push(address),
+                        Action::Refund => report.refunded.push(address),
+                    }
+                }
+                Err(err) if already_settled(&err) => {
+                    // Another keeper (or an earlier run of this one) got there first.
+                    self.retries.remove(&address);
+                }
+                Err(err) => {
+                    let retry = self.retries.entry(address).or_insert(Retry {
+                        attempts: 0,
+                        not_before: now,
+                    });
+                    retry.attempts += 1;
+                    let delay = self.backoff.delay(retry.attempts);
+                    retry.not_before = now + delay;
+                    warn!(
+                        "{action:?} {address} failed (attempt {}), retrying in {delay:?}: {err}",
+                        retry.attempts
+                    );
+                    report.failed.push(address);
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Polls forever, backing off while the RPC node itself is failing.
+    pub async fn run(&mut self, poll_interval: Duration) {
+        let mut failures = 0;
+        loop {
+            let delay = match self.tick().await {
+                Ok(report) => {
+                    failures = 0;
+                    if !report.is_idle() {
+                        info!(
+                            "settled {}, refunded {}, failed {}, deferred {}",
+                            report.settled.len(),
+                            report.refunded.len(),
+                            report.failed.len(),
+                            report.deferred.len()
+                        );
+                    }
+                    poll_interval
+                }
+                Err(err) => {
+                    failures += 1;
+                    let delay = self.backoff.delay(failures);
+                    warn!("scanning open rounds failed, retrying in {delay:?}: {err}");
+                    delay
+                }
+            };
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn execute(&self, action: Action, address: &Pubkey, item: &Pending) -> Result<()> {
+        let ixs = match (item, action) {
+            (Pending::Round(bet_round), Action::Settle) => {
+                self.randomness
+                    .settle_instructions(&self.program_id, address, bet_round)
+                    .await?
+            }
+            (Pending::Round(bet_round), Action::Refund) => vec![instructions::refund_timeout(
+                &self.program_id,
+                address,
+                bet_round,
+            )],
+            (Pending::Challenge(challenge), Action::Settle) => {
+                let alpha = challenge_vrf_input(&self.chain, address, challenge)
+                    .await?
+                    .ok_or_else(|| {
+                        ClientError::Config(format!(
+                            "no hash for slot {} in SlotHashes yet",
+                            challenge.entropy_slot()
+                        ))
+                    })?;
+                self.randomness
+                    .settle_challenge_instructions(&self.program_id, address, challenge, &alpha)
+                    .await?
+            }
+            (Pending::Challenge(challenge), Action::Refund) => {
+                vec![instructions::cancel_challenge(
+                    &self.program_id,
+                    &self.payer.pubkey(),
+                    address,
+                    challenge,
+                )]
+            }
+            (Pending::Pool(pool), action) => self.pool_instructions(action, address, pool).await?,
+        };
+        // A pool round's positions go out a few per transaction to stay under
+        // the size limit; everything else, beacons included, fits in one.
+        let per_transaction = match item {
+            Pending::Pool(_) => POSITIONS_PER_TRANSACTION,
+            _ => ixs.len().max(1),
+        };
+        for chunk in ixs.chunks(per_transaction) {
+            self.send(chunk).await?;
+        }
+        match item {
+            Pending::Round(bet_round) => info!(
+                "{action:?} {address} (player {}, stake {})",
+                bet_round.player, bet_round.stake_lamports
+            ),
+            Pending::Challenge(challenge) => info!(
+                "{action:?} challenge {address} ({} vs {}, stake {})",
+                challenge.creator, challenge.opponent, challenge.stake
+            ),
+            Pending::Pool(pool) => info!(
+                "{action:?} pool round {} {address} (alive {}, dead {}, {} open positions)",
+                pool.round_id, pool.alive_total, pool.dead_total, pool.open_positions
+            ),
+        }
+        Ok(())
+    }
+
+    /// Resolves a locked pool round, or claims or refunds every position of
+    /// one that is over. Claims and refunds return the round's rent to the
+    /// vault authority with the last position.
+    async fn pool_instructions(
+        &self,
+        action: Action,
+        address: &Pubkey,
+        pool: &PoolRound,
+    ) -> Result<Vec<Instruction>> {
+        let authority = fetch_vault(&self.chain, &self.program_id)
+            .await?
+            .vault
+            .authority;
+        if !pool.is_resolved && action == Action::Settle {
+            let alpha = pool_vrf_input(&self.chain, address, pool)
+                .await?
+                .ok_or_else(|| {
+                    ClientError::Config(format!(
+                        "no hash for slot {} in SlotHashes yet",
+                        pool.entropy_slot()
+                    ))
+                })?;
+            return self
+                .randomness
+                .resolve_pool_instructions(&self.program_id, &authority, address, pool, &alpha)
+                .await;
+        }
+
+        let positions = fetch_pool_positions(&self.chain, &self.program_id, address).await?;
+        Ok(positions
+            .iter()
+            .map(|position| match action {
+                Action::Settle => instructions::claim_position(
+                    &self.program_id,
+                    &authority,
+                    &position.player,
+                    pool.round_id,
+                ),
+                Action::Refund => instructions::refund_position(
+                    &self.program_id,
+                    &authority,
+                    &position.player,
+                    pool.round_id,
+                ),
+            })
+            .collect())
+    }
+
+    async fn send(&self, ixs: &[Instruction]) -> Result<()> {
+        let blockhash = self.chain.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            blockhash,
+        );
+        self.chain.send_transaction(&transaction).await?;
+        Ok(())
+    }
+}
+
+fn already_settled(err: &ClientError) -> bool {
+    matches!(
+        err.transaction_error(),
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code)))
+            if code == u32::from(CatflipError::BetAlreadySettled)
+    )
+}
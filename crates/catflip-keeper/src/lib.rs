@@ -0,0 +1,18 @@
+//! Settlement keeper for catflip bet rounds, challenges and pool rounds.
+//!
+//! Nothing on-chain drives a round to completion: `fulfill_randomness` has to
+//! be cranked by someone and a round past `BetRound::TIMEOUT_SLOTS` sits until
+//! `refund_timeout` is called. The keeper polls for open rounds and does both,
+//! and does the same for matched challenges with `settle_challenge` and
+//! `cancel_challenge`. It resolves locked pool rounds, then claims or refunds
+//! their positions so each round closes.
+//! It keeps no local state beyond retry timers; everything it acts on is
+//! rediscovered from the chain, so restarting it is always safe.
+
+pub mod backoff;
+pub mod keeper;
+pub mod randomness;
+
+pub use backoff::Backoff;
+pub use keeper::{Keeper, TickReport};
+pub use randomness::RandomnessSource;
@@ -0,0 +1,97 @@
+use catflip_client::config::{read_keypair, Settings};
+use catflip_keeper::{Backoff, Keeper, RandomnessSource};
+use clap::Parser;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "catflip-keeper",
+    version,
+    about = "Settle and refund open catflip bet rounds"
+)]
+struct Args {
+    /// Solana CLI config file to take the RPC URL and keypair from
+    #[arg(short = 'C', long, value_name = "PATH")]
+    config: Option<String>,
+
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[arg(
+        short = 'u',
+        long,
+        value_name = "URL_OR_MONIKER",
+        env = "CATFLIP_KEEPER_URL"
+    )]
+    url: Option<String>,
+
+    /// Keypair that pays for settlement transactions
+    #[arg(short = 'k', long, value_name = "PATH", env = "CATFLIP_KEEPER_KEYPAIR")]
+    keypair: Option<String>,
+
+    #[arg(long, default_value_t = catflip::ID)]
+    program_id: Pubkey,
+
+    /// VRF keypair registered in the vault with `set-vrf-key`
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "CATFLIP_KEEPER_VRF_KEYPAIR",
+        required_unless_present = "oracle_url"
+    )]
+    vrf_keypair: Option<String>,
+
+    /// URL of a `catflip-oracle` service for one of the oracles that sign
+    /// rounds; repeat it once per signing oracle to settle from oracle
+    /// beacons instead of the VRF
+    #[arg(long, value_name = "URL", conflicts_with = "vrf_keypair")]
+    oracle_url: Vec<String>,
+
+    /// Seconds between scans for open rounds
+    #[arg(long, default_value_t = 5)]
+    poll_interval: u64,
+
+    /// Longest wait between retries of a failing round, in seconds
+    #[arg(long, default_value_t = 120)]
+    max_backoff: u64,
+
+    /// Scan once and exit instead of running as a daemon
+    #[arg(long)]
+    once: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    let settings = Settings::load(
+        args.config.as_deref(),
+        args.url.as_deref(),
+        args.keypair.as_deref(),
+    )?;
+    let randomness = match &args.vrf_keypair {
+        Some(path) => RandomnessSource::Vrf(read_keypair(path)?),
+        None => RandomnessSource::oracles(args.oracle_url),
+    };
+    let backoff = Backoff {
+        max: Duration::from_secs(args.max_backoff),
+        ..Backoff::default()
+    };
+    let mut keeper = Keeper::new(
+        RpcClient::new_with_commitment(settings.url, settings.commitment),
+        settings.keypair,
+        args.program_id,
+        randomness,
+        backoff,
+    );
+
+    if args.once {
+        let report = keeper.tick().await?;
+        println!("{report:#?}");
+        return Ok(());
+    }
+
+    keeper.run(Duration::from_secs(args.poll_interval)).await;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use catflip::{
+    instructions::Randomness,
+    state::{BetRound, Challenge, PoolRound},
+};
+use catflip_client::{beacons, instructions, vrf, Result};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair};
+
+/// How the keeper obtains the outcome when it settles a round.
+pub enum RandomnessSource {
+    /// The keeper holds the VRF key registered in the vault and proves each
+    /// round's output itself; `fulfill_randomness` verifies the proof.
+    Vrf(Keypair),
+    /// The keeper asks each oracle service at `endpoints` for its beacon and
+    /// submits them together. It holds no oracle key, so it can't produce a
+    /// beacon an oracle didn't sign.
+    Oracles {
+        endpoints: Vec<String>,
+        http: reqwest::Client,
+    },
+}
+
+impl RandomnessSource {
+    /// Settles from the oracle services at `endpoints`, in any order.
+    pub fn oracles(endpoints: Vec<String>) -> Self {
+        RandomnessSource::Oracles {
+            endpoints,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn settle_instructions(
+        &self,
+        program_id: &Pubkey,
+        round: &Pubkey,
+        bet_round: &BetRound,
+    ) -> Result<Vec<Instruction>> {
+        let (mut ixs, randomness) = self.randomness(round, &bet_round.vrf_input(round)).await?;
+        ixs.push(instructions::fulfill_randomness(
+            program_id, round, bet_round, randomness,
+        ));
+        Ok(ixs)
+    }
+
+    /// `alpha` is the challenge's VRF input, which needs its slot hash; see
+    /// `catflip_client::state::challenge_vrf_input`.
+    pub async fn settle_challenge_instructions(
+        &self,
+        program_id: &Pubkey,
+        address: &Pubkey,
+        challenge: &Challenge,
+        alpha: &[u8],
+    ) -> Result<Vec<Instruction>> {
+        let (mut ixs, randomness) = self.randomness(address, alpha).await?;
+        ixs.push(instructions::settle_challenge(
+            program_id, address, challenge, randomness,
+        ));
+        Ok(ixs)
+    }
+
+    /// `alpha` is the pool round's VRF input, which needs its slot hash; see
+    /// `catflip_client::state::pool_vrf_input`.
+    pub async fn resolve_pool_instructions(
+        &self,
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        address: &Pubkey,
+        pool: &PoolRound,
+        alpha: &[u8],
+    ) -> Result<Vec<Instruction>> {
+        let (mut ixs, randomness) = self.randomness(address, alpha).await?;
+        ixs.push(instructions::resolve_pool(
+            program_id,
+            authority,
+            pool.round_id,
+            randomness,
+        ));
+        Ok(ixs)
+    }
+
+    /// The randomness for the account at `address` whose VRF input is
+    /// `alpha`, after any instructions that have to go before the settling
+    /// one: oracle beacons travel in an Ed25519 instruction of their own.
+    async fn randomness(
+        &self,
+        address: &Pubkey,
+        alpha: &[u8],
+    ) -> Result<(Vec<Instruction>, Randomness)> {
+        match self {
+            RandomnessSource::Vrf(key) => {
+                let (output, proof) = vrf::prove(key, alpha);
+                Ok((Vec::new(), Randomness::Vrf { output, proof }))
+            }
+            RandomnessSource::Oracles { endpoints, http } => {
+                let mut signed = Vec::with_capacity(endpoints.len());
+                for url in endpoints {
+                    signed.push(beacons::fetch(http, url, address, alpha).await?);
+                }
+                Ok((
+                    vec![beacons::instruction(alpha, &signed)],
+                    Randomness::Oracles,
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,401 @@
+use anchor_lang::Discriminator;
+use catflip::{
+    instructions::BetGuards,
+    state::{BetRound, Side},
+};
+use catflip_client::{
+    instructions,
+    pda::{bet_round_address, challenge_address, pool_address},
+    state::{
+        fetch_bet_round, fetch_challenge, fetch_open_bet_rounds, fetch_pool,
+        BET_ROUND_IS_SETTLED_OFFSET,
+    },
+    testing::{program_test, TestChain},
+    vrf, Chain,
+};
+use catflip_keeper::{Backoff, Keeper, RandomnessSource, TickReport};
+use solana_sdk::{
+    account::AccountSharedData,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use std::time::Duration;
+
+async fn send(chain: &TestChain, payer: &Keypair, ixs: &[Instruction]) {
+    let blockhash = chain.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &[payer], blockhash);
+    chain.send_transaction(&tx).await.unwrap();
+}
+
+async fn place_bet(chain: &TestChain, player: &Keypair, amount: u64) -> Pubkey {
+    let slot = chain.get_slot().await.unwrap();
+    send(
+        chain,
+        player,
+        &[instructions::bet(
+            &catflip::ID,
+            &player.pubkey(),
+            slot,
+            amount,
+            vrf::player_seed(),
+            BetGuards::default(),
+        )],
+    )
+    .await;
+    bet_round_address(&catflip::ID, &player.pubkey(), slot).0
+}
+
+/// The payer doubles as the house's VRF key.
+fn new_keeper(chain: TestChain, payer: &Keypair) -> Keeper<TestChain> {
+    Keeper::new(
+        chain,
+        Keypair::from_bytes(&payer.to_bytes()).unwrap(),
+        catflip::ID,
+        RandomnessSource::Vrf(Keypair::from_bytes(&payer.to_bytes()).unwrap()),
+        Backoff::default(),
+    )
+}
+
+#[tokio::test]
+async fn settles_open_rounds_and_refunds_expired_ones() {
+    let mut context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let chain = TestChain::new(context.banks_client.clone());
+
+    send(
+        &chain,
+        &payer,
+        &[
+            instructions::initialize(
+                &catflip::ID,
+                &payer.pubkey(),
+                LAMPORTS_PER_SOL / 1000,
+                1000,
+                200,
+            ),
+            instructions::fund_vault(&catflip::ID, &payer.pubkey(), 10 * LAMPORTS_PER_SOL),
+            instructions::set_vrf_key(&catflip::ID, &payer.pubkey(), &payer.pubkey()),
+        ],
+    )
+    .await;
+
+    let expired = place_bet(&chain, &payer, LAMPORTS_PER_SOL / 10).await;
+    let expired_round = fetch_bet_round(&chain, &expired).await.unwrap();
+    context
+        .warp_to_slot(expired_round.slot + catflip::state::BetRound::TIMEOUT_SLOTS + 1)
+        .unwrap();
+    let live = place_bet(&chain, &payer, LAMPORTS_PER_SOL / 20).await;
+    assert_eq!(
+        fetch_open_bet_rounds(&chain, &catflip::ID)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let mut keeper = new_keeper(chain.clone(), &payer);
+    let report = keeper.tick().await.unwrap();
+    assert_eq!(
+        report,
+        TickReport {
+            settled: vec![live],
+            refunded: vec![expired],
+            ..TickReport::default()
+        }
+    );
+
+    assert!(chain.get_account(&expired).await.unwrap().is_none());
+    assert!(fetch_bet_round(&chain, &live).await.unwrap().is_settled);
+
+    // Nothing is left to do, on this run or after a restart.
+    assert!(keeper.tick().await.unwrap().is_idle());
+    let mut restarted = new_keeper(chain, &payer);
+    assert!(restarted.tick().await.unwrap().is_idle());
+}
+
+#[tokio::test]
+async fn settles_matched_challenges_once_their_slot_is_hashed() {
+    let mut context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let chain = TestChain::new(context.banks_client.clone());
+    let opponent = Keypair::new();
+
+    send(
+        &chain,
+        &payer,
+        &[
+            instructions::initialize(
+                &catflip::ID,
+                &payer.pubkey(),
+                LAMPORTS_PER_SOL / 1000,
+                1000,
+                200,
+            ),
+            instructions::fund_vault(&catflip::ID, &payer.pubkey(), 10 * LAMPORTS_PER_SOL),
+            instructions::set_vrf_key(&catflip::ID, &payer.pubkey(), &payer.pubkey()),
+            system_instruction::transfer(&payer.pubkey(), &opponent.pubkey(), LAMPORTS_PER_SOL),
+        ],
+    )
+    .await;
+
+    let slot = chain.get_slot().await.unwrap();
+    let stake = LAMPORTS_PER_SOL / 10;
+    let address = challenge_address(&catflip::ID, &payer.pubkey(), slot).0;
+    send(
+        &chain,
+        &payer,
+        &[instructions::open_challenge(
+            &catflip::ID,
+            &payer.pubkey(),
+            slot,
+            stake,
+            Side::Alive,
+            vrf::player_seed(),
+        )],
+    )
+    .await;
+
+    // An open challenge has nothing to settle yet.
+    let mut keeper = new_keeper(chain.clone(), &payer);
+    assert!(keeper.tick().await.unwrap().is_idle());
+
+    send(
+        &chain,
+        &opponent,
+        &[instructions::match_challenge(
+            &catflip::ID,
+            &opponent.pubkey(),
+            &address,
+            vrf::player_seed(),
+        )],
+    )
+    .await;
+    let matched = fetch_challenge(&chain, &address).await.unwrap();
+
+    // Nor a matched one whose entropy slot is still ahead.
+    assert!(keeper.tick().await.unwrap().is_idle());
+
+    context.warp_to_slot(matched.entropy_slot() + 1).unwrap();
+    assert_eq!(keeper.tick().await.unwrap().settled, vec![address]);
+    assert!(chain.get_account(&address).await.unwrap().is_none());
+    assert!(keeper.tick().await.unwrap().is_idle());
+}
+
+#[tokio::test]
+async fn resolves_pool_rounds_and_closes_them() {
+    let mut context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let chain = TestChain::new(context.banks_client.clone());
+    let player = Keypair::new();
+
+    send(
+        &chain,
+        &payer,
+        &[
+            instructions::initialize(
+                &catflip::ID,
+                &payer.pubkey(),
+                LAMPORTS_PER_SOL / 1000,
+                1000,
+                200,
+            ),
+            instructions::set_vrf_key(&catflip::ID, &payer.pubkey(), &payer.pubkey()),
+            system_instruction::transfer(&payer.pubkey(), &player.pubkey(), LAMPORTS_PER_SOL),
+            instructions::open_pool(&catflip::ID, &payer.pubkey(), 1, 5),
+        ],
+    )
+    .await;
+    let join = |player: &Keypair, side| {
+        instructions::join_pool(
+            &catflip::ID,
+            &player.pubkey(),
+            1,
+            side,
+            LAMPORTS_PER_SOL / 10,
+            vrf::player_seed(),
+        )
+    };
+    send(&chain, &payer, &[join(&payer, Side::Alive)]).await;
+    send(&chain, &player, &[join(&player, Side::Dead)]).await;
+    let address = pool_address(&catflip::ID, 1).0;
+    let pool = fetch_pool(&chain, &catflip::ID, 1).await.unwrap();
+
+    // Nothing to do while betting is open or the entropy slot is ahead.
+    let mut keeper = new_keeper(chain.clone(), &payer);
+    assert!(keeper.tick().await.unwrap().is_idle());
+    context.warp_to_slot(pool.lock_slot).unwrap();
+    assert!(keeper.tick().await.unwrap().is_idle());
+
+    // One pass resolves the round, the next claims both positions, the
+    // last of which closes it.
+    context.warp_to_slot(pool.entropy_slot() + 1).unwrap();
+    assert_eq!(keeper.tick().await.unwrap().settled, vec![address]);
+    assert!(
+        fetch_pool(&chain, &catflip::ID, 1)
+            .await
+            .unwrap()
+            .is_resolved
+    );
+    assert_eq!(keeper.tick().await.unwrap().settled, vec![address]);
+    assert!(chain.get_account(&address).await.unwrap().is_none());
+    assert!(keeper.tick().await.unwrap().is_idle());
+
+    // A round that expired unresolved has its positions refunded instead.
+    send(
+        &chain,
+        &payer,
+        &[instructions::open_pool(&catflip::ID, &payer.pubkey(), 2, 5)],
+    )
+    .await;
+    send(
+        &chain,
+        &player,
+        &[instructions::join_pool(
+            &catflip::ID,
+            &player.pubkey(),
+            2,
+            Side::Dead,
+            LAMPORTS_PER_SOL / 10,
+            vrf::player_seed(),
+        )],
+    )
+    .await;
+    let address = pool_address(&catflip::ID, 2).0;
+    let pool = fetch_pool(&chain, &catflip::ID, 2).await.unwrap();
+    context.warp_to_slot(pool.timeout_slot() + 1).unwrap();
+    assert_eq!(keeper.tick().await.unwrap().refunded, vec![address]);
+    assert!(chain.get_account(&address).await.unwrap().is_none());
+}
+
+/// Serves `oracle`'s beacons on a local port and returns its URL.
+async fn spawn_oracle(chain: TestChain, oracle: Keypair) -> String {
+    let app = catflip_oracle::router(catflip_oracle::AppState::new(chain, oracle, catflip::ID));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+#[tokio::test]
+async fn settles_from_oracle_services() {
+    let context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let chain = TestChain::new(context.banks_client.clone());
+
+    let oracles: Vec<Keypair> = (0..2).map(|_| Keypair::new()).collect();
+    let keys: Vec<Pubkey> = oracles.iter().map(Signer::pubkey).collect();
+    send(
+        &chain,
+        &payer,
+        &[
+            instructions::initialize(
+                &catflip::ID,
+                &payer.pubkey(),
+                LAMPORTS_PER_SOL / 1000,
+                1000,
+                200,
+            ),
+            instructions::fund_vault(&catflip::ID, &payer.pubkey(), 10 * LAMPORTS_PER_SOL),
+            instructions::set_oracles(&catflip::ID, &payer.pubkey(), &keys, 2),
+        ],
+    )
+    .await;
+
+    // Each oracle keeps its own key; the keeper only knows where to ask.
+    let mut endpoints = Vec::new();
+    for oracle in oracles {
+        endpoints.push(spawn_oracle(chain.clone(), oracle).await);
+    }
+    let round = place_bet(&chain, &payer, LAMPORTS_PER_SOL / 10).await;
+
+    // A service that is down fails the round until it's back.
+    let down = {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let mut keeper = Keeper::new(
+        chain.clone(),
+        Keypair::from_bytes(&payer.to_bytes()).unwrap(),
+        catflip::ID,
+        RandomnessSource::oracles(vec![endpoints[0].clone(), down]),
+        Backoff::default(),
+    );
+    assert_eq!(keeper.tick().await.unwrap().failed, vec![round]);
+
+    endpoints.reverse();
+    let mut keeper = Keeper::new(
+        chain.clone(),
+        Keypair::from_bytes(&payer.to_bytes()).unwrap(),
+        catflip::ID,
+        RandomnessSource::oracles(endpoints),
+        Backoff::default(),
+    );
+    assert_eq!(keeper.tick().await.unwrap().settled, vec![round]);
+    assert!(fetch_bet_round(&chain, &round).await.unwrap().is_settled);
+}
+
+#[tokio::test]
+async fn skips_rounds_that_do_not_decode() {
+    let mut context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let chain = TestChain::new(context.banks_client.clone());
+    send(
+        &chain,
+        &payer,
+        &[
+            instructions::initialize(
+                &catflip::ID,
+                &payer.pubkey(),
+                LAMPORTS_PER_SOL / 1000,
+                1000,
+                200,
+            ),
+            instructions::fund_vault(&catflip::ID, &payer.pubkey(), 10 * LAMPORTS_PER_SOL),
+            instructions::set_vrf_key(&catflip::ID, &payer.pubkey(), &payer.pubkey()),
+        ],
+    )
+    .await;
+
+    // Matches the open-round filters but is too short to be a round.
+    let broken = Pubkey::new_unique();
+    let mut data = vec![0; BET_ROUND_IS_SETTLED_OFFSET + 1];
+    data[..8].copy_from_slice(&BetRound::DISCRIMINATOR);
+    let mut account = AccountSharedData::new(LAMPORTS_PER_SOL, data.len(), &catflip::ID);
+    account.set_data_from_slice(&data);
+    context.set_account(&broken, &account);
+    chain.track(&Transaction::new_with_payer(
+        &[Instruction::new_with_bytes(
+            catflip::ID,
+            &[],
+            vec![AccountMeta::new_readonly(broken, false)],
+        )],
+        Some(&payer.pubkey()),
+    ));
+
+    let live = place_bet(&chain, &payer, LAMPORTS_PER_SOL / 10).await;
+    let mut keeper = new_keeper(chain.clone(), &payer);
+    assert_eq!(
+        keeper.tick().await.unwrap(),
+        TickReport {
+            settled: vec![live],
+            ..TickReport::default()
+        }
+    );
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let backoff = Backoff {
+        initial: Duration::from_secs(2),
+        max: Duration::from_secs(10),
+    };
+    assert_eq!(backoff.delay(1), Duration::from_secs(2));
+    assert_eq!(backoff.delay(2), Duration::from_secs(4));
+    assert_eq!(backoff.delay(3), Duration::from_secs(8));
+    assert_eq!(backoff.delay(4), Duration::from_secs(10));
+    assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(10));
+}
@@ -0,0 +1,39 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use catflip_client::ClientError;
+use serde_json::json;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OracleError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error("{0} not found")]
+    NotFound(String),
+
+    /// The round exists but isn't waiting for randomness.
+    #[error("{0}")]
+    NotReady(String),
+}
+
+impl IntoResponse for OracleError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            OracleError::Client(err) => {
+                log::error!("reading the chain failed: {err}");
+                StatusCode::BAD_GATEWAY
+            }
+            OracleError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            OracleError::NotFound(_) => StatusCode::NOT_FOUND,
+            OracleError::NotReady(_) => StatusCode::CONFLICT,
+        };
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
@@ -0,0 +1,20 @@
+//! Oracle service for vaults that settle with `set_oracles`.
+//!
+//! Each oracle runs its own instance with its own key, so whoever submits
+//! settlements holds no oracle key and can't produce a beacon on their own.
+//! The service only signs a round's VRF input once it has read the round from
+//! the chain and found it waiting for randomness: an unsettled bet round, a
+//! matched challenge or a pool round whose betting has closed, each once its
+//! entropy slot has been hashed and before its timeout. Signing any input on
+//! request would let a player learn the outcome of a seed before betting
+//! with it.
+//!
+//! Beacons are served to anyone who asks, other oracles included, so an
+//! oracle can learn a round's outcome before serving its own beacon and
+//! refuse to serve it, leaving the round to refund. See `catflip::beacons`.
+
+pub mod error;
+pub mod routes;
+
+pub use error::OracleError;
+pub use routes::{router, AppState};
@@ -0,0 +1,56 @@
+use catflip_client::config::{read_keypair, rpc_settings};
+use catflip_oracle::{router, AppState};
+use clap::Parser;
+use log::info;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signer::Signer};
+use std::net::SocketAddr;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "catflip-oracle",
+    version,
+    about = "Sign oracle beacons for catflip rounds waiting for randomness"
+)]
+struct Args {
+    /// Solana CLI config file to take the RPC URL from
+    #[arg(short = 'C', long, value_name = "PATH")]
+    config: Option<String>,
+
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[arg(
+        short = 'u',
+        long,
+        value_name = "URL_OR_MONIKER",
+        env = "CATFLIP_ORACLE_URL"
+    )]
+    url: Option<String>,
+
+    /// This oracle's keypair, registered with `set-oracles`
+    #[arg(long, value_name = "PATH", env = "CATFLIP_ORACLE_KEYPAIR")]
+    oracle_keypair: String,
+
+    #[arg(long, env = "CATFLIP_ORACLE_LISTEN", default_value = "127.0.0.1:8090")]
+    listen: SocketAddr,
+
+    #[arg(long, default_value_t = catflip::ID)]
+    program_id: Pubkey,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+
+    let (url, _) = rpc_settings(args.config.as_deref(), args.url.as_deref());
+    let oracle = read_keypair(&args.oracle_keypair)?;
+    info!("oracle {} serving on {}", oracle.pubkey(), args.listen);
+    // Rounds are only signed once finalized, so a fork can't swap the seed
+    // behind a beacon that was already handed out.
+    let chain = RpcClient::new_with_commitment(url, CommitmentConfig::finalized());
+    let app = router(AppState::new(chain, oracle, args.program_id));
+
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anchor_lang::Discriminator;
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use catflip::state::{BetRound, Challenge, PoolPhase, PoolRound};
+use catflip_client::{
+    beacons::Beacon,
+    state::{challenge_vrf_input, decode, pool_vrf_input},
+    Chain,
+};
+use log::info;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::sync::Arc;
+
+use crate::OracleError;
+
+pub struct AppState<C> {
+    chain: Arc<C>,
+    oracle: Arc<Keypair>,
+    program_id: Pubkey,
+}
+
+// A derived `Clone` would require `C: Clone`.
+impl<C> Clone for AppState<C> {
+    fn clone(&self) -> Self {
+        Self {
+            chain: self.chain.clone(),
+            oracle: self.oracle.clone(),
+            program_id: self.program_id,
+        }
+    }
+}
+
+impl<C> AppState<C> {
+    /// Signs with `oracle` for rounds owned by `program_id`.
+    pub fn new(chain: C, oracle: Keypair, program_id: Pubkey) -> Self {
+        Self {
+            chain: Arc::new(chain),
+            oracle: Arc::new(oracle),
+            program_id,
+        }
+    }
+}
+
+pub fn router<C: Chain + 'static>(state: AppState<C>) -> Router {
+    Router::new()
+        .route("/beacons/:round", get(beacon::<C>))
+        .with_state(state)
+}
+
+/// `GET /beacons/:round`: this oracle's beacon for a round waiting for
+/// randomness. Once served it is public, so serve every round or none.
+async fn beacon<C: Chain>(
+    State(state): State<AppState<C>>,
+    Path(round): Path<String>,
+) -> Result<Json<Beacon>, OracleError> {
+    let address: Pubkey = round
+        .parse()
+        .map_err(|_| OracleError::BadRequest(format!("invalid round address {round}")))?;
+    let account = state
+        .chain
+        .get_account(&address)
+        .await?
+        .filter(|account| account.owner == state.program_id)
+        .ok_or_else(|| OracleError::NotFound(format!("round {address}")))?;
+    let slot = state.chain.get_slot().await?;
+    let alpha = waiting_vrf_input(state.chain.as_ref(), &address, &account, slot).await?;
+
+    let signature = state.oracle.sign_message(&alpha);
+    info!("signed {address} at slot {slot}");
+    Ok(Json(Beacon {
+        oracle: state.oracle.pubkey().to_string(),
+        signature: signature.to_string(),
+    }))
+}
+
+/// The VRF input of the round in `account`, as long as the round is waiting
+/// for randomness at `slot`.
+async fn waiting_vrf_input<C: Chain>(
+    chain: &C,
+    address: &Pubkey,
+    account: &Account,
+    slot: u64,
+) -> Result<Vec<u8>, OracleError> {
+    let not_ready = |reason: &str| Err(OracleError::NotReady(format!("{address} {reason}")));
+    match account.data.get(..8) {
+        Some(tag) if tag == BetRound::DISCRIMINATOR => {
+            let round: BetRound = decode(address, account)?;
+            if round.is_settled {
+                return not_ready("is already settled");
+            }
+            if slot > round.timeout_slot() {
+                return not_ready("has timed out");
+            }
+            Ok(round.vrf_input(address).to_vec())
+        }
+        Some(tag) if tag == Challenge::DISCRIMINATOR => {
+            let challenge: Challenge = decode(address, account)?;
+            if !challenge.is_matched() {
+                return not_ready("is not matched yet");
+            }
+            if slot > challenge.timeout_slot() {
+                return not_ready("has timed out");
+            }
+            match challenge_vrf_input(chain, address, &challenge).await? {
+                Some(alpha) => Ok(alpha.to_vec()),
+                None => not_ready("waits for its entropy slot to be hashed"),
+            }
+        }
+        Some(tag) if tag == PoolRound::DISCRIMINATOR => {
+            let pool: PoolRound = decode(address, account)?;
+            match pool.phase(slot) {
+                PoolPhase::Locked => match pool_vrf_input(chain, address, &pool).await? {
+                    Some(alpha) => Ok(alpha.to_vec()),
+                    None => not_ready("waits for its entropy slot to be hashed"),
+                },
+                PoolPhase::Open => not_ready("is still taking bets"),
+                PoolPhase::Resolved => not_ready("is already resolved"),
+                PoolPhase::Expired => not_ready("has timed out"),
+            }
+        }
+        _ => Err(OracleError::NotFound(format!("round {address}"))),
+    }
+}
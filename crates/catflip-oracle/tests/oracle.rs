@@ -0,0 +1,114 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use catflip::{instructions::BetGuards, state::BetRound};
+use catflip_client::{
+    beacons::Beacon,
+    instructions,
+    pda::{bet_round_address, vault_address},
+    state::fetch_bet_round,
+    testing::{program_test, TestChain},
+    vrf, Chain,
+};
+use catflip_oracle::{router, AppState};
+use http_body_util::BodyExt;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use tower::ServiceExt;
+
+async fn send(chain: &TestChain, payer: &Keypair, ixs: &[Instruction]) {
+    let blockhash = chain.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &[payer], blockhash);
+    chain.send_transaction(&tx).await.unwrap();
+}
+
+async fn get(app: Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    let response = app
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn signs_only_rounds_waiting_for_randomness() {
+    let mut context = program_test().start_with_context().await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let chain = TestChain::new(context.banks_client.clone());
+    send(
+        &chain,
+        &payer,
+        &[
+            instructions::initialize(
+                &catflip::ID,
+                &payer.pubkey(),
+                LAMPORTS_PER_SOL / 1000,
+                1000,
+                200,
+            ),
+            instructions::fund_vault(&catflip::ID, &payer.pubkey(), 10 * LAMPORTS_PER_SOL),
+        ],
+    )
+    .await;
+
+    let slot = chain.get_slot().await.unwrap();
+    send(
+        &chain,
+        &payer,
+        &[instructions::bet(
+            &catflip::ID,
+            &payer.pubkey(),
+            slot,
+            LAMPORTS_PER_SOL / 10,
+            vrf::player_seed(),
+            BetGuards::default(),
+        )],
+    )
+    .await;
+    let round = bet_round_address(&catflip::ID, &payer.pubkey(), slot).0;
+    let bet_round = fetch_bet_round(&chain, &round).await.unwrap();
+
+    let oracle = Keypair::new();
+    let app = router(AppState::new(
+        chain.clone(),
+        Keypair::from_bytes(&oracle.to_bytes()).unwrap(),
+        catflip::ID,
+    ));
+
+    let (status, body) = get(app.clone(), &format!("/beacons/{round}")).await;
+    assert_eq!(status, StatusCode::OK);
+    let beacon: Beacon = serde_json::from_slice(&body).unwrap();
+    assert_eq!(beacon.oracle, oracle.pubkey().to_string());
+    let signature: Signature = beacon.signature.parse().unwrap();
+    assert!(signature.verify(oracle.pubkey().as_ref(), &bet_round.vrf_input(&round)));
+
+    // Nothing to sign for an unknown address or an account that isn't a
+    // round.
+    let vault = vault_address(&catflip::ID).0;
+    for uri in [
+        format!("/beacons/{}", Pubkey::new_unique()),
+        format!("/beacons/{vault}"),
+    ] {
+        assert_eq!(get(app.clone(), &uri).await.0, StatusCode::NOT_FOUND);
+    }
+    assert_eq!(
+        get(app.clone(), "/beacons/not-a-key").await.0,
+        StatusCode::BAD_REQUEST
+    );
+
+    // Once the round has timed out it can only be refunded.
+    context
+        .warp_to_slot(bet_round.slot + BetRound::TIMEOUT_SLOTS + 1)
+        .unwrap();
+    let (status, _) = get(app, &format!("/beacons/{round}")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
@@ -0,0 +1,116 @@
+//! Every game the casino runs, as the simulator sees it.
+//!
+//! The Solana games call straight into the programs' own payout, odds and
+//! exposure functions. The Sui games are Move, so their formulas are ported
+//! line for line in [`sui`], including the exact ranges their random draws
+//! come from.
+
+pub mod solana;
+pub mod sui;
+
+use catflip_minimal::Odds;
+use clap::ValueEnum;
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+
+pub type SimRng = ChaCha8Rng;
+
+pub trait Game: Sync {
+    fn name(&self) -> String;
+
+    /// Smallest stake the game takes.
+    fn min_bet(&self) -> u64;
+
+    /// Whether the house takes `stake` while holding `bankroll`, applying the
+    /// same solvency and exposure checks the program does.
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool;
+
+    /// Plays one bet and returns what the player is paid, stake included;
+    /// zero on a loss.
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64;
+
+    /// Exact expected payout per unit staked, from the game's outcome
+    /// distribution rather than from sampling.
+    fn expected_rtp(&self, stake: u64) -> f64;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameKind {
+    /// Anchor catflip, with the configured edge and exposure
+    Catflip,
+    CatflipMinimal,
+    CatflipUltra,
+    SuiFlip,
+    SuiCrash,
+    SuiPump,
+    SuiRevolver,
+}
+
+impl GameKind {
+    pub const ALL: [GameKind; 7] = [
+        GameKind::Catflip,
+        GameKind::CatflipMinimal,
+        GameKind::CatflipUltra,
+        GameKind::SuiFlip,
+        GameKind::SuiCrash,
+        GameKind::SuiPump,
+        GameKind::SuiRevolver,
+    ];
+}
+
+/// Settings for the games that have them. Defaults match the values the
+/// programs are deployed or documented with.
+#[derive(Clone, Debug)]
+pub struct GameParams {
+    pub house_edge_bps: u16,
+    pub max_exposure_bps: u16,
+    /// catflip and catflip-minimal take their minimum from vault config.
+    pub min_bet: u64,
+    /// catflip-minimal's odds, as set by `UpdateConfig`.
+    pub minimal_odds: Odds,
+    pub crash_target_x100: u64,
+    pub pump_threshold: u8,
+}
+
+impl Default for GameParams {
+    fn default() -> Self {
+        Self {
+            house_edge_bps: 200,
+            max_exposure_bps: 1000,
+            min_bet: 1_000_000,
+            minimal_odds: Odds::default(),
+            crash_target_x100: 200,
+            pump_threshold: 50,
+        }
+    }
+}
+
+pub fn build(kind: GameKind, params: &GameParams) -> Box<dyn Game> {
+    match kind {
+        GameKind::Catflip => Box::new(solana::Catflip::new(params)),
+        GameKind::CatflipMinimal => Box::new(solana::CatflipMinimal {
+            min_bet: params.min_bet,
+            odds: params.minimal_odds,
+        }),
+        GameKind::CatflipUltra => Box::new(solana::CatflipUltra),
+        GameKind::SuiFlip => Box::new(sui::Flip),
+        GameKind::SuiCrash => Box::new(sui::Crash {
+            target_x100: params.crash_target_x100,
+        }),
+        GameKind::SuiPump => Box::new(sui::Pump {
+            threshold: params.pump_threshold,
+        }),
+        GameKind::SuiRevolver => Box::new(sui::Revolver),
+    }
+}
+
+/// Probability that a uniform `u64` leaves a remainder below `below` when
+/// divided by `modulus`, modulo bias included.
+pub(crate) fn modulo_win_probability(modulus: u64, below: u64) -> f64 {
+    let space = u64::MAX as u128 + 1;
+    let full_cycles = space / modulus as u128;
+    let remainder = space % modulus as u128;
+    let wins = full_cycles * below as u128 + remainder.min(below as u128);
+    wins as f64 / space as f64
+}
@@ -0,0 +1,157 @@
+//! The Solana programs, driven through their own payout and odds functions.
+//!
+//! On chain, catflip settles from an ECVRF proof or threshold oracle
+//! beacons, and catflip-minimal and catflip-ultra resolve a committed bet
+//! from a later SlotHashes entry. Only their instant bets still roll from
+//! the clock, which catflip-minimal refuses unless the authority opts in.
+//! The simulator feeds all of them uniform random values instead: it
+//! measures the odds as designed, not the randomness sources.
+
+use anchor_lang::{prelude::Pubkey, solana_program::hash::Hash};
+use catflip::state::{BetRound, Vault};
+use catflip_minimal::{odds, Odds};
+use rand::{Rng, RngCore};
+
+use super::{modulo_win_probability, Game, GameParams, SimRng};
+
+pub struct Catflip {
+    vault: Vault,
+}
+
+impl Catflip {
+    pub fn new(params: &GameParams) -> Self {
+        Self {
+            vault: Vault {
+                authority: Pubkey::default(),
+                bump: 0,
+                is_paused: false,
+                min_bet_lamports: params.min_bet,
+                max_exposure_bps: params.max_exposure_bps,
+                house_edge_bps: params.house_edge_bps,
+                total_volume: 0,
+                total_bets: 0,
+                total_wins: 0,
+                imported_principal: 0,
+                vrf_public_key: [0; 32],
+                oracle_count: 0,
+                oracle_threshold: 0,
+                oracles: [Pubkey::default(); Vault::MAX_ORACLES],
+                player_liabilities: 0,
+                reserved_payouts: 0,
+                rake_bps: 0,
+            },
+        }
+    }
+}
+
+impl Game for Catflip {
+    fn name(&self) -> String {
+        format!(
+            "catflip (edge {} bps, exposure {} bps)",
+            self.vault.house_edge_bps, self.vault.max_exposure_bps
+        )
+    }
+
+    fn min_bet(&self) -> u64 {
+        self.vault.min_bet_lamports
+    }
+
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool {
+        stake >= self.vault.min_bet_lamports
+            && self
+                .vault
+                .max_bet(bankroll)
+                .is_some_and(|max_bet| stake <= max_bet)
+            && self
+                .vault
+                .potential_payout(stake)
+                .is_some_and(|payout| bankroll >= payout)
+    }
+
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64 {
+        if BetRound::is_winning_roll(rng.next_u64()) {
+            self.vault.potential_payout(stake).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    fn expected_rtp(&self, stake: u64) -> f64 {
+        let payout = self.vault.potential_payout(stake).unwrap_or(0);
+        0.5 * payout as f64 / stake as f64
+    }
+}
+
+pub struct CatflipMinimal {
+    pub min_bet: u64,
+    pub odds: Odds,
+}
+
+impl Game for CatflipMinimal {
+    fn name(&self) -> String {
+        let Odds {
+            win_probability_bps,
+            payout_multiplier_bps,
+        } = self.odds;
+        format!(
+            "catflip-minimal (win {win_probability_bps} bps, payout {payout_multiplier_bps} bps)"
+        )
+    }
+
+    fn min_bet(&self) -> u64 {
+        self.min_bet
+    }
+
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool {
+        // The payout check runs after the stake has reached the vault.
+        stake >= self.min_bet
+            && self
+                .odds
+                .payout(stake)
+                .is_some_and(|payout| bankroll.saturating_add(stake) >= payout)
+    }
+
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64 {
+        let roll = odds::roll(Hash::new_from_array(rng.gen()));
+        if self.odds.wins(roll) {
+            self.odds.payout(stake).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    fn expected_rtp(&self, stake: u64) -> f64 {
+        // The roll is unbiased, so this is exact.
+        let win = self.odds.win_probability_bps as f64 / odds::BPS as f64;
+        win * self.odds.payout(stake).unwrap_or(0) as f64 / stake as f64
+    }
+}
+
+pub struct CatflipUltra;
+
+impl Game for CatflipUltra {
+    fn name(&self) -> String {
+        "catflip-ultra".to_string()
+    }
+
+    fn min_bet(&self) -> u64 {
+        1
+    }
+
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool {
+        stake > 0 && catflip_ultra::payout(stake).is_some_and(|payout| bankroll >= payout)
+    }
+
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64 {
+        if catflip_ultra::is_winning_roll(rng.next_u64()) {
+            catflip_ultra::payout(stake).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    fn expected_rtp(&self, stake: u64) -> f64 {
+        let win = modulo_win_probability(100, catflip_ultra::WIN_RATE_PERCENT);
+        win * catflip_ultra::payout(stake).unwrap_or(0) as f64 / stake as f64
+    }
+}
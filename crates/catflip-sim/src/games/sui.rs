@@ -0,0 +1,210 @@
+//! Ports of the Sui Move games in `sui-catsino/sources`. Each constant and
+//! formula mirrors its Move counterpart; keep them in step when the Move code
+//! changes. `sui::random::generate_u64_in_range(min, max)` is inclusive at
+//! both ends, and the ranges below keep that.
+
+use rand::Rng;
+
+use super::{Game, SimRng};
+
+/// `casino::place_bet`: a roll in `[1, 100]` wins at or below `WIN_RATE`.
+pub struct Flip;
+
+impl Flip {
+    const MIN_BET: u64 = 1_000_000;
+    const WIN_RATE: u64 = 47;
+    const PAYOUT_MULTIPLIER: u64 = 200;
+
+    fn payout(stake: u64) -> Option<u64> {
+        Some(stake.checked_mul(Self::PAYOUT_MULTIPLIER)? / 100)
+    }
+}
+
+impl Game for Flip {
+    fn name(&self) -> String {
+        "sui-flip".to_string()
+    }
+
+    fn min_bet(&self) -> u64 {
+        Self::MIN_BET
+    }
+
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool {
+        stake >= Self::MIN_BET && Self::payout(stake).is_some_and(|payout| bankroll >= payout)
+    }
+
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64 {
+        let random_value = rng.gen_range(1..=100);
+        if random_value <= Self::WIN_RATE {
+            Self::payout(stake).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    fn expected_rtp(&self, stake: u64) -> f64 {
+        let payout = Self::payout(stake).unwrap_or(0);
+        Self::WIN_RATE as f64 / 100.0 * payout as f64 / stake as f64
+    }
+}
+
+/// `crash::play`: wins if the generated crash point reaches the target.
+pub struct Crash {
+    pub target_x100: u64,
+}
+
+impl Crash {
+    const MIN_BET: u64 = 100_000_000;
+    const MIN_TARGET_X100: u64 = 101;
+    const MAX_TARGET_X100: u64 = 3000;
+    const PAYOUT_FACTOR: u64 = 9700;
+
+    fn payout(stake: u64, target_x100: u64) -> Option<u64> {
+        Some(
+            stake
+                .checked_mul(target_x100)?
+                .checked_mul(Self::PAYOUT_FACTOR)?
+                / 1_000_000,
+        )
+    }
+
+    /// `generate_crash_multiplier` for a roll in `[1, 10000]`.
+    fn crash_x100(random_roll: u64) -> u64 {
+        if random_roll <= 5000 {
+            Self::MIN_TARGET_X100 + (random_roll * 99) / 5000
+        } else if random_roll <= 8000 {
+            200 + ((random_roll - 5000) * 300) / 3000
+        } else if random_roll <= 9500 {
+            500 + ((random_roll - 8000) * 500) / 1500
+        } else {
+            1000 + ((random_roll - 9500) * 2000) / 500
+        }
+    }
+}
+
+impl Game for Crash {
+    fn name(&self) -> String {
+        format!("sui-crash (target {:.2}x)", self.target_x100 as f64 / 100.0)
+    }
+
+    fn min_bet(&self) -> u64 {
+        Self::MIN_BET
+    }
+
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool {
+        stake >= Self::MIN_BET
+            && (Self::MIN_TARGET_X100..=Self::MAX_TARGET_X100).contains(&self.target_x100)
+            && Self::payout(stake, self.target_x100).is_some_and(|payout| bankroll >= payout)
+    }
+
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64 {
+        let random_roll = rng.gen_range(0..=9999) + 1;
+        if self.target_x100 <= Self::crash_x100(random_roll) {
+            Self::payout(stake, self.target_x100).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    fn expected_rtp(&self, stake: u64) -> f64 {
+        let wins = (1..=10_000)
+            .filter(|&roll| self.target_x100 <= Self::crash_x100(roll))
+            .count();
+        let payout = Self::payout(stake, self.target_x100).unwrap_or(0);
+        wins as f64 / 10_000.0 * payout as f64 / stake as f64
+    }
+}
+
+/// `pump::play_pump_or_dump`: a uniform result in `[0, 100)` wins below the
+/// chosen threshold.
+pub struct Pump {
+    pub threshold: u8,
+}
+
+impl Pump {
+    const MIN_BET: u64 = 10_000_000;
+    const MAX_BET: u64 = 10_000_000_000;
+    const MAX_EXPOSURE_PERCENTAGE: u64 = 20;
+
+    fn payout(stake: u64, threshold: u8) -> Option<u64> {
+        Some(stake.checked_mul(97)? / threshold as u64)
+    }
+}
+
+impl Game for Pump {
+    fn name(&self) -> String {
+        format!("sui-pump (threshold {})", self.threshold)
+    }
+
+    fn min_bet(&self) -> u64 {
+        Self::MIN_BET
+    }
+
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool {
+        (1..=98).contains(&self.threshold)
+            && (Self::MIN_BET..=Self::MAX_BET).contains(&stake)
+            && Self::payout(stake, self.threshold).is_some_and(|payout| {
+                bankroll >= payout
+                    && payout as u128 * 100
+                        <= bankroll as u128 * Self::MAX_EXPOSURE_PERCENTAGE as u128
+            })
+    }
+
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64 {
+        // `generate_uniform_u8` rejection-samples, so the result is uniform.
+        let result: u8 = rng.gen_range(0..100);
+        if result < self.threshold {
+            Self::payout(stake, self.threshold).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    fn expected_rtp(&self, stake: u64) -> f64 {
+        let payout = Self::payout(stake, self.threshold).unwrap_or(0);
+        self.threshold as f64 / 100.0 * payout as f64 / stake as f64
+    }
+}
+
+/// `revolver::play`: an angle drawn from `[0, 360]` wins below 45 degrees.
+/// That range has 361 values, not 360.
+pub struct Revolver;
+
+impl Revolver {
+    const MIN_BET: u64 = 1_000_000;
+    const WIN_ANGLE_DEGREES: u64 = 45;
+    const PAYOUT_NUMERATOR: u64 = 194;
+    const PAYOUT_DENOMINATOR: u64 = 25;
+
+    fn payout(stake: u64) -> Option<u64> {
+        Some(stake.checked_mul(Self::PAYOUT_NUMERATOR)? / Self::PAYOUT_DENOMINATOR)
+    }
+}
+
+impl Game for Revolver {
+    fn name(&self) -> String {
+        "sui-revolver".to_string()
+    }
+
+    fn min_bet(&self) -> u64 {
+        Self::MIN_BET
+    }
+
+    fn accepts(&self, stake: u64, bankroll: u64) -> bool {
+        stake >= Self::MIN_BET && Self::payout(stake).is_some_and(|payout| bankroll >= payout)
+    }
+
+    fn play(&self, stake: u64, rng: &mut SimRng) -> u64 {
+        let angle_deg = rng.gen_range(0..=360);
+        if angle_deg < Self::WIN_ANGLE_DEGREES {
+            Self::payout(stake).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    fn expected_rtp(&self, stake: u64) -> f64 {
+        let payout = Self::payout(stake).unwrap_or(0);
+        Self::WIN_ANGLE_DEGREES as f64 / 361.0 * payout as f64 / stake as f64
+    }
+}
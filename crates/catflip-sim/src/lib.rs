@@ -0,0 +1,12 @@
+//! Monte Carlo simulator for the casino's games.
+//!
+//! Plays millions of fixed-size bets per game against a house bankroll and
+//! reports the empirical RTP next to the exact one, the variance of a single
+//! bet, the distribution of drawdowns and final bankrolls, and how often the
+//! house ends up unable to take the bet at all.
+
+pub mod games;
+pub mod sim;
+
+pub use games::{build, Game, GameKind, GameParams};
+pub use sim::{simulate, SimConfig, SimError, SimReport};
@@ -0,0 +1,128 @@
+use anyhow::bail;
+use catflip_minimal::Odds;
+use catflip_sim::{build, simulate, GameKind, GameParams, SimConfig};
+use clap::{Parser, ValueEnum};
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Display,
+    Json,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "catflip-sim",
+    version,
+    about = "Simulate house edge, drawdowns and risk of ruin for each game"
+)]
+struct Args {
+    /// Games to simulate; all of them by default
+    #[arg(long, value_enum, value_delimiter = ',')]
+    game: Vec<GameKind>,
+
+    /// Starting house bankroll, in SOL/SUI
+    #[arg(long, default_value_t = 100.0)]
+    bankroll: f64,
+
+    /// Stake of every bet, in SOL/SUI
+    #[arg(long, default_value_t = 0.1)]
+    stake: f64,
+
+    /// Bets per run
+    #[arg(long, default_value_t = 10_000)]
+    bets: u64,
+
+    #[arg(long, default_value_t = 1_000)]
+    runs: u64,
+
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+
+    /// Worker threads; defaults to the number of CPUs
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// catflip house edge
+    #[arg(long, default_value_t = 200)]
+    house_edge_bps: u16,
+
+    /// catflip max exposure per bet, as a share of the vault
+    #[arg(long, default_value_t = 1000)]
+    max_exposure_bps: u16,
+
+    /// Minimum bet for catflip and catflip-minimal, in SOL
+    #[arg(long, default_value_t = 0.001)]
+    min_bet: f64,
+
+    /// catflip-minimal win probability
+    #[arg(long, default_value_t = Odds::default().win_probability_bps)]
+    win_probability_bps: u16,
+
+    /// catflip-minimal payout multiplier, stake included: 19600 pays 1.96x
+    #[arg(long, default_value_t = Odds::default().payout_multiplier_bps)]
+    payout_multiplier_bps: u32,
+
+    /// Crash cash-out target, e.g. 2.0 for 2x
+    #[arg(long, default_value_t = 2.0)]
+    crash_target: f64,
+
+    /// Pump threshold (1-98): win chance in percent
+    #[arg(long, default_value_t = 50)]
+    pump_threshold: u8,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Display)]
+    output: OutputFormat,
+}
+
+fn base_units(amount: f64) -> anyhow::Result<u64> {
+    if !amount.is_finite() || amount < 0.0 {
+        bail!("invalid amount {amount}");
+    }
+    Ok((amount * 1e9).round() as u64)
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let params = GameParams {
+        house_edge_bps: args.house_edge_bps,
+        max_exposure_bps: args.max_exposure_bps,
+        min_bet: base_units(args.min_bet)?,
+        minimal_odds: Odds {
+            win_probability_bps: args.win_probability_bps,
+            payout_multiplier_bps: args.payout_multiplier_bps,
+        },
+        crash_target_x100: (args.crash_target * 100.0).round() as u64,
+        pump_threshold: args.pump_threshold,
+    };
+    let config = SimConfig {
+        bankroll: base_units(args.bankroll)?,
+        stake: base_units(args.stake)?,
+        bets_per_run: args.bets,
+        runs: args.runs,
+        seed: args.seed,
+        threads: args
+            .threads
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get())),
+    };
+    let games = if args.game.is_empty() {
+        GameKind::ALL.to_vec()
+    } else {
+        args.game
+    };
+
+    let mut reports = Vec::new();
+    for kind in games {
+        let report = simulate(build(kind, &params).as_ref(), &config)?;
+        if args.output == OutputFormat::Display {
+            println!("{report}\n");
+        }
+        reports.push(report);
+    }
+    if args.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use rand::SeedableRng;
+use serde::Serialize;
+use std::{fmt, thread};
+use thiserror::Error;
+
+use crate::games::{Game, SimRng};
+
+/// One simulation: `runs` independent sequences of up to `bets_per_run`
+/// fixed-size bets against a house starting with `bankroll`.
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    pub bankroll: u64,
+    pub stake: u64,
+    pub bets_per_run: u64,
+    pub runs: u64,
+    pub seed: u64,
+    /// Worker threads. Results don't depend on it: every run draws from its
+    /// own stream of the seeded RNG and runs are combined in order.
+    pub threads: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum SimError {
+    #[error("{game} does not accept a stake of {stake} against a bankroll of {bankroll}")]
+    StakeRejected {
+        game: String,
+        stake: u64,
+        bankroll: u64,
+    },
+
+    #[error("runs and bets per run must both be positive")]
+    Empty,
+}
+
+/// Quantiles of a per-run quantity.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub min: f64,
+    pub p1: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    fn new(mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+        let at = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+        Self {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            min: values[0],
+            p1: at(0.01),
+            p10: at(0.10),
+            p50: at(0.50),
+            p90: at(0.90),
+            p99: at(0.99),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimReport {
+    pub game: String,
+    pub runs: u64,
+    /// Bets actually played; runs stop early on ruin.
+    pub bets: u64,
+    pub staked: u128,
+    pub paid: u128,
+    /// Expected payout per unit staked, from the game's odds.
+    pub expected_rtp: f64,
+    /// Observed payout per unit staked.
+    pub empirical_rtp: f64,
+    /// One minus the empirical RTP.
+    pub house_edge: f64,
+    /// Variance of a single bet's payout as a multiple of its stake.
+    pub variance: f64,
+    /// Largest fall from a running peak, as a fraction of that peak.
+    pub max_drawdown: Distribution,
+    /// House bankroll at the end of each run, in base units.
+    pub final_bankroll: Distribution,
+    /// Share of runs in which the house could no longer cover the stake.
+    pub ruin_probability: f64,
+}
+
+struct RunResult {
+    bets: u64,
+    staked: u128,
+    paid: u128,
+    sum: f64,
+    sum_of_squares: f64,
+    max_drawdown: f64,
+    final_bankroll: u64,
+    ruined: bool,
+}
+
+/// Runs `config` against `game`. A run ends in ruin the first time the game's
+/// own checks would turn down `config.stake`: past that point the house can't
+/// take the bets it is being simulated on.
+pub fn simulate(game: &dyn Game, config: &SimConfig) -> Result<SimReport, SimError> {
+    if config.runs == 0 || config.bets_per_run == 0 {
+        return Err(SimError::Empty);
+    }
+    if !game.accepts(config.stake, config.bankroll) {
+        return Err(SimError::StakeRejected {
+            game: game.name(),
+            stake: config.stake,
+            bankroll: config.bankroll,
+        });
+    }
+
+    let threads = config.threads.clamp(1, config.runs as usize);
+    let mut results: Vec<Option<RunResult>> = (0..config.runs).map(|_| None).collect();
+    thread::scope(|scope| {
+        let chunk = (config.runs as usize).div_ceil(threads);
+        for (index, slots) in results.chunks_mut(chunk).enumerate() {
+            let first_run = (index * chunk) as u64;
+            scope.spawn(move || {
+                for (offset, slot) in slots.iter_mut().enumerate() {
+                    *slot = Some(run(game, config, first_run + offset as u64));
+                }
+            });
+        }
+    });
+    let results: Vec<RunResult> = results.into_iter().map(Option::unwrap).collect();
+
+    let bets: u64 = results.iter().map(|r| r.bets).sum();
+    let staked: u128 = results.iter().map(|r| r.staked).sum();
+    let paid: u128 = results.iter().map(|r| r.paid).sum();
+    let mean = results.iter().map(|r| r.sum).sum::<f64>() / bets as f64;
+    let mean_of_squares = results.iter().map(|r| r.sum_of_squares).sum::<f64>() / bets as f64;
+    let empirical_rtp = paid as f64 / staked as f64;
+    let ruined = results.iter().filter(|r| r.ruined).count();
+
+    Ok(SimReport {
+        game: game.name(),
+        runs: config.runs,
+        bets,
+        staked,
+        paid,
+        expected_rtp: game.expected_rtp(config.stake),
+        empirical_rtp,
+        house_edge: 1.0 - empirical_rtp,
+        variance: mean_of_squares - mean * mean,
+        max_drawdown: Distribution::new(results.iter().map(|r| r.max_drawdown).collect()),
+        final_bankroll: Distribution::new(
+            results.iter().map(|r| r.final_bankroll as f64).collect(),
+        ),
+        ruin_probability: ruined as f64 / config.runs as f64,
+    })
+}
+
+fn run(game: &dyn Game, config: &SimConfig, index: u64) -> RunResult {
+    let mut rng = SimRng::seed_from_u64(config.seed);
+    rng.set_stream(index);
+
+    let stake = config.stake;
+    let mut result = RunResult {
+        bets: 0,
+        staked: 0,
+        paid: 0,
+        sum: 0.0,
+        sum_of_squares: 0.0,
+        max_drawdown: 0.0,
+        final_bankroll: config.bankroll,
+        ruined: false,
+    };
+    let mut bankroll = config.bankroll;
+    let mut peak = bankroll;
+
+    for _ in 0..config.bets_per_run {
+        if !game.accepts(stake, bankroll) {
+            result.ruined = true;
+            break;
+        }
+        let payout = game.play(stake, &mut rng);
+        // `accepts` guarantees the house could cover `payout`.
+        bankroll = bankroll + stake - payout;
+
+        let multiple = payout as f64 / stake as f64;
+        result.bets += 1;
+        result.staked += stake as u128;
+        result.paid += payout as u128;
+        result.sum += multiple;
+        result.sum_of_squares += multiple * multiple;
+
+        if bankroll > peak {
+            peak = bankroll;
+        } else {
+            let drawdown = (peak - bankroll) as f64 / peak as f64;
+            result.max_drawdown = result.max_drawdown.max(drawdown);
+        }
+    }
+
+    result.final_bankroll = bankroll;
+    result
+}
+
+impl fmt::Display for SimReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNIT: f64 = 1e9;
+        writeln!(f, "{}", self.game)?;
+        writeln!(f, "  bets            {} over {} runs", self.bets, self.runs)?;
+        writeln!(
+            f,
+            "  RTP             {:.4}% (expected {:.4}%)",
+            self.empirical_rtp * 100.0,
+            self.expected_rtp * 100.0
+        )?;
+        writeln!(f, "  house edge      {:.4}%", self.house_edge * 100.0)?;
+        writeln!(
+            f,
+            "  variance        {:.4} (std dev {:.4}x stake)",
+            self.variance,
+            self.variance.sqrt()
+        )?;
+        writeln!(
+            f,
+            "  max drawdown    p50 {:.2}%  p90 {:.2}%  p99 {:.2}%  max {:.2}%",
+            self.max_drawdown.p50 * 100.0,
+            self.max_drawdown.p90 * 100.0,
+            self.max_drawdown.p99 * 100.0,
+            self.max_drawdown.max * 100.0
+        )?;
+        writeln!(
+            f,
+            "  final bankroll  p1 {:.4}  p50 {:.4}  p99 {:.4}",
+            self.final_bankroll.p1 / UNIT,
+            self.final_bankroll.p50 / UNIT,
+            self.final_bankroll.p99 / UNIT
+        )?;
+        write!(f, "  ruin            {:.4}%", self.ruin_probability * 100.0)
+    }
+}
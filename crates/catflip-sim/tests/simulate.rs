@@ -0,0 +1,106 @@
+use catflip_sim::{build, simulate, GameKind, GameParams, SimConfig, SimError};
+
+const SOL: u64 = 1_000_000_000;
+
+fn config(runs: u64, bets_per_run: u64) -> SimConfig {
+    SimConfig {
+        bankroll: 100 * SOL,
+        stake: SOL / 10,
+        bets_per_run,
+        runs,
+        seed: 7,
+        threads: 4,
+    }
+}
+
+#[test]
+fn empirical_rtp_converges_to_the_exact_odds() {
+    let params = GameParams::default();
+    for kind in GameKind::ALL {
+        let report = simulate(build(kind, &params).as_ref(), &config(100, 5_000)).unwrap();
+        assert_eq!(report.bets, 500_000, "{}", report.game);
+
+        // Five standard errors of the mean payout multiple.
+        let tolerance = 5.0 * (report.variance / report.bets as f64).sqrt();
+        assert!(
+            (report.empirical_rtp - report.expected_rtp).abs() < tolerance,
+            "{}: empirical {} vs expected {} (tolerance {tolerance})",
+            report.game,
+            report.empirical_rtp,
+            report.expected_rtp,
+        );
+    }
+}
+
+#[test]
+fn exact_rtp_matches_the_documented_odds() {
+    let params = GameParams::default();
+    let rtp = |kind| build(kind, &params).expected_rtp(SOL);
+
+    // 50% at 2x less a 2% edge.
+    assert!((rtp(GameKind::Catflip) - 0.98).abs() < 1e-12);
+    // 49% at 1.96x. Ultra adds a vanishing modulo bias from its u64 roll;
+    // catflip-minimal rejection-samples and has none.
+    assert!((rtp(GameKind::CatflipMinimal) - 0.9604).abs() < 1e-12);
+    assert!((rtp(GameKind::CatflipUltra) - 0.9604).abs() < 1e-9);
+    // The README's 47% at 2x: a 6% edge.
+    assert!((rtp(GameKind::SuiFlip) - 0.94).abs() < 1e-12);
+    // Crash points reach 2.00x on 5001 of 10000 rolls, paid at 1.94x.
+    assert!((rtp(GameKind::SuiCrash) - 0.5001 * 1.94).abs() < 1e-12);
+    // Pump pays 97/threshold on a threshold% chance: always 97%.
+    assert!((rtp(GameKind::SuiPump) - 0.97).abs() < 1e-12);
+    // Revolver draws from 361 angles, not 360.
+    assert!((rtp(GameKind::SuiRevolver) - 45.0 / 361.0 * 7.76).abs() < 1e-12);
+}
+
+#[test]
+fn results_depend_on_the_seed_but_not_the_thread_count() {
+    let game = build(GameKind::SuiRevolver, &GameParams::default());
+    let mut config = config(16, 2_000);
+
+    config.threads = 1;
+    let single = simulate(game.as_ref(), &config).unwrap();
+    config.threads = 5;
+    assert_eq!(simulate(game.as_ref(), &config).unwrap(), single);
+
+    config.seed += 1;
+    assert_ne!(simulate(game.as_ref(), &config).unwrap(), single);
+}
+
+#[test]
+fn thin_bankrolls_get_ruined() {
+    let game = build(GameKind::SuiFlip, &GameParams::default());
+
+    // A 1 SOL house taking 0.1 SOL flips at 2x turns bets away after nine
+    // net losses: gambler's ruin puts that at (47/53)^9, about 34%.
+    let thin = SimConfig {
+        bankroll: SOL,
+        ..config(200, 2_000)
+    };
+    let report = simulate(game.as_ref(), &thin).unwrap();
+    assert!((0.25..0.5).contains(&report.ruin_probability), "{report}");
+    assert!(report.max_drawdown.max > 0.8);
+
+    let deep = SimConfig {
+        bankroll: 10_000 * SOL,
+        ..config(200, 2_000)
+    };
+    let report = simulate(game.as_ref(), &deep).unwrap();
+    assert_eq!(report.ruin_probability, 0.0);
+    assert!(report.max_drawdown.max < 0.01);
+}
+
+#[test]
+fn exposure_limits_reject_stakes_up_front() {
+    // catflip caps a bet at max_exposure_bps of the vault: 10% of 1 SOL.
+    let game = build(GameKind::Catflip, &GameParams::default());
+    let config = SimConfig {
+        bankroll: SOL,
+        stake: SOL / 5,
+        ..config(1, 1)
+    };
+    assert!(matches!(
+        simulate(game.as_ref(), &config),
+        Err(SimError::StakeRejected { .. })
+    ));
+}
@@ -0,0 +1,76 @@
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+/// Errors returned as `ProgramError::Custom`. Codes match what the Anchor
+/// catflip program returns for the same failure: 6000 and up are
+/// `CatflipError` variants, and 2000-3999 are the Anchor framework codes for
+/// its account checks. Failures catflip has no counterpart for start at 7000,
+/// clear of any `CatflipError` variants added later.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum CatflipMinimalError {
+    #[error("Bet amount is below minimum")]
+    BetBelowMinimum = 6000,
+
+    #[error("Bet amount exceeds maximum exposure")]
+    BetExceedsMaxExposure = 6001,
+
+    #[error("Insufficient vault balance for potential payout")]
+    InsufficientVaultBalance = 6002,
+
+    #[error("Game is currently paused")]
+    GamePaused = 6003,
+
+    #[error("Unauthorized")]
+    Unauthorized = 6007,
+
+    #[error("Math overflow")]
+    MathOverflow = 6008,
+
+    #[error("Invalid vault configuration")]
+    InvalidConfig = 6009,
+
+    #[error("A seeds constraint was violated")]
+    ConstraintSeeds = 2006,
+
+    #[error("The given account is owned by a different program than expected")]
+    AccountOwnedByWrongProgram = 3007,
+
+    #[error("Program ID was not as expected")]
+    InvalidProgramId = 3008,
+
+    #[error("The given account did not sign")]
+    AccountNotSigner = 3010,
+
+    #[error("The program expected this account to be already initialized")]
+    AccountNotInitialized = 3012,
+
+    #[error("The given public key does not match the required sysvar")]
+    AccountSysvarMismatch = 3015,
+
+    #[error("Bets cannot be placed through CPI")]
+    CpiNotAllowed = 7000,
+
+    #[error("Bet must be the last instruction in the transaction")]
+    InstructionAfterBet = 7001,
+
+    #[error("Stake would leave the player below the rent-exempt minimum")]
+    PlayerBelowRentExempt = 7002,
+
+    #[error("Vault must be paused first")]
+    VaultNotPaused = 7003,
+
+    #[error("Bet cannot be resolved before its target slot is hashed")]
+    BetNotReady = 7004,
+
+    #[error("Instant bets are turned off; commit the bet instead")]
+    InstantBetsDisabled = 7005,
+
+    #[error("Vault has unresolved commitments")]
+    CommitmentsOpen = 7006,
+}
+
+impl From<CatflipMinimalError> for ProgramError {
+    fn from(e: CatflipMinimalError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,63 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+    msg,
+    pubkey,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::{instructions, Sysvar},
+};
+
+use crate::errors::CatflipMinimalError;
+
+/// Compute budget instructions are applied before the transaction runs, so
+/// they can follow a bet without seeing its outcome.
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Rejects a bet whose outcome something else in the transaction could react
+/// to.
+///
+/// `Bet` takes the stake and pays out in one instruction, so any code that
+/// runs after it and can fail the transaction gets a free option on the
+/// result: it can look at the player's balance and abort a loss. That code can
+/// be a program that invoked `Bet` through CPI, which regains control when the
+/// call returns, or any later instruction in the transaction; even a plain
+/// system transfer of the right amount fails only when the bet was lost. So
+/// bets must be top-level and last, apart from compute budget instructions.
+/// Earlier instructions are fine: they have run before the roll.
+pub fn check_bet_is_final(instructions_sysvar: &AccountInfo) -> ProgramResult {
+    if get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT {
+        msg!("Bets cannot be placed through CPI");
+        return Err(CatflipMinimalError::CpiNotAllowed.into());
+    }
+
+    if !instructions::check_id(instructions_sysvar.key) {
+        msg!("Expected the instructions sysvar, got {}", instructions_sysvar.key);
+        return Err(CatflipMinimalError::InvalidProgramId.into());
+    }
+
+    let current = instructions::load_current_index_checked(instructions_sysvar)? as usize;
+    let mut index = current + 1;
+    while let Ok(instruction) = instructions::load_instruction_at_checked(index, instructions_sysvar) {
+        if instruction.program_id != COMPUTE_BUDGET_PROGRAM_ID {
+            msg!("Bet must be the last instruction, found {} after it", instruction.program_id);
+            return Err(CatflipMinimalError::InstructionAfterBet.into());
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+/// Rejects a stake that leaves `player` holding some lamports but less than
+/// the rent-exempt minimum. The runtime would fail such a transaction only
+/// when the bet is lost, since a win tops the account back up, which is the
+/// same free option as above.
+pub fn check_player_rent(player: &AccountInfo) -> ProgramResult {
+    let lamports = player.lamports();
+    if lamports != 0 && lamports < Rent::get()?.minimum_balance(player.data_len()) {
+        msg!("Stake would leave the player below the rent-exempt minimum");
+        return Err(CatflipMinimalError::PlayerBelowRentExempt.into());
+    }
+    Ok(())
+}
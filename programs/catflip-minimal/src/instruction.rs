@@ -0,0 +1,150 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+
+use crate::odds::Odds;
+
+// Instructions
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub enum CatflipInstruction {
+    /// Initialize vault, creating it at `vault_address`
+    /// Accounts: [signer, writable vault_account, system_program]
+    Initialize { min_bet: u64 },
+
+    /// Place bet. Must be the last instruction in the transaction and cannot
+    /// be invoked through CPI; see `guard::check_bet_is_final`.
+    ///
+    /// Unsafe against the slot leader: the roll comes from the clock and
+    /// the bet, which the leader knows before including it. The vault
+    /// refuses it until the authority sends `SetInstantBets`; `CommitBet`
+    /// is the safe way to bet.
+    /// Accounts: [signer, writable vault_account, system_program, instructions_sysvar]
+    Bet { amount: u64 },
+
+    // New variants go at the end: the Borsh tag is the variant index, and
+    // existing clients encode `Initialize` as 0 and `Bet` as 1.
+
+    /// Withdraw house funds above the rent-exempt minimum
+    /// Accounts: [writable authority signer, writable vault_account]
+    Withdraw { amount: u64 },
+
+    /// Replace the betting limits and odds. `max_bet` of 0 means no maximum.
+    /// Accounts: [authority signer, writable vault_account]
+    UpdateConfig {
+        min_bet: u64,
+        max_bet: u64,
+        odds: Odds,
+    },
+
+    /// Stop or resume accepting bets
+    /// Accounts: [authority signer, writable vault_account]
+    SetPaused { is_paused: bool },
+
+    /// Close the vault, sending all of its lamports to the authority.
+    /// Refused while any commitment is open, since it pays from the vault.
+    /// Accounts: [writable authority signer, writable vault_account]
+    CloseVault,
+
+    /// Move the whole vault to another program's house: all of its lamports
+    /// go to `target` and the vault is closed. The vault must be paused, so
+    /// the stats logged here are final, and have no open commitments. An
+    /// `OriginalVault` is moved as it is, from wherever it was created.
+    /// Accounts: [authority signer, writable vault_account, writable target]
+    MigrateOut,
+
+    /// Take a stake now and settle it later with `ResolveBet`, from the hash
+    /// of a slot that comes after this one. `nonce` tells the player's open
+    /// commitments apart; the commitment lives at `commitment_address`. The
+    /// payout is reserved in the vault until the bet resolves.
+    /// Accounts: [writable signer player, writable vault_account,
+    /// writable commitment, system_program]
+    CommitBet { amount: u64, nonce: u64 },
+
+    /// Settle a committed bet once `RESOLVE_DELAY_SLOTS` have passed, or
+    /// refund it if its slot hash has aged out of the `SlotHashes` sysvar.
+    /// Anyone may send it; the payout or refund and the commitment's rent
+    /// go to the player.
+    /// Accounts: [writable player, writable vault_account, writable
+    /// commitment, slot_hashes_sysvar]
+    ResolveBet,
+
+    /// Accept or refuse the instant `Bet`
+    /// Accounts: [authority signer, writable vault_account]
+    SetInstantBets { enabled: bool },
+}
+
+impl CatflipInstruction {
+    /// Decodes the Borsh encoding of an instruction.
+    ///
+    /// Clients keep encoding with Borsh; the program reads the bytes by hand
+    /// because Borsh's errors are heap-allocated strings and its reader goes
+    /// through `io::Read`. Anything Borsh would reject, including trailing
+    /// bytes and a bool that isn't 0 or 1, is rejected here too.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut input = Input(data);
+        let instruction = match input.u8()? {
+            0 => Self::Initialize { min_bet: input.u64()? },
+            1 => Self::Bet { amount: input.u64()? },
+            2 => Self::Withdraw { amount: input.u64()? },
+            3 => Self::UpdateConfig {
+                min_bet: input.u64()?,
+                max_bet: input.u64()?,
+                odds: Odds {
+                    win_probability_bps: input.u16()?,
+                    payout_multiplier_bps: input.u32()?,
+                },
+            },
+            4 => Self::SetPaused { is_paused: input.bool()? },
+            5 => Self::CloseVault,
+            6 => Self::MigrateOut,
+            7 => Self::CommitBet {
+                amount: input.u64()?,
+                nonce: input.u64()?,
+            },
+            8 => Self::ResolveBet,
+            9 => Self::SetInstantBets { enabled: input.bool()? },
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        if !input.0.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(instruction)
+    }
+}
+
+/// Little-endian reader over instruction data.
+struct Input<'a>(&'a [u8]);
+
+impl Input<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ProgramError> {
+        if self.0.len() < N {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, ProgramError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ProgramError> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ProgramError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, ProgramError> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn bool(&mut self) -> Result<bool, ProgramError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
@@ -1,44 +1,114 @@
-use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
-    entrypoint,
     entrypoint::ProgramResult,
+    hash::hashv,
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
+    rent::Rent,
     system_instruction,
+    system_program,
     sysvar::Sysvar,
-    program::invoke,
+    program::{invoke, invoke_signed},
 };
 
+pub mod errors;
+pub mod guard;
+pub mod instruction;
+pub mod odds;
+pub mod slot_hashes;
+pub mod state;
+
+pub use errors::CatflipMinimalError;
+pub use instruction::CatflipInstruction;
+pub use odds::Odds;
+pub use slot_hashes::RESOLVE_DELAY_SLOTS;
+pub use state::{BetCommitment, OriginalVault, Vault};
+
+use slot_hashes::SlotHash;
+
 // Program entrypoint
-entrypoint!(process_instruction);
-
-// Instructions
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub enum CatflipInstruction {
-    /// Initialize vault
-    /// Accounts: [signer, vault_account, system_program]
-    Initialize { min_bet: u64 },
-    
-    /// Place bet
-    /// Accounts: [signer, vault_account, system_program]  
-    Bet { amount: u64 },
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+/// Seed of the vault PDA, the one account that holds house funds and config.
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Seed of the PDAs holding committed bets, followed by the player's key
+/// and the nonce.
+pub const COMMITMENT_SEED: &[u8] = b"commitment";
+
+/// Address and bump of the vault PDA.
+pub fn vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED], program_id)
 }
 
-// Vault state (minimal)
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub struct Vault {
-    pub is_initialized: bool,
-    pub authority: Pubkey,
-    pub min_bet: u64,
-    pub total_bets: u64,
-    pub total_volume: u64,
+/// Address and bump of the PDA holding `player`'s bet committed with `nonce`.
+pub fn commitment_address(program_id: &Pubkey, player: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COMMITMENT_SEED, player.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
 }
 
-impl Vault {
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 8; // 57 bytes
+fn check_system_program(system_program: &AccountInfo) -> ProgramResult {
+    if !system_program::check_id(system_program.key) {
+        msg!("Expected the system program, got {}", system_program.key);
+        return Err(CatflipMinimalError::InvalidProgramId.into());
+    }
+    Ok(())
+}
+
+/// Creates `account` at a PDA as a rent-exempt account of `space` bytes
+/// owned by the program, paid for by `payer`. Anyone can send lamports to the
+/// PDA before it exists, which would make `create_account` fail, so a funded
+/// address is topped up and taken over instead.
+fn create_pda<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent_minimum = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    if current_lamports == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                rent_minimum,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+    } else {
+        if current_lamports < rent_minimum {
+            invoke(
+                &system_instruction::transfer(
+                    payer.key,
+                    account.key,
+                    rent_minimum - current_lamports,
+                ),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(account.key, space as u64),
+            &[account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(account.key, program_id),
+            &[account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+    }
+    Ok(())
 }
 
 // Process instruction
@@ -47,7 +117,7 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = CatflipInstruction::try_from_slice(instruction_data)?;
+    let instruction = CatflipInstruction::unpack(instruction_data)?;
     
     match instruction {
         CatflipInstruction::Initialize { min_bet } => {
@@ -56,41 +126,89 @@ pub fn process_instruction(
         CatflipInstruction::Bet { amount } => {
             bet(program_id, accounts, amount)
         }
+        CatflipInstruction::Withdraw { amount } => {
+            withdraw(program_id, accounts, amount)
+        }
+        CatflipInstruction::UpdateConfig { min_bet, max_bet, odds } => {
+            update_config(program_id, accounts, min_bet, max_bet, odds)
+        }
+        CatflipInstruction::SetPaused { is_paused } => {
+            set_paused(program_id, accounts, is_paused)
+        }
+        CatflipInstruction::CloseVault => {
+            close_vault(program_id, accounts)
+        }
+        CatflipInstruction::MigrateOut => {
+            migrate_out(program_id, accounts)
+        }
+        CatflipInstruction::CommitBet { amount, nonce } => {
+            commit_bet(program_id, accounts, amount, nonce)
+        }
+        CatflipInstruction::ResolveBet => {
+            resolve_bet(program_id, accounts)
+        }
+        CatflipInstruction::SetInstantBets { enabled } => {
+            set_instant_bets(program_id, accounts, enabled)
+        }
     }
 }
 
 // Initialize vault
 fn initialize(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     min_bet: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let authority = next_account_info(account_info_iter)?;
     let vault_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
     
     if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+        return Err(CatflipMinimalError::AccountNotSigner.into());
+    }
+    check_system_program(system_program)?;
+
+    let (vault_key, bump) = vault_address(program_id);
+    if *vault_account.key != vault_key {
+        msg!("Vault must be the PDA {}", vault_key);
+        return Err(CatflipMinimalError::ConstraintSeeds.into());
     }
 
     // Check if already initialized
-    if vault_account.data_len() > 0 {
-        let vault = Vault::try_from_slice(&vault_account.data.borrow())?;
-        if vault.is_initialized {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
+    if vault_account.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
     }
+    if !system_program::check_id(vault_account.owner) || vault_account.data_len() > 0 {
+        return Err(CatflipMinimalError::AccountOwnedByWrongProgram.into());
+    }
+
+    // Create the vault as a rent-exempt account owned by the program
+    create_pda(
+        program_id,
+        authority,
+        vault_account,
+        system_program,
+        Vault::LEN,
+        &[VAULT_SEED, &[bump]],
+    )?;
 
     // Create vault
-    let vault = Vault {
-        is_initialized: true,
+    let mut data = vault_account.try_borrow_mut_data()?;
+    *Vault::from_data_mut(&mut data)? = Vault {
+        is_initialized: 1,
         authority: *authority.key,
+        bump,
         min_bet,
         total_bets: 0,
         total_volume: 0,
+        is_paused: 0,
+        max_bet: 0,
+        odds: Odds::default(),
+        instant_bets: 0,
+        open_commitments: 0,
+        reserved_payouts: 0,
     };
-
-    vault.serialize(&mut &mut vault_account.data.borrow_mut()[..])?;
     
     msg!("Vault initialized with min bet: {}", min_bet);
     Ok(())
@@ -98,7 +216,7 @@ fn initialize(
 
 // Place bet
 fn bet(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
@@ -106,61 +224,427 @@ fn bet(
     let player = next_account_info(account_info_iter)?;
     let vault_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
+    let instructions_sysvar = next_account_info(account_info_iter)?;
 
     if !player.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+        return Err(CatflipMinimalError::AccountNotSigner.into());
     }
+    check_system_program(system_program)?;
+    guard::check_bet_is_final(instructions_sysvar)?;
 
-    // Load and validate vault
-    let mut vault = Vault::try_from_slice(&vault_account.data.borrow())?;
-    if !vault.is_initialized {
-        return Err(ProgramError::UninitializedAccount);
-    }
+    // Load and validate vault, updating its stats in place. The borrow of
+    // its data must end before the stake transfer, since a CPI that is handed
+    // an account fails while the account's data is borrowed.
+    let (odds, total_bets, reserved_payouts) = {
+        let mut vault = Vault::load(program_id, vault_account)?;
 
-    if amount < vault.min_bet {
-        msg!("Bet too small: {} < {}", amount, vault.min_bet);
-        return Err(ProgramError::InvalidArgument);
-    }
+        if vault.is_paused != 0 {
+            return Err(CatflipMinimalError::GamePaused.into());
+        }
+        if vault.instant_bets == 0 {
+            return Err(CatflipMinimalError::InstantBetsDisabled.into());
+        }
+
+        let min_bet = vault.min_bet;
+        if amount < min_bet {
+            msg!("Bet too small: {} < {}", amount, min_bet);
+            return Err(CatflipMinimalError::BetBelowMinimum.into());
+        }
+
+        let max_bet = vault.max_bet;
+        if max_bet != 0 && amount > max_bet {
+            msg!("Bet too large: {} > {}", amount, max_bet);
+            return Err(CatflipMinimalError::BetExceedsMaxExposure.into());
+        }
+
+        let total_bets = vault.total_bets;
+        vault.total_bets = total_bets + 1;
+        vault.total_volume += amount;
+        (vault.odds, total_bets, vault.reserved_payouts)
+    };
 
     // Transfer bet to vault
     invoke(
         &system_instruction::transfer(player.key, vault_account.key, amount),
         &[player.clone(), vault_account.clone(), system_program.clone()],
     )?;
+    guard::check_player_rent(player)?;
 
-    // Simple deterministic "randomness" using clock
+    // Deterministic "randomness" from the clock and the bet. Hashing spreads
+    // it evenly over the roll range; it does not make it unpredictable, which
+    // is why the authority has to opt in to instant bets.
     let clock = Clock::get()?;
-    let random_seed = clock.unix_timestamp as u64 ^ clock.slot ^ amount;
-    let is_winner = (random_seed % 100) < 49; // 49% win rate
+    let entropy = hashv(&[
+        &clock.slot.to_le_bytes(),
+        &clock.unix_timestamp.to_le_bytes(),
+        player.key.as_ref(),
+        &amount.to_le_bytes(),
+        &total_bets.to_le_bytes(),
+    ]);
+    let is_winner = odds.wins(odds::roll(entropy));
 
     let mut payout = 0u64;
     
     if is_winner {
-        // Calculate payout (1.96x for 4% house edge)
-        payout = (amount * 196) / 100;
+        payout = odds.payout(amount).ok_or(CatflipMinimalError::MathOverflow)?;
         
-        // Check vault has enough balance
-        if **vault_account.lamports.borrow() < payout {
+        // Check vault has enough balance, leaving it rent exempt and the
+        // open commitments' payouts untouched
+        let rent_minimum = Rent::get()?.minimum_balance(vault_account.data_len());
+        let available = vault_account
+            .lamports()
+            .saturating_sub(rent_minimum)
+            .saturating_sub(reserved_payouts);
+        if available < payout {
             msg!("Insufficient vault funds");
-            return Err(ProgramError::InsufficientFunds);
+            return Err(CatflipMinimalError::InsufficientVaultBalance.into());
         }
 
         // Transfer payout to player
         **vault_account.try_borrow_mut_lamports()? -= payout;
         **player.try_borrow_mut_lamports()? += payout;
-        
-        msg!("Winner! Payout: {}", payout);
+    }
+
+    // The one log line per bet, which the indexer parses; each `msg!` costs
+    // compute.
+    msg!("Bet result: player={}, stake={}, winner={}, payout={}", 
+         player.key, amount, is_winner, payout);
+
+    Ok(())
+}
+
+// Withdraw house funds
+fn withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+
+    // Open commitments' payouts stay behind, so the authority can't void a
+    // bet it sees winning by emptying the vault first.
+    let available = Vault::load_as_authority(program_id, authority, vault_account)?
+        .available(vault_account)?;
+    if amount > available {
+        msg!("Withdrawal of {} exceeds available {}", amount, available);
+        return Err(CatflipMinimalError::InsufficientVaultBalance.into());
+    }
+
+    **vault_account.try_borrow_mut_lamports()? -= amount;
+    **authority.try_borrow_mut_lamports()? += amount;
+
+    msg!("Vault withdrawal: {} lamports", amount);
+    Ok(())
+}
+
+// Update limits and odds
+fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_bet: u64,
+    max_bet: u64,
+    odds: Odds,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+
+    let mut vault = Vault::load_as_authority(program_id, authority, vault_account)?;
+    Vault::validate_config(min_bet, max_bet, &odds)?;
+
+    vault.min_bet = min_bet;
+    vault.max_bet = max_bet;
+    vault.odds = odds;
+
+    msg!("Config: min_bet={}, max_bet={}, win_bps={}, payout_bps={}, edge_bps={}",
+         min_bet, max_bet, { odds.win_probability_bps }, { odds.payout_multiplier_bps }, odds.house_edge_bps());
+    Ok(())
+}
+
+// Pause or resume betting
+fn set_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    is_paused: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+
+    let mut vault = Vault::load_as_authority(program_id, authority, vault_account)?;
+    vault.is_paused = is_paused as u8;
+
+    msg!("Game paused: {}", is_paused);
+    Ok(())
+}
+
+// Accept or refuse instant bets
+fn set_instant_bets(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+
+    let mut vault = Vault::load_as_authority(program_id, authority, vault_account)?;
+    vault.instant_bets = enabled as u8;
+
+    msg!("Instant bets: {}", enabled);
+    Ok(())
+}
+
+// Close the vault
+fn close_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+
+    let vault = Vault::load_as_authority(program_id, authority, vault_account)?;
+    check_no_open_commitments(&vault)?;
+    drop(vault);
+
+    let lamports = vault_account.lamports();
+    **vault_account.try_borrow_mut_lamports()? = 0;
+    **authority.try_borrow_mut_lamports()? += lamports;
+
+    // Hand the emptied account back to the system program so the PDA can be
+    // initialized again.
+    vault_account.realloc(0, false)?;
+    vault_account.assign(&system_program::ID);
+
+    msg!("Vault closed, {} lamports returned", lamports);
+    Ok(())
+}
+
+// Drain the vault into its replacement
+fn migrate_out(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+    let target = next_account_info(account_info_iter)?;
+
+    let (total_bets, total_volume) = if vault_account.data_len() == OriginalVault::LEN {
+        // The original program had no pause, but nothing bets on this
+        // layout any more, so its stats are already final.
+        let vault = OriginalVault::load_as_authority(program_id, authority, vault_account)?;
+        (vault.total_bets, vault.total_volume)
     } else {
-        msg!("House wins! Bet: {}", amount);
+        let vault = Vault::load_as_authority(program_id, authority, vault_account)?;
+        if vault.is_paused == 0 {
+            return Err(CatflipMinimalError::VaultNotPaused.into());
+        }
+        check_no_open_commitments(&vault)?;
+        (vault.total_bets, vault.total_volume)
+    };
+    if target.key == vault_account.key {
+        msg!("Migration target must not be the vault itself");
+        return Err(ProgramError::InvalidArgument);
     }
 
-    // Update vault stats
-    vault.total_bets += 1;
-    vault.total_volume += amount;
-    vault.serialize(&mut &mut vault_account.data.borrow_mut()[..])?;
+    let lamports = vault_account.lamports();
+    **vault_account.try_borrow_mut_lamports()? = 0;
+    **target.try_borrow_mut_lamports()? += lamports;
 
-    msg!("Bet result: player={}, stake={}, winner={}, payout={}", 
+    vault_account.realloc(0, false)?;
+    vault_account.assign(&system_program::ID);
+
+    // The target program records these as the imported history, e.g.
+    // catflip's `migrate_in`.
+    msg!("Vault migrated: target={}, lamports={}, total_bets={}, total_volume={}",
+         target.key, lamports, total_bets, total_volume);
+    Ok(())
+}
+
+// Commit to a bet resolved by a later slot hash
+fn commit_bet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    nonce: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let player = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+    let commitment_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !player.is_signer {
+        return Err(CatflipMinimalError::AccountNotSigner.into());
+    }
+    check_system_program(system_program)?;
+
+    // The outcome isn't known until the target slot is hashed, so unlike
+    // `Bet` this needs no guard against CPI or later instructions.
+    let odds = {
+        let mut vault = Vault::load(program_id, vault_account)?;
+
+        if vault.is_paused != 0 {
+            return Err(CatflipMinimalError::GamePaused.into());
+        }
+
+        let min_bet = vault.min_bet;
+        if amount < min_bet {
+            msg!("Bet too small: {} < {}", amount, min_bet);
+            return Err(CatflipMinimalError::BetBelowMinimum.into());
+        }
+
+        let max_bet = vault.max_bet;
+        if max_bet != 0 && amount > max_bet {
+            msg!("Bet too large: {} > {}", amount, max_bet);
+            return Err(CatflipMinimalError::BetExceedsMaxExposure.into());
+        }
+
+        // The payout is reserved until the bet resolves: the slot hash is
+        // public before anyone resolves it, and the authority must not be
+        // able to withdraw a win it can see coming.
+        let odds = vault.odds;
+        let payout = odds.payout(amount).ok_or(CatflipMinimalError::MathOverflow)?;
+        if vault.available(vault_account)? < payout {
+            msg!("Insufficient vault funds");
+            return Err(CatflipMinimalError::InsufficientVaultBalance.into());
+        }
+        vault.open_commitments += 1;
+        vault.reserved_payouts += payout;
+
+        odds
+    };
+
+    let (commitment_key, bump) = commitment_address(program_id, player.key, nonce);
+    if *commitment_account.key != commitment_key {
+        msg!("Commitment must be the PDA {}", commitment_key);
+        return Err(CatflipMinimalError::ConstraintSeeds.into());
+    }
+    if commitment_account.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda(
+        program_id,
+        player,
+        commitment_account,
+        system_program,
+        BetCommitment::LEN,
+        &[COMMITMENT_SEED, player.key.as_ref(), &nonce.to_le_bytes(), &[bump]],
+    )?;
+
+    invoke(
+        &system_instruction::transfer(player.key, vault_account.key, amount),
+        &[player.clone(), vault_account.clone(), system_program.clone()],
+    )?;
+
+    let commit_slot = Clock::get()?.slot;
+    let mut data = commitment_account.try_borrow_mut_data()?;
+    *BetCommitment::from_data_mut(&mut data)? = BetCommitment {
+        player: *player.key,
+        amount,
+        nonce,
+        commit_slot,
+        odds,
+        bump,
+    };
+
+    msg!("Bet committed: player={}, stake={}, nonce={}, slot={}",
+         player.key, amount, nonce, commit_slot);
+    Ok(())
+}
+
+// Settle or refund a committed bet
+fn resolve_bet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let player = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+    let commitment_account = next_account_info(account_info_iter)?;
+    let slot_hashes_sysvar = next_account_info(account_info_iter)?;
+
+    let commitment = BetCommitment::load(program_id, player, commitment_account)?;
+    let amount = commitment.amount;
+    let odds = commitment.odds;
+    let reserved = odds.payout(amount).ok_or(CatflipMinimalError::MathOverflow)?;
+    let target = commitment.commit_slot + RESOLVE_DELAY_SLOTS;
+
+    // Resolving is open to anyone so that the house can settle bets the
+    // player would rather leave until they age out and are refunded.
+    let slot_hash = match slot_hashes::lookup(slot_hashes_sysvar, target)? {
+        SlotHash::Found(slot_hash) => slot_hash,
+        SlotHash::Pending => {
+            msg!("Bet resolves on slot {}", target);
+            return Err(CatflipMinimalError::BetNotReady.into());
+        }
+        SlotHash::AgedOut => {
+            // The stake is part of the reserved payout, so it is still there.
+            release(&mut *Vault::load(program_id, vault_account)?, reserved);
+            **vault_account.try_borrow_mut_lamports()? -= amount;
+            **player.try_borrow_mut_lamports()? += amount;
+            close_commitment(player, commitment_account)?;
+
+            msg!("Bet refunded: player={}, stake={}, nonce={}",
+                 player.key, amount, { commitment.nonce });
+            return Ok(());
+        }
+    };
+
+    {
+        let mut vault = Vault::load(program_id, vault_account)?;
+        vault.total_bets += 1;
+        vault.total_volume += amount;
+        release(&mut vault, reserved);
+    }
+
+    let entropy = hashv(&[
+        slot_hash.as_ref(),
+        player.key.as_ref(),
+        &commitment.nonce.to_le_bytes(),
+    ]);
+    let is_winner = odds.wins(odds::roll(entropy));
+
+    let mut payout = 0u64;
+    if is_winner {
+        // Reserved when the bet was committed.
+        payout = reserved;
+        **vault_account.try_borrow_mut_lamports()? -= payout;
+        **player.try_borrow_mut_lamports()? += payout;
+    }
+    close_commitment(player, commitment_account)?;
+
+    msg!("Bet result: player={}, stake={}, winner={}, payout={}",
          player.key, amount, is_winner, payout);
+    Ok(())
+}
+
+/// Releases a resolved commitment's reserved payout.
+fn release(vault: &mut Vault, reserved: u64) {
+    vault.open_commitments -= 1;
+    vault.reserved_payouts -= reserved;
+}
+
+/// Refuses to hand over a vault that open commitments still pay out from.
+fn check_no_open_commitments(vault: &Vault) -> ProgramResult {
+    let open_commitments = vault.open_commitments;
+    if open_commitments != 0 {
+        msg!("{} commitments are still open", open_commitments);
+        return Err(CatflipMinimalError::CommitmentsOpen.into());
+    }
+    Ok(())
+}
 
+/// Closes a resolved commitment, returning its rent to the player.
+fn close_commitment(player: &AccountInfo, commitment_account: &AccountInfo) -> ProgramResult {
+    let lamports = commitment_account.lamports();
+    **commitment_account.try_borrow_mut_lamports()? = 0;
+    **player.try_borrow_mut_lamports()? += lamports;
+    commitment_account.realloc(0, false)?;
+    commitment_account.assign(&system_program::ID);
     Ok(())
-}
\ No newline at end of file
+}
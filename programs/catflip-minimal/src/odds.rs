@@ -0,0 +1,111 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
+use solana_program::{entrypoint::ProgramResult, hash::{hashv, Hash}};
+
+use crate::errors::CatflipMinimalError;
+
+/// Basis points: probabilities and multipliers are stored over this.
+pub const BPS: u64 = 10_000;
+
+/// Largest house edge a deployment may configure, in bps of the stake.
+pub const MAX_HOUSE_EDGE_BPS: u64 = 1_000;
+
+/// Win probability and payout of a bet.
+///
+/// Packed like `Vault`, which stores it inline. Borsh's derives borrow each
+/// field, which packed structs don't allow, so its impls are written out.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct Odds {
+    /// Chance of winning, in bps.
+    pub win_probability_bps: u16,
+    /// Payout of a winning bet as a multiple of the stake, stake included,
+    /// in bps: 19600 pays 1.96x.
+    pub payout_multiplier_bps: u32,
+}
+
+impl BorshSerialize for Odds {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let Self { win_probability_bps, payout_multiplier_bps } = *self;
+        win_probability_bps.serialize(writer)?;
+        payout_multiplier_bps.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Odds {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            win_probability_bps: u16::deserialize_reader(reader)?,
+            payout_multiplier_bps: u32::deserialize_reader(reader)?,
+        })
+    }
+}
+
+impl Default for Odds {
+    /// 49% to win 1.96x: a 3.96% house edge.
+    fn default() -> Self {
+        Self {
+            win_probability_bps: 4_900,
+            payout_multiplier_bps: 19_600,
+        }
+    }
+}
+
+impl Odds {
+    /// Expected payout per unit staked, in bps of bps.
+    fn expected_return(&self) -> u64 {
+        self.win_probability_bps as u64 * self.payout_multiplier_bps as u64
+    }
+
+    /// House edge in bps of the stake, rounded down.
+    pub fn house_edge_bps(&self) -> u64 {
+        (BPS * BPS).saturating_sub(self.expected_return()) / BPS
+    }
+
+    /// Rejects odds that are not a bet (certain win or loss, a payout that
+    /// doesn't return the stake), that favour the player, or whose edge is
+    /// above `MAX_HOUSE_EDGE_BPS`.
+    pub fn validate(&self) -> ProgramResult {
+        let expected_return = self.expected_return();
+        if self.win_probability_bps == 0
+            || self.win_probability_bps as u64 >= BPS
+            || self.payout_multiplier_bps as u64 <= BPS
+            || !((BPS - MAX_HOUSE_EDGE_BPS) * BPS..=BPS * BPS).contains(&expected_return)
+        {
+            return Err(CatflipMinimalError::InvalidConfig.into());
+        }
+        Ok(())
+    }
+
+    /// Whether `roll`, uniform in `[0, BPS)`, wins.
+    pub fn wins(&self, roll: u64) -> bool {
+        roll < self.win_probability_bps as u64
+    }
+
+    /// What a winning bet of `amount` pays out, stake included.
+    pub fn payout(&self, amount: u64) -> Option<u64> {
+        let payout = amount as u128 * self.payout_multiplier_bps as u128 / BPS as u128;
+        u64::try_from(payout).ok()
+    }
+}
+
+/// Draws a roll uniform in `[0, BPS)` from `entropy`.
+///
+/// Taking a `u64` modulo `BPS` would favour the low rolls slightly, since
+/// 2^64 is not a multiple of `BPS`. Words from the top partial cycle are
+/// rejected instead and the next word of the hash is tried; a hash with no
+/// usable word is hashed again.
+pub fn roll(entropy: Hash) -> u64 {
+    const LIMIT: u64 = u64::MAX - u64::MAX % BPS;
+
+    let mut hash = entropy;
+    loop {
+        for word in hash.to_bytes().chunks_exact(8) {
+            let value = u64::from_le_bytes(word.try_into().unwrap());
+            if value < LIMIT {
+                return value % BPS;
+            }
+        }
+        hash = hashv(&[hash.as_ref()]);
+    }
+}
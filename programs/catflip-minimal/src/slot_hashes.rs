@@ -0,0 +1,74 @@
+use solana_program::{
+    account_info::AccountInfo,
+    hash::Hash,
+    msg,
+    program_error::ProgramError,
+    slot_hashes::MAX_ENTRIES,
+    sysvar,
+};
+
+use crate::errors::CatflipMinimalError;
+
+/// Slots between a commitment and the slot whose hash resolves it. A leader
+/// produces four consecutive slots, so the slot `RESOLVE_DELAY_SLOTS` after
+/// the commitment always belongs to a later leader window.
+pub const RESOLVE_DELAY_SLOTS: u64 = 4;
+
+/// Serialized size of one `(slot, hash)` entry.
+const ENTRY_LEN: usize = 8 + 32;
+
+/// Where a target slot stands in the `SlotHashes` sysvar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotHash {
+    /// Hash of the first slot at or after the target that wasn't skipped.
+    Found(Hash),
+    /// No slot at or after the target has been hashed yet.
+    Pending,
+    /// The sysvar no longer reaches back to the target, so which slot's hash
+    /// would have resolved it can't be known.
+    AgedOut,
+}
+
+/// Looks up `target` in the `SlotHashes` sysvar account.
+///
+/// The sysvar is read from the account data rather than deserialized: it is
+/// 20 KB, and only the entries around `target` are needed. Entries are
+/// newest first and skipped slots have none, so the hash used is that of the
+/// oldest entry at or after `target`. The same `target` always picks the
+/// same entry, whoever resolves it and whenever.
+pub fn lookup(slot_hashes: &AccountInfo, target: u64) -> Result<SlotHash, ProgramError> {
+    if !sysvar::slot_hashes::check_id(slot_hashes.key) {
+        msg!("Expected the SlotHashes sysvar, got {}", slot_hashes.key);
+        return Err(CatflipMinimalError::AccountSysvarMismatch.into());
+    }
+    let data = slot_hashes.try_borrow_data()?;
+    Ok(find(&data, target))
+}
+
+/// [`lookup`] on the raw sysvar data.
+pub fn find(data: &[u8], target: u64) -> SlotHash {
+    let entries = data
+        .get(..8)
+        .map(|len| u64::from_le_bytes(len.try_into().unwrap()) as usize)
+        .and_then(|len| data.get(8..8 + len.checked_mul(ENTRY_LEN)?))
+        .unwrap_or_default();
+    let slot = |entry: &[u8]| u64::from_le_bytes(entry[..8].try_into().unwrap());
+
+    let mut found = None;
+    for (index, entry) in entries.chunks_exact(ENTRY_LEN).enumerate() {
+        if slot(entry) < target {
+            break;
+        }
+        found = Some((index, entry));
+    }
+
+    match found {
+        None => SlotHash::Pending,
+        // Every entry is at or after the target, and a full sysvar has
+        // evicted older ones that may have come first.
+        Some((index, entry)) if slot(entry) != target && index + 1 == MAX_ENTRIES => {
+            SlotHash::AgedOut
+        }
+        Some((_, entry)) => SlotHash::Found(Hash::new(&entry[8..])),
+    }
+}
@@ -0,0 +1,249 @@
+use std::cell::RefMut;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+    sysvar::Sysvar,
+};
+
+use crate::{errors::CatflipMinimalError, odds::Odds, COMMITMENT_SEED, VAULT_SEED};
+
+/// Vault state, read and written in place in the account data.
+///
+/// The layout is packed, so its first `LEGACY_LEN` bytes are exactly the
+/// Borsh encoding the vault had before it became zero-copy. Fields after
+/// `odds` came later; `load` grows a vault stored without them. The
+/// original vault, from before the PDA and its bump, is not a prefix of
+/// this layout; see `OriginalVault`. Packed
+/// fields can't be borrowed: copy them out (`let min_bet = vault.min_bet;`)
+/// before formatting or comparing them by reference.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Vault {
+    /// 1 once initialized.
+    pub is_initialized: u8,
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub min_bet: u64,
+    pub total_bets: u64,
+    pub total_volume: u64,
+    /// 1 while bets are refused.
+    pub is_paused: u8,
+    /// 0 means no maximum.
+    pub max_bet: u64,
+    pub odds: Odds,
+    /// 1 while the instant `Bet` is accepted. Its roll is predictable, so
+    /// it starts off and only the authority turns it on.
+    pub instant_bets: u8,
+    /// Commitments not resolved yet.
+    pub open_commitments: u64,
+    /// What the open commitments pay out if they all win, stakes included.
+    /// Withdrawals and instant bets leave it in the vault.
+    pub reserved_payouts: u64,
+}
+
+impl Vault {
+    pub const LEN: usize = std::mem::size_of::<Vault>(); // 90 bytes
+
+    /// Size of a vault stored before `instant_bets` and the commitment
+    /// fields were added.
+    pub const LEGACY_LEN: usize = 73;
+
+    pub fn validate_config(min_bet: u64, max_bet: u64, odds: &Odds) -> ProgramResult {
+        if max_bet != 0 && max_bet < min_bet {
+            return Err(CatflipMinimalError::InvalidConfig.into());
+        }
+        odds.validate()
+    }
+
+    /// Views vault data, e.g. as fetched by a client, without any checks.
+    pub fn from_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        bytemuck::try_from_bytes(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Views freshly allocated vault data, without any checks.
+    pub fn from_data_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        bytemuck::try_from_bytes_mut(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Loads the vault for an admin instruction signed by `authority`.
+    pub fn load_as_authority<'a>(
+        program_id: &Pubkey,
+        authority: &AccountInfo,
+        vault_account: &'a AccountInfo,
+    ) -> Result<RefMut<'a, Self>, ProgramError> {
+        if !authority.is_signer {
+            return Err(CatflipMinimalError::AccountNotSigner.into());
+        }
+        let vault = Vault::load(program_id, vault_account)?;
+        if vault.authority != *authority.key {
+            msg!("Signer {} is not the vault authority", authority.key);
+            return Err(CatflipMinimalError::Unauthorized.into());
+        }
+        Ok(vault)
+    }
+
+    /// Loads the vault from `vault_account`, checking that it is the
+    /// program-owned PDA rather than any account with a matching layout.
+    ///
+    /// A vault of `LEGACY_LEN` bytes is grown to `LEN` first, with the new
+    /// fields zeroed. Its balance pays the extra rent, so the instruction
+    /// fails if the vault holds less than the rent-exempt minimum for `LEN`.
+    ///
+    /// The vault stays mutably borrowed until the returned reference is
+    /// dropped, and CPIs that are handed the vault account fail while it is.
+    pub fn load<'a>(
+        program_id: &Pubkey,
+        vault_account: &'a AccountInfo,
+    ) -> Result<RefMut<'a, Self>, ProgramError> {
+        if vault_account.owner != program_id {
+            if system_program::check_id(vault_account.owner) && vault_account.lamports() == 0 {
+                return Err(CatflipMinimalError::AccountNotInitialized.into());
+            }
+            msg!("Vault is not owned by the program");
+            return Err(CatflipMinimalError::AccountOwnedByWrongProgram.into());
+        }
+
+        if vault_account.data_len() == Vault::LEGACY_LEN {
+            vault_account.realloc(Vault::LEN, true)?;
+        }
+
+        let data = vault_account.try_borrow_mut_data()?;
+        let vault = RefMut::filter_map(data, |data| Vault::from_data_mut(data).ok())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if vault.is_initialized == 0 {
+            return Err(CatflipMinimalError::AccountNotInitialized.into());
+        }
+
+        let expected = Pubkey::create_program_address(&[VAULT_SEED, &[vault.bump]], program_id);
+        if expected.as_ref() != Ok(vault_account.key) {
+            msg!("Vault is not the program's vault PDA");
+            return Err(CatflipMinimalError::ConstraintSeeds.into());
+        }
+
+        Ok(vault)
+    }
+
+    /// Lamports the vault can pay out or hand over: its balance above the
+    /// rent-exempt minimum, less what open commitments may still claim.
+    /// `vault_account` is the loaded vault's own account, so it is `LEN`
+    /// bytes; its data can't be reread while this is borrowed from it.
+    pub fn available(&self, vault_account: &AccountInfo) -> Result<u64, ProgramError> {
+        let rent_minimum = Rent::get()?.minimum_balance(Vault::LEN);
+        Ok(vault_account
+            .lamports()
+            .saturating_sub(rent_minimum)
+            .saturating_sub(self.reserved_payouts))
+    }
+}
+
+/// The vault as first deployed: Borsh-encoded, without a bump or bet
+/// limits, in an account the authority created at an address of its
+/// choosing. Nothing can bet on it any more; `MigrateOut` is the only
+/// instruction that accepts it.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct OriginalVault {
+    /// 1 once initialized.
+    pub is_initialized: u8,
+    pub authority: Pubkey,
+    pub min_bet: u64,
+    pub total_bets: u64,
+    pub total_volume: u64,
+}
+
+impl OriginalVault {
+    pub const LEN: usize = std::mem::size_of::<OriginalVault>(); // 57 bytes
+
+    /// Reads the original vault in `vault_account` for its `authority`. It
+    /// was never a PDA, so owning the account is the program's only proof
+    /// that the data is a vault it wrote.
+    pub fn load_as_authority(
+        program_id: &Pubkey,
+        authority: &AccountInfo,
+        vault_account: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        if !authority.is_signer {
+            return Err(CatflipMinimalError::AccountNotSigner.into());
+        }
+        if vault_account.owner != program_id {
+            msg!("Vault is not owned by the program");
+            return Err(CatflipMinimalError::AccountOwnedByWrongProgram.into());
+        }
+
+        let data = vault_account.try_borrow_data()?;
+        let vault: Self = *bytemuck::try_from_bytes(&data).map_err(|_| ProgramError::InvalidAccountData)?;
+        if vault.is_initialized == 0 {
+            return Err(CatflipMinimalError::AccountNotInitialized.into());
+        }
+        if vault.authority != *authority.key {
+            msg!("Signer {} is not the vault authority", authority.key);
+            return Err(CatflipMinimalError::Unauthorized.into());
+        }
+
+        Ok(vault)
+    }
+}
+
+/// A bet committed to by `CommitBet` and not yet resolved, at the PDA
+/// `commitment_address(player, nonce)`. The stake is already in the vault.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct BetCommitment {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    /// Slot the commitment was made in; the bet resolves on a later one.
+    pub commit_slot: u64,
+    /// The vault's odds when the bet was placed, so later changes don't
+    /// apply to it.
+    pub odds: Odds,
+    pub bump: u8,
+}
+
+impl BetCommitment {
+    pub const LEN: usize = std::mem::size_of::<BetCommitment>(); // 63 bytes
+
+    /// Views commitment data without any checks.
+    pub fn from_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        bytemuck::try_from_bytes(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Views freshly allocated commitment data, without any checks.
+    pub fn from_data_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        bytemuck::try_from_bytes_mut(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Reads the commitment in `commitment_account`, checking that it is the
+    /// program-owned PDA of `player`.
+    pub fn load(
+        program_id: &Pubkey,
+        player: &AccountInfo,
+        commitment_account: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        if commitment_account.owner != program_id {
+            if system_program::check_id(commitment_account.owner) && commitment_account.lamports() == 0 {
+                return Err(CatflipMinimalError::AccountNotInitialized.into());
+            }
+            return Err(CatflipMinimalError::AccountOwnedByWrongProgram.into());
+        }
+
+        let commitment = *BetCommitment::from_data(&commitment_account.try_borrow_data()?)?;
+        let expected = Pubkey::create_program_address(
+            &[COMMITMENT_SEED, player.key.as_ref(), &commitment.nonce.to_le_bytes(), &[commitment.bump]],
+            program_id,
+        );
+        if expected.as_ref() != Ok(commitment_account.key) {
+            msg!("Commitment is not {}'s commitment PDA", player.key);
+            return Err(CatflipMinimalError::ConstraintSeeds.into());
+        }
+
+        Ok(commitment)
+    }
+}
@@ -0,0 +1,399 @@
+//! Bets committed in one slot and resolved from the hash of a later one.
+
+use borsh::BorshSerialize;
+use catflip_minimal::{
+    commitment_address, odds, process_instruction,
+    slot_hashes::{find, SlotHash},
+    vault_address, BetCommitment, CatflipInstruction, CatflipMinimalError, Odds, Vault,
+    RESOLVE_DELAY_SLOTS,
+};
+use solana_program_test::{
+    processor, BanksClientError, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext,
+};
+use solana_sdk::{
+    clock::Clock,
+    hash::{hashv, Hash},
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    slot_hashes::{SlotHashes, MAX_ENTRIES},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+const MIN_BET: u64 = LAMPORTS_PER_SOL / 1000;
+
+struct Setup {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    vault: Pubkey,
+    player: Keypair,
+}
+
+async fn setup() -> Setup {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "catflip_minimal",
+        program_id,
+        processor!(process_instruction),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let (vault, _) = vault_address(&program_id);
+    let payer = context.payer.pubkey();
+    let player = Keypair::new();
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &CatflipInstruction::Initialize { min_bet: MIN_BET }
+            .try_to_vec()
+            .unwrap(),
+        vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let fund = system_instruction::transfer(&payer, &vault, 10 * LAMPORTS_PER_SOL);
+    let fund_player = system_instruction::transfer(&payer, &player.pubkey(), LAMPORTS_PER_SOL);
+    send(&mut context, &[initialize, fund, fund_player], &[])
+        .await
+        .unwrap();
+
+    Setup {
+        context,
+        program_id,
+        vault,
+        player,
+    }
+}
+
+impl Setup {
+    fn commit(&self, amount: u64, nonce: u64) -> Instruction {
+        let player = self.player.pubkey();
+        let (commitment, _) = commitment_address(&self.program_id, &player, nonce);
+        Instruction::new_with_bytes(
+            self.program_id,
+            &CatflipInstruction::CommitBet { amount, nonce }
+                .try_to_vec()
+                .unwrap(),
+            vec![
+                AccountMeta::new(player, true),
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new(commitment, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        )
+    }
+
+    fn resolve(&self, player: Pubkey, nonce: u64) -> Instruction {
+        let (commitment, _) = commitment_address(&self.program_id, &self.player.pubkey(), nonce);
+        Instruction::new_with_bytes(
+            self.program_id,
+            &CatflipInstruction::ResolveBet.try_to_vec().unwrap(),
+            vec![
+                AccountMeta::new(player, false),
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new(commitment, false),
+                AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+            ],
+        )
+    }
+
+    /// An admin instruction signed by the fee payer, the vault's authority.
+    fn admin(&self, data: CatflipInstruction, extra: &[AccountMeta]) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new(self.context.payer.pubkey(), true),
+            AccountMeta::new(self.vault, false),
+        ];
+        accounts.extend_from_slice(extra);
+        Instruction::new_with_bytes(self.program_id, &data.try_to_vec().unwrap(), accounts)
+    }
+
+    async fn slot(&mut self) -> u64 {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+            .slot
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(address)
+            .await
+            .unwrap()
+    }
+
+    async fn vault(&mut self) -> Vault {
+        let account = self
+            .context
+            .banks_client
+            .get_account(self.vault)
+            .await
+            .unwrap()
+            .unwrap();
+        *Vault::from_data(&account.data).unwrap()
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+fn assert_rejected(result: Result<(), BanksClientError>, expected: InstructionError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, expected)
+    );
+}
+
+fn custom(err: CatflipMinimalError) -> InstructionError {
+    InstructionError::Custom(err as u32)
+}
+
+/// `SlotHashes` sysvar data holding `slots`, newest first.
+fn sysvar_data(slots: &[u64]) -> Vec<u8> {
+    let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+    for &slot in slots {
+        data.extend(slot.to_le_bytes());
+        data.extend(hash_of(slot).to_bytes());
+    }
+    data
+}
+
+fn commitment_rent() -> u64 {
+    Rent::default().minimum_balance(BetCommitment::LEN)
+}
+
+fn hash_of(slot: u64) -> Hash {
+    hashv(&[&slot.to_le_bytes()])
+}
+
+#[test]
+fn lookups_use_the_first_hashed_slot_at_or_after_the_target() {
+    let data = sysvar_data(&[20, 17, 16, 12]);
+    assert_eq!(find(&data, 21), SlotHash::Pending);
+    assert_eq!(find(&data, 20), SlotHash::Found(hash_of(20)));
+    // 18 and 19 were skipped.
+    assert_eq!(find(&data, 18), SlotHash::Found(hash_of(20)));
+    assert_eq!(find(&data, 16), SlotHash::Found(hash_of(16)));
+    // Nothing older was ever evicted from a sysvar that isn't full.
+    assert_eq!(find(&data, 3), SlotHash::Found(hash_of(12)));
+    assert_eq!(find(&[], 3), SlotHash::Pending);
+
+    let full: Vec<u64> = (100..100 + MAX_ENTRIES as u64).rev().collect();
+    let data = sysvar_data(&full);
+    assert_eq!(find(&data, 100), SlotHash::Found(hash_of(100)));
+    assert_eq!(find(&data, 99), SlotHash::AgedOut);
+}
+
+#[tokio::test]
+async fn committed_bets_resolve_from_a_later_slot_hash() {
+    let mut setup = setup().await;
+    let player = setup.player.pubkey();
+    let before = setup.balance(player).await;
+
+    let commit = setup.commit(MIN_BET, 7);
+    send(&mut setup.context, &[commit], &[&setup.player])
+        .await
+        .unwrap();
+    let commit_slot = setup.slot().await;
+    assert_eq!(
+        setup.balance(player).await,
+        before - MIN_BET - commitment_rent()
+    );
+
+    // The same nonce can't be reused while its bet is open.
+    let again = setup.commit(MIN_BET + 1, 7);
+    let result = send(&mut setup.context, &[again], &[&setup.player]).await;
+    assert_rejected(result, InstructionError::AccountAlreadyInitialized);
+
+    let resolve = setup.resolve(player, 7);
+    let result = send(&mut setup.context, std::slice::from_ref(&resolve), &[]).await;
+    assert_rejected(result, custom(CatflipMinimalError::BetNotReady));
+
+    let target = commit_slot + RESOLVE_DELAY_SLOTS;
+    setup.context.warp_to_slot(target + 1).unwrap();
+
+    // Only the player's own account can receive the result.
+    let misdirected = setup.resolve(Pubkey::new_unique(), 7);
+    let result = send(&mut setup.context, &[misdirected], &[]).await;
+    assert_rejected(result, custom(CatflipMinimalError::ConstraintSeeds));
+
+    // The outcome follows from the target's slot hash, the player and the
+    // nonce alone.
+    let slot_hashes = setup
+        .context
+        .banks_client
+        .get_account(sysvar::slot_hashes::ID)
+        .await
+        .unwrap()
+        .unwrap();
+    let SlotHash::Found(slot_hash) = find(&slot_hashes.data, target) else {
+        panic!("slot {target} should have been hashed");
+    };
+    let entropy = hashv(&[slot_hash.as_ref(), player.as_ref(), &7u64.to_le_bytes()]);
+    let wins = Odds::default().wins(odds::roll(entropy));
+
+    // Anyone can resolve; here the fee payer does.
+    send(&mut setup.context, std::slice::from_ref(&resolve), &[])
+        .await
+        .unwrap();
+    let payout = if wins {
+        Odds::default().payout(MIN_BET).unwrap()
+    } else {
+        0
+    };
+    assert_eq!(setup.balance(player).await, before - MIN_BET + payout);
+    let vault = setup.vault().await;
+    assert_eq!(({ vault.total_bets }, { vault.total_volume }), (1, MIN_BET));
+
+    // On the same blockhash the resend would be deduplicated and report the
+    // first attempt's success.
+    let banks_client = &mut setup.context.banks_client;
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    banks_client
+        .get_new_latest_blockhash(&blockhash)
+        .await
+        .unwrap();
+    let result = send(&mut setup.context, &[resolve], &[]).await;
+    assert_rejected(result, custom(CatflipMinimalError::AccountNotInitialized));
+}
+
+#[tokio::test]
+async fn bets_whose_slot_hash_aged_out_are_refunded() {
+    let mut setup = setup().await;
+    let player = setup.player.pubkey();
+    let before = setup.balance(player).await;
+
+    let commit = setup.commit(MIN_BET, 1);
+    send(&mut setup.context, &[commit], &[&setup.player])
+        .await
+        .unwrap();
+    let commit_slot = setup.slot().await;
+
+    // A full sysvar that starts after the target slot.
+    let oldest = commit_slot + RESOLVE_DELAY_SLOTS + 1;
+    let entries: Vec<_> = (oldest..oldest + MAX_ENTRIES as u64)
+        .map(|slot| (slot, hash_of(slot)))
+        .collect();
+    setup.context.set_sysvar(&SlotHashes::new(&entries));
+
+    let resolve = setup.resolve(player, 1);
+    send(&mut setup.context, &[resolve], &[]).await.unwrap();
+    assert_eq!(setup.balance(player).await, before);
+    let vault = setup.vault().await;
+    assert_eq!({ vault.total_bets }, 0);
+    assert_eq!(
+        ({ vault.open_commitments }, { vault.reserved_payouts }),
+        (0, 0)
+    );
+}
+
+#[tokio::test]
+async fn commitments_need_the_slot_hashes_sysvar_and_their_pda() {
+    let mut setup = setup().await;
+    let player = setup.player.pubkey();
+
+    let mut commit = setup.commit(MIN_BET, 2);
+    commit.accounts[2].pubkey = commitment_address(&setup.program_id, &player, 3).0;
+    let result = send(&mut setup.context, &[commit], &[&setup.player]).await;
+    assert_rejected(result, custom(CatflipMinimalError::ConstraintSeeds));
+
+    let commit = setup.commit(MIN_BET, 2);
+    send(&mut setup.context, &[commit], &[&setup.player])
+        .await
+        .unwrap();
+    let mut resolve = setup.resolve(player, 2);
+    resolve.accounts[3].pubkey = sysvar::clock::ID;
+    let result = send(&mut setup.context, &[resolve], &[]).await;
+    assert_rejected(result, custom(CatflipMinimalError::AccountSysvarMismatch));
+}
+
+#[tokio::test]
+async fn open_commitments_are_reserved_from_the_authority() {
+    let mut setup = setup().await;
+    let player = setup.player.pubkey();
+    let commit = setup.commit(MIN_BET, 4);
+    send(&mut setup.context, &[commit], &[&setup.player])
+        .await
+        .unwrap();
+    let commit_slot = setup.slot().await;
+    let payout = Odds::default().payout(MIN_BET).unwrap();
+    let vault = setup.vault().await;
+    assert_eq!(
+        ({ vault.open_commitments }, { vault.reserved_payouts }),
+        (1, payout)
+    );
+
+    // The authority can take everything but the payout the bet may win.
+    let rent_minimum = Rent::default().minimum_balance(Vault::LEN);
+    let available = setup.balance(setup.vault).await - rent_minimum - payout;
+    let too_much = setup.admin(
+        CatflipInstruction::Withdraw {
+            amount: available + 1,
+        },
+        &[],
+    );
+    let result = send(&mut setup.context, &[too_much], &[]).await;
+    assert_rejected(
+        result,
+        custom(CatflipMinimalError::InsufficientVaultBalance),
+    );
+    let all = setup.admin(CatflipInstruction::Withdraw { amount: available }, &[]);
+    send(&mut setup.context, &[all], &[]).await.unwrap();
+
+    // What is reserved for one bet doesn't cover another.
+    let commit = setup.commit(MIN_BET, 5);
+    let result = send(&mut setup.context, &[commit], &[&setup.player]).await;
+    assert_rejected(
+        result,
+        custom(CatflipMinimalError::InsufficientVaultBalance),
+    );
+
+    // Nor can the vault be closed or migrated from under the bet.
+    let pause = setup.admin(CatflipInstruction::SetPaused { is_paused: true }, &[]);
+    send(&mut setup.context, &[pause], &[]).await.unwrap();
+    let close = setup.admin(CatflipInstruction::CloseVault, &[]);
+    let result = send(&mut setup.context, std::slice::from_ref(&close), &[]).await;
+    assert_rejected(result, custom(CatflipMinimalError::CommitmentsOpen));
+    let migrate_out = setup.admin(
+        CatflipInstruction::MigrateOut,
+        &[AccountMeta::new(Pubkey::new_unique(), false)],
+    );
+    let result = send(&mut setup.context, &[migrate_out], &[]).await;
+    assert_rejected(result, custom(CatflipMinimalError::CommitmentsOpen));
+
+    // Resolving pays from the reserve and releases it, paused or not.
+    setup
+        .context
+        .warp_to_slot(commit_slot + RESOLVE_DELAY_SLOTS + 1)
+        .unwrap();
+    let resolve = setup.resolve(player, 4);
+    send(&mut setup.context, &[resolve], &[]).await.unwrap();
+    let vault = setup.vault().await;
+    assert_eq!(
+        ({ vault.open_commitments }, { vault.reserved_payouts }),
+        (0, 0)
+    );
+    send(&mut setup.context, &[close], &[]).await.unwrap();
+}
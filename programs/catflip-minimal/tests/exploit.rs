@@ -0,0 +1,250 @@
+//! A bet that resolves in the instruction that takes the stake is a free
+//! option if anything after it can see the result and abort. These tests
+//! play the attacker.
+
+use borsh::BorshSerialize;
+use catflip_minimal::{
+    process_instruction, vault_address, CatflipInstruction, CatflipMinimalError,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo,
+    compute_budget::ComputeBudgetInstruction,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+const MIN_BET: u64 = LAMPORTS_PER_SOL / 1000;
+const ABORTED: u32 = 666;
+
+/// Either bets through CPI and aborts unless the player's balance went up
+/// (tag 0, accounts `[target program, bet accounts...]`), or aborts unless
+/// the player holds at least an amount (tag 1, accounts `[player]`).
+fn attacker(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match data[0] {
+        0 => {
+            let (target, bet_accounts) = accounts.split_first().unwrap();
+            let player = &bet_accounts[0];
+            let before = player.lamports();
+            let metas = bet_accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: *account.key,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect();
+            invoke(
+                &Instruction::new_with_bytes(*target.key, &data[1..], metas),
+                bet_accounts,
+            )?;
+            if player.lamports() <= before {
+                return Err(ProgramError::Custom(ABORTED));
+            }
+            Ok(())
+        }
+        _ => {
+            let minimum = u64::from_le_bytes(data[1..9].try_into().unwrap());
+            if accounts[0].lamports() < minimum {
+                return Err(ProgramError::Custom(ABORTED));
+            }
+            Ok(())
+        }
+    }
+}
+
+struct Setup {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    attacker_id: Pubkey,
+    vault: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let program_id = Pubkey::new_unique();
+    let attacker_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "catflip_minimal",
+        program_id,
+        processor!(process_instruction),
+    );
+    program_test.add_program("attacker", attacker_id, processor!(attacker));
+    let mut context = program_test.start_with_context().await;
+
+    let (vault, _) = vault_address(&program_id);
+    let payer = context.payer.pubkey();
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &CatflipInstruction::Initialize { min_bet: MIN_BET }
+            .try_to_vec()
+            .unwrap(),
+        vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let fund = system_instruction::transfer(&payer, &vault, 10 * LAMPORTS_PER_SOL);
+    let enable_instant_bets = Instruction::new_with_bytes(
+        program_id,
+        &CatflipInstruction::SetInstantBets { enabled: true }
+            .try_to_vec()
+            .unwrap(),
+        vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(vault, false),
+        ],
+    );
+    send(&mut context, &[initialize, fund, enable_instant_bets], &[])
+        .await
+        .unwrap();
+
+    Setup {
+        context,
+        program_id,
+        attacker_id,
+        vault,
+    }
+}
+
+fn bet_accounts(player: Pubkey, vault: Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(player, true),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(sysvar::instructions::ID, false),
+    ]
+}
+
+fn bet_data(amount: u64) -> Vec<u8> {
+    CatflipInstruction::Bet { amount }.try_to_vec().unwrap()
+}
+
+impl Setup {
+    fn bet(&self, player: Pubkey, amount: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &bet_data(amount),
+            bet_accounts(player, self.vault),
+        )
+    }
+
+    fn bet_through_attacker(&self, player: Pubkey, amount: u64) -> Instruction {
+        let mut accounts = vec![AccountMeta::new_readonly(self.program_id, false)];
+        accounts.extend(bet_accounts(player, self.vault));
+        let mut data = vec![0];
+        data.extend(bet_data(amount));
+        Instruction::new_with_bytes(self.attacker_id, &data, accounts)
+    }
+
+    fn require_balance(&self, player: Pubkey, minimum: u64) -> Instruction {
+        let mut data = vec![1];
+        data.extend(minimum.to_le_bytes());
+        Instruction::new_with_bytes(
+            self.attacker_id,
+            &data,
+            vec![AccountMeta::new_readonly(player, false)],
+        )
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+fn assert_bet_rejected(
+    result: Result<(), BanksClientError>,
+    index: u8,
+    expected: CatflipMinimalError,
+) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(index, InstructionError::Custom(expected as u32))
+    );
+}
+
+#[tokio::test]
+async fn bets_through_cpi_are_rejected() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+
+    // Without the guard, the attacker only ever lands winning bets.
+    for amount in [MIN_BET, MIN_BET + 1, MIN_BET + 2] {
+        let wrapped = setup.bet_through_attacker(payer, amount);
+        let result = send(&mut setup.context, &[wrapped], &[]).await;
+        assert_bet_rejected(result, 0, CatflipMinimalError::CpiNotAllowed);
+    }
+}
+
+#[tokio::test]
+async fn instructions_after_a_bet_are_rejected() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+    let balance = setup.context.banks_client.get_balance(payer).await.unwrap();
+
+    // A balance check after the bet aborts every loss.
+    let bet = setup.bet(payer, MIN_BET);
+    let check = setup.require_balance(payer, balance);
+    let result = send(&mut setup.context, &[bet, check], &[]).await;
+    assert_bet_rejected(result, 0, CatflipMinimalError::InstructionAfterBet);
+
+    // A plain transfer, sized to overdraw only after a loss, would do the
+    // same without an attacker program at all.
+    let bet = setup.bet(payer, MIN_BET + 1);
+    let transfer = system_instruction::transfer(&payer, &setup.vault, 1);
+    let result = send(&mut setup.context, &[bet, transfer], &[]).await;
+    assert_bet_rejected(result, 0, CatflipMinimalError::InstructionAfterBet);
+
+    // Instructions before the bet, and compute budget ones anywhere, run
+    // without seeing the outcome.
+    let before = system_instruction::transfer(&payer, &setup.vault, 1);
+    let budget = ComputeBudgetInstruction::set_compute_unit_limit(200_000);
+    let bet = setup.bet(payer, MIN_BET + 2);
+    send(&mut setup.context, &[before, bet, budget], &[])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn stakes_cannot_strand_the_player_below_rent() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+    let rent_minimum = Rent::default().minimum_balance(0);
+
+    // Left with dust after staking, the player's account would fail the
+    // transaction's rent check on a loss but not on a win.
+    let player = Keypair::new();
+    let fund = system_instruction::transfer(&payer, &player.pubkey(), rent_minimum + MIN_BET - 1);
+    send(&mut setup.context, &[fund], &[]).await.unwrap();
+    let bet = setup.bet(player.pubkey(), MIN_BET);
+    let result = send(&mut setup.context, &[bet], &[&player]).await;
+    assert_bet_rejected(result, 0, CatflipMinimalError::PlayerBelowRentExempt);
+
+    // Staking everything is fine: an empty account passes either way.
+    let everything = rent_minimum + MIN_BET - 1;
+    let bet = setup.bet(player.pubkey(), everything);
+    send(&mut setup.context, &[bet], &[&player]).await.unwrap();
+}
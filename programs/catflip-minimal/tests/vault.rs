@@ -0,0 +1,902 @@
+use borsh::BorshSerialize;
+use catflip_minimal::{
+    odds, process_instruction, vault_address, CatflipInstruction, CatflipMinimalError, Odds,
+    OriginalVault, Vault,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::hash::Hash;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+const MIN_BET: u64 = LAMPORTS_PER_SOL / 1000;
+
+fn program_test(program_id: Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "catflip_minimal",
+        program_id,
+        processor!(process_instruction),
+    );
+    program_test
+}
+
+fn instruction(
+    program_id: Pubkey,
+    signer: Pubkey,
+    vault: Pubkey,
+    system_program: Pubkey,
+    data: CatflipInstruction,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id,
+        &data.try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ],
+    )
+}
+
+fn initialize(program_id: Pubkey, authority: Pubkey, vault: Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        authority,
+        vault,
+        system_program::ID,
+        CatflipInstruction::Initialize { min_bet: MIN_BET },
+    )
+}
+
+fn bet(program_id: Pubkey, player: Pubkey, vault: Pubkey, amount: u64) -> Instruction {
+    instruction(
+        program_id,
+        player,
+        vault,
+        system_program::ID,
+        CatflipInstruction::Bet { amount },
+    )
+}
+
+fn admin(
+    program_id: Pubkey,
+    authority: Pubkey,
+    vault: Pubkey,
+    data: CatflipInstruction,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id,
+        &data.try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(vault, false),
+        ],
+    )
+}
+
+/// Turns the instant `Bet` on, which new vaults refuse.
+fn enable_instant_bets(program_id: Pubkey, authority: Pubkey, vault: Pubkey) -> Instruction {
+    admin(
+        program_id,
+        authority,
+        vault,
+        CatflipInstruction::SetInstantBets { enabled: true },
+    )
+}
+
+/// A vault with valid contents at an arbitrary address, for planting
+/// accounts the program must not accept.
+fn forged_vault(owner: Pubkey, authority: Pubkey) -> Account {
+    let vault = Vault {
+        is_initialized: 1,
+        authority,
+        bump: 255,
+        min_bet: MIN_BET,
+        total_bets: 0,
+        total_volume: 0,
+        is_paused: 0,
+        max_bet: 0,
+        odds: Odds::default(),
+        instant_bets: 1,
+        open_commitments: 0,
+        reserved_payouts: 0,
+    };
+    Account {
+        lamports: 10 * LAMPORTS_PER_SOL,
+        data: bytemuck::bytes_of(&vault).to_vec(),
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+async fn send_as(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer, signer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+fn assert_instruction_error(result: Result<(), BanksClientError>, expected: InstructionError) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, err) => assert_eq!(err, expected),
+        other => panic!("expected an instruction error, got {other:?}"),
+    }
+}
+
+fn custom(err: CatflipMinimalError) -> InstructionError {
+    InstructionError::Custom(err as u32)
+}
+
+async fn fetch_vault(context: &mut ProgramTestContext, address: Pubkey) -> (Account, Vault) {
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let vault = *Vault::from_data(&account.data).unwrap();
+    (account, vault)
+}
+
+#[test]
+fn error_codes_match_catflip() {
+    use anchor_lang::error::ErrorCode;
+    use catflip::errors::CatflipError;
+
+    let pairs = [
+        (
+            CatflipMinimalError::BetBelowMinimum,
+            u32::from(CatflipError::BetBelowMinimum),
+        ),
+        (
+            CatflipMinimalError::InsufficientVaultBalance,
+            u32::from(CatflipError::InsufficientVaultBalance),
+        ),
+        (
+            CatflipMinimalError::BetExceedsMaxExposure,
+            u32::from(CatflipError::BetExceedsMaxExposure),
+        ),
+        (
+            CatflipMinimalError::GamePaused,
+            u32::from(CatflipError::GamePaused),
+        ),
+        (
+            CatflipMinimalError::Unauthorized,
+            u32::from(CatflipError::Unauthorized),
+        ),
+        (
+            CatflipMinimalError::MathOverflow,
+            u32::from(CatflipError::MathOverflow),
+        ),
+        (
+            CatflipMinimalError::InvalidConfig,
+            u32::from(CatflipError::InvalidConfig),
+        ),
+        (
+            CatflipMinimalError::ConstraintSeeds,
+            u32::from(ErrorCode::ConstraintSeeds),
+        ),
+        (
+            CatflipMinimalError::AccountOwnedByWrongProgram,
+            u32::from(ErrorCode::AccountOwnedByWrongProgram),
+        ),
+        (
+            CatflipMinimalError::InvalidProgramId,
+            u32::from(ErrorCode::InvalidProgramId),
+        ),
+        (
+            CatflipMinimalError::AccountNotSigner,
+            u32::from(ErrorCode::AccountNotSigner),
+        ),
+        (
+            CatflipMinimalError::AccountNotInitialized,
+            u32::from(ErrorCode::AccountNotInitialized),
+        ),
+        (
+            CatflipMinimalError::AccountSysvarMismatch,
+            u32::from(ErrorCode::AccountSysvarMismatch),
+        ),
+    ];
+    for (minimal, code) in pairs {
+        assert_eq!(minimal as u32, code, "{minimal:?}");
+    }
+}
+
+#[tokio::test]
+async fn initialize_creates_the_vault_pda() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, bump) = vault_address(&program_id);
+    let mut context = program_test(program_id).start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    send(&mut context, &[initialize(program_id, payer, vault_key)])
+        .await
+        .unwrap();
+
+    let (account, vault) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.data.len(), Vault::LEN);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(Vault::LEN)
+    );
+    assert_eq!(vault.authority, payer);
+    assert_eq!(vault.bump, bump);
+    assert_eq!({ vault.min_bet }, MIN_BET);
+
+    context.warp_to_slot(2).unwrap();
+    let again = send(&mut context, &[initialize(program_id, payer, vault_key)]).await;
+    assert_instruction_error(again, InstructionError::AccountAlreadyInitialized);
+}
+
+#[tokio::test]
+async fn initialize_takes_over_a_prefunded_pda() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, _) = vault_address(&program_id);
+    let mut context = program_test(program_id).start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    // Lamports sent to the address before initialization must not block it.
+    let prefunded = Rent::default().minimum_balance(0);
+    send(
+        &mut context,
+        &[system_instruction::transfer(&payer, &vault_key, prefunded)],
+    )
+    .await
+    .unwrap();
+    send(&mut context, &[initialize(program_id, payer, vault_key)])
+        .await
+        .unwrap();
+
+    let (account, vault) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!(account.owner, program_id);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(Vault::LEN)
+    );
+    assert_eq!(vault.is_initialized, 1);
+}
+
+#[tokio::test]
+async fn initialize_rejects_other_addresses_and_programs() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, _) = vault_address(&program_id);
+    let mut context = program_test(program_id).start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    let not_a_pda = Keypair::new().pubkey();
+    let result = send(&mut context, &[initialize(program_id, payer, not_a_pda)]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::ConstraintSeeds));
+
+    let fake_system_program = instruction(
+        program_id,
+        payer,
+        vault_key,
+        Pubkey::new_unique(),
+        CatflipInstruction::Initialize { min_bet: MIN_BET },
+    );
+    let result = send(&mut context, &[fake_system_program]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::InvalidProgramId));
+}
+
+#[tokio::test]
+async fn bet_rejects_foreign_vaults() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, _) = vault_address(&program_id);
+    let not_owned = Pubkey::new_unique();
+    let not_a_pda = Pubkey::new_unique();
+
+    let mut program_test = program_test(program_id);
+    let authority = Pubkey::new_unique();
+    program_test.add_account(not_owned, forged_vault(Pubkey::new_unique(), authority));
+    program_test.add_account(not_a_pda, forged_vault(program_id, authority));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    let result = send(&mut context, &[bet(program_id, payer, vault_key, MIN_BET)]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::AccountNotInitialized));
+
+    send(&mut context, &[initialize(program_id, payer, vault_key)])
+        .await
+        .unwrap();
+
+    // Same layout, but owned by another program.
+    let result = send(&mut context, &[bet(program_id, payer, not_owned, MIN_BET)]).await;
+    assert_instruction_error(
+        result,
+        custom(CatflipMinimalError::AccountOwnedByWrongProgram),
+    );
+
+    // Owned by the program, but not its vault PDA.
+    let result = send(&mut context, &[bet(program_id, payer, not_a_pda, MIN_BET)]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::ConstraintSeeds));
+
+    let fake_system_program = instruction(
+        program_id,
+        payer,
+        vault_key,
+        Pubkey::new_unique(),
+        CatflipInstruction::Bet { amount: MIN_BET },
+    );
+    let result = send(&mut context, &[fake_system_program]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::InvalidProgramId));
+}
+
+#[tokio::test]
+async fn bet_settles_against_the_vault() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, _) = vault_address(&program_id);
+    let mut context = program_test(program_id).start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    send(
+        &mut context,
+        &[
+            initialize(program_id, payer, vault_key),
+            system_instruction::transfer(&payer, &vault_key, LAMPORTS_PER_SOL),
+        ],
+    )
+    .await
+    .unwrap();
+
+    // The roll is predictable, so the authority has to opt in.
+    let result = send(&mut context, &[bet(program_id, payer, vault_key, MIN_BET)]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::InstantBetsDisabled));
+    send(
+        &mut context,
+        &[enable_instant_bets(program_id, payer, vault_key)],
+    )
+    .await
+    .unwrap();
+    let (before, _) = fetch_vault(&mut context, vault_key).await;
+
+    let result = send(
+        &mut context,
+        &[bet(program_id, payer, vault_key, MIN_BET - 1)],
+    )
+    .await;
+    assert_instruction_error(result, custom(CatflipMinimalError::BetBelowMinimum));
+
+    send(&mut context, &[bet(program_id, payer, vault_key, MIN_BET)])
+        .await
+        .unwrap();
+
+    let (after, vault) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!({ vault.total_bets }, 1);
+    assert_eq!({ vault.total_volume }, MIN_BET);
+    let won = before.lamports + MIN_BET - Odds::default().payout(MIN_BET).unwrap();
+    let lost = before.lamports + MIN_BET;
+    assert!(after.lamports == won || after.lamports == lost);
+}
+
+#[test]
+fn existing_instructions_keep_their_encoding() {
+    let initialize = CatflipInstruction::Initialize { min_bet: 7 };
+    assert_eq!(
+        initialize.try_to_vec().unwrap(),
+        [0, 7, 0, 0, 0, 0, 0, 0, 0]
+    );
+    let bet = CatflipInstruction::Bet { amount: 7 };
+    assert_eq!(bet.try_to_vec().unwrap(), [1, 7, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn instructions_decode_exactly_like_borsh() {
+    let instructions = [
+        CatflipInstruction::Initialize { min_bet: 7 },
+        CatflipInstruction::Bet { amount: u64::MAX },
+        CatflipInstruction::Withdraw { amount: 1 },
+        CatflipInstruction::UpdateConfig {
+            min_bet: 1,
+            max_bet: 2,
+            odds: Odds {
+                win_probability_bps: 4_950,
+                payout_multiplier_bps: 19_800,
+            },
+        },
+        CatflipInstruction::SetPaused { is_paused: true },
+        CatflipInstruction::SetPaused { is_paused: false },
+        CatflipInstruction::CloseVault,
+        CatflipInstruction::MigrateOut,
+        CatflipInstruction::CommitBet {
+            amount: 1,
+            nonce: u64::MAX,
+        },
+        CatflipInstruction::ResolveBet,
+        CatflipInstruction::SetInstantBets { enabled: true },
+        CatflipInstruction::SetInstantBets { enabled: false },
+    ];
+    for instruction in instructions {
+        let data = instruction.try_to_vec().unwrap();
+        assert_eq!(CatflipInstruction::unpack(&data).unwrap(), instruction);
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        let truncated = &data[..data.len() - 1];
+        for invalid in [&trailing[..], truncated] {
+            assert_eq!(
+                CatflipInstruction::unpack(invalid),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+
+    for invalid in [&[][..], &[10], &[4, 2]] {
+        assert_eq!(
+            CatflipInstruction::unpack(invalid),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
+
+#[test]
+fn vault_layout_matches_its_borsh_encoding() {
+    /// The vault as it was stored before it became zero-copy.
+    #[derive(BorshSerialize)]
+    struct BorshVault {
+        is_initialized: bool,
+        authority: Pubkey,
+        bump: u8,
+        min_bet: u64,
+        total_bets: u64,
+        total_volume: u64,
+        is_paused: bool,
+        max_bet: u64,
+        odds: Odds,
+    }
+
+    let authority = Pubkey::new_unique();
+    let odds = Odds {
+        win_probability_bps: 4_950,
+        payout_multiplier_bps: 19_800,
+    };
+    let borsh = BorshVault {
+        is_initialized: true,
+        authority,
+        bump: 254,
+        min_bet: 1,
+        total_bets: 2,
+        total_volume: 3,
+        is_paused: true,
+        max_bet: 4,
+        odds,
+    }
+    .try_to_vec()
+    .unwrap();
+    let vault = Vault {
+        is_initialized: 1,
+        authority,
+        bump: 254,
+        min_bet: 1,
+        total_bets: 2,
+        total_volume: 3,
+        is_paused: 1,
+        max_bet: 4,
+        odds,
+        instant_bets: 1,
+        open_commitments: 5,
+        reserved_payouts: 6,
+    };
+
+    assert_eq!(Vault::LEGACY_LEN, 73);
+    assert_eq!(Vault::LEN, 90);
+    assert_eq!(&bytemuck::bytes_of(&vault)[..Vault::LEGACY_LEN], &borsh[..]);
+}
+
+#[tokio::test]
+async fn legacy_vaults_grow_on_first_use() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, bump) = vault_address(&program_id);
+    let authority = Keypair::new();
+    let mut vault = forged_vault(program_id, authority.pubkey());
+    let mut state = *Vault::from_data(&vault.data).unwrap();
+    state.bump = bump;
+    vault.data = bytemuck::bytes_of(&state)[..Vault::LEGACY_LEN].to_vec();
+    let mut program_test = program_test(program_id);
+    program_test.add_account(vault_key, vault);
+    let mut context = program_test.start_with_context().await;
+
+    let pause = admin(
+        program_id,
+        authority.pubkey(),
+        vault_key,
+        CatflipInstruction::SetPaused { is_paused: true },
+    );
+    send_as(&mut context, &[pause], &authority).await.unwrap();
+
+    let (account, vault) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!(account.data.len(), Vault::LEN);
+    assert_eq!(vault.authority, authority.pubkey());
+    assert_eq!(({ vault.min_bet }, vault.is_paused), (MIN_BET, 1));
+    // The fields it grew by start out zeroed, so instant bets are off.
+    assert_eq!(vault.instant_bets, 0);
+    assert_eq!(
+        ({ vault.open_commitments }, { vault.reserved_payouts }),
+        (0, 0)
+    );
+}
+
+#[tokio::test]
+async fn admin_instructions_require_the_authority() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, _) = vault_address(&program_id);
+    let mut context = program_test(program_id).start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    // An authority other than the fee payer, so that it can leave a
+    // transaction unsigned.
+    let authority = Keypair::new();
+    send(
+        &mut context,
+        &[system_instruction::transfer(
+            &payer,
+            &authority.pubkey(),
+            LAMPORTS_PER_SOL,
+        )],
+    )
+    .await
+    .unwrap();
+    let init = initialize(program_id, authority.pubkey(), vault_key);
+    send_as(&mut context, &[init], &authority).await.unwrap();
+
+    let stranger = Keypair::new();
+    for data in [
+        CatflipInstruction::Withdraw { amount: 1 },
+        CatflipInstruction::UpdateConfig {
+            min_bet: 1,
+            max_bet: 0,
+            odds: Odds::default(),
+        },
+        CatflipInstruction::SetPaused { is_paused: true },
+        CatflipInstruction::SetInstantBets { enabled: true },
+        CatflipInstruction::CloseVault,
+    ] {
+        let instruction = admin(program_id, stranger.pubkey(), vault_key, data);
+        let result = send_as(&mut context, &[instruction], &stranger).await;
+        assert_instruction_error(result, custom(CatflipMinimalError::Unauthorized));
+    }
+
+    // The authority's key without its signature is not enough either.
+    let mut unsigned = admin(
+        program_id,
+        authority.pubkey(),
+        vault_key,
+        CatflipInstruction::SetPaused { is_paused: true },
+    );
+    unsigned.accounts[0].is_signer = false;
+    unsigned
+        .accounts
+        .push(AccountMeta::new_readonly(stranger.pubkey(), true));
+    let result = send_as(&mut context, &[unsigned], &stranger).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::AccountNotSigner));
+}
+
+#[tokio::test]
+async fn authority_manages_the_vault() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, _) = vault_address(&program_id);
+    let mut context = program_test(program_id).start_with_context().await;
+    let payer = context.payer.pubkey();
+    send(
+        &mut context,
+        &[
+            initialize(program_id, payer, vault_key),
+            system_instruction::transfer(&payer, &vault_key, LAMPORTS_PER_SOL),
+            enable_instant_bets(program_id, payer, vault_key),
+        ],
+    )
+    .await
+    .unwrap();
+    let admin = |data| admin(program_id, payer, vault_key, data);
+
+    let promotion = Odds {
+        win_probability_bps: 4_500,
+        payout_multiplier_bps: 21_000,
+    };
+    let player_edge = Odds {
+        win_probability_bps: 5_000,
+        payout_multiplier_bps: 20_100,
+    };
+    for (max_bet, odds) in [(MIN_BET - 1, promotion), (0, player_edge)] {
+        let invalid = CatflipInstruction::UpdateConfig {
+            min_bet: MIN_BET,
+            max_bet,
+            odds,
+        };
+        let result = send(&mut context, &[admin(invalid)]).await;
+        assert_instruction_error(result, custom(CatflipMinimalError::InvalidConfig));
+    }
+
+    let config = CatflipInstruction::UpdateConfig {
+        min_bet: MIN_BET,
+        max_bet: 10 * MIN_BET,
+        odds: promotion,
+    };
+    send(&mut context, &[admin(config)]).await.unwrap();
+    let (_, vault) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!((vault.max_bet, vault.odds), (10 * MIN_BET, promotion));
+
+    let result = send(
+        &mut context,
+        &[bet(program_id, payer, vault_key, 11 * MIN_BET)],
+    )
+    .await;
+    assert_instruction_error(result, custom(CatflipMinimalError::BetExceedsMaxExposure));
+
+    let pause = CatflipInstruction::SetPaused { is_paused: true };
+    send(&mut context, &[admin(pause)]).await.unwrap();
+    let result = send(&mut context, &[bet(program_id, payer, vault_key, MIN_BET)]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::GamePaused));
+    let resume = CatflipInstruction::SetPaused { is_paused: false };
+    send(&mut context, &[admin(resume)]).await.unwrap();
+    send(
+        &mut context,
+        &[bet(program_id, payer, vault_key, 2 * MIN_BET)],
+    )
+    .await
+    .unwrap();
+
+    // Withdrawals stop at the rent-exempt minimum.
+    let rent_minimum = Rent::default().minimum_balance(Vault::LEN);
+    let (account, _) = fetch_vault(&mut context, vault_key).await;
+    let available = account.lamports - rent_minimum;
+    let too_much = CatflipInstruction::Withdraw {
+        amount: available + 1,
+    };
+    let result = send(&mut context, &[admin(too_much)]).await;
+    assert_instruction_error(
+        result,
+        custom(CatflipMinimalError::InsufficientVaultBalance),
+    );
+    let all = CatflipInstruction::Withdraw { amount: available };
+    send(&mut context, &[admin(all)]).await.unwrap();
+    let (account, _) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!(account.lamports, rent_minimum);
+
+    // Closing returns the rent and frees the address for a new vault.
+    let before = context.banks_client.get_balance(payer).await.unwrap();
+    send(&mut context, &[admin(CatflipInstruction::CloseVault)])
+        .await
+        .unwrap();
+    let closed = context.banks_client.get_account(vault_key).await.unwrap();
+    assert!(closed.is_none());
+    let after = context.banks_client.get_balance(payer).await.unwrap();
+    assert!(after > before);
+
+    send(&mut context, &[initialize(program_id, payer, vault_key)])
+        .await
+        .unwrap();
+    let (_, vault) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!({ vault.total_bets }, 0);
+}
+
+#[tokio::test]
+async fn migrate_out_moves_a_paused_vault_to_the_target() {
+    let program_id = Pubkey::new_unique();
+    let (vault_key, _) = vault_address(&program_id);
+    let mut context = program_test(program_id).start_with_context().await;
+    let payer = context.payer.pubkey();
+    send(
+        &mut context,
+        &[
+            initialize(program_id, payer, vault_key),
+            system_instruction::transfer(&payer, &vault_key, LAMPORTS_PER_SOL),
+            enable_instant_bets(program_id, payer, vault_key),
+            bet(program_id, payer, vault_key, MIN_BET),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let target = Pubkey::new_unique();
+    let migrate_out = |authority: Pubkey, target: Pubkey| {
+        let mut instruction = admin(
+            program_id,
+            authority,
+            vault_key,
+            CatflipInstruction::MigrateOut,
+        );
+        instruction.accounts.push(AccountMeta::new(target, false));
+        instruction
+    };
+
+    // Bets could still change the stats of a running vault.
+    let result = send(&mut context, &[migrate_out(payer, target)]).await;
+    assert_instruction_error(result, custom(CatflipMinimalError::VaultNotPaused));
+
+    let pause = CatflipInstruction::SetPaused { is_paused: true };
+    send(&mut context, &[admin(program_id, payer, vault_key, pause)])
+        .await
+        .unwrap();
+
+    let stranger = Keypair::new();
+    let result = send_as(
+        &mut context,
+        &[migrate_out(stranger.pubkey(), stranger.pubkey())],
+        &stranger,
+    )
+    .await;
+    assert_instruction_error(result, custom(CatflipMinimalError::Unauthorized));
+    let result = send(&mut context, &[migrate_out(payer, vault_key)]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    let (before, vault) = fetch_vault(&mut context, vault_key).await;
+    assert_eq!(({ vault.total_bets }, { vault.total_volume }), (1, MIN_BET));
+    send(&mut context, &[migrate_out(payer, target)])
+        .await
+        .unwrap();
+
+    let migrated = context.banks_client.get_balance(target).await.unwrap();
+    assert_eq!(migrated, before.lamports);
+    let closed = context.banks_client.get_account(vault_key).await.unwrap();
+    assert!(closed.is_none());
+}
+
+#[tokio::test]
+async fn migrate_out_moves_an_original_vault() {
+    /// The vault as the first deployment stored it.
+    #[derive(BorshSerialize)]
+    struct BorshVault {
+        is_initialized: bool,
+        authority: Pubkey,
+        min_bet: u64,
+        total_bets: u64,
+        total_volume: u64,
+    }
+
+    let program_id = Pubkey::new_unique();
+    let authority = Keypair::new();
+    let data = BorshVault {
+        is_initialized: true,
+        authority: authority.pubkey(),
+        min_bet: MIN_BET,
+        total_bets: 7,
+        total_volume: 7 * MIN_BET,
+    }
+    .try_to_vec()
+    .unwrap();
+    assert_eq!(data.len(), OriginalVault::LEN);
+    let original = *bytemuck::from_bytes::<OriginalVault>(&data);
+    assert_eq!(
+        ({ original.total_bets }, original.authority),
+        (7, authority.pubkey())
+    );
+
+    // Created by the authority at a keypair address, not the vault PDA.
+    let vault_key = Pubkey::new_unique();
+    let foreign_key = Pubkey::new_unique();
+    let account = |owner| Account {
+        lamports: 2 * LAMPORTS_PER_SOL,
+        data: data.clone(),
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+    let mut program_test = program_test(program_id);
+    program_test.add_account(vault_key, account(program_id));
+    program_test.add_account(foreign_key, account(Pubkey::new_unique()));
+    let mut context = program_test.start_with_context().await;
+
+    let target = Pubkey::new_unique();
+    let migrate_out = |authority: Pubkey, vault: Pubkey| {
+        let mut instruction = admin(program_id, authority, vault, CatflipInstruction::MigrateOut);
+        instruction.accounts.push(AccountMeta::new(target, false));
+        instruction
+    };
+
+    let stranger = Keypair::new();
+    let result = send_as(
+        &mut context,
+        &[migrate_out(stranger.pubkey(), vault_key)],
+        &stranger,
+    )
+    .await;
+    assert_instruction_error(result, custom(CatflipMinimalError::Unauthorized));
+    // Anyone can write these 57 bytes into an account of their own.
+    let result = send_as(
+        &mut context,
+        &[migrate_out(authority.pubkey(), foreign_key)],
+        &authority,
+    )
+    .await;
+    assert_instruction_error(
+        result,
+        custom(CatflipMinimalError::AccountOwnedByWrongProgram),
+    );
+
+    // No pause needed: nothing bets on the original layout any more.
+    send_as(
+        &mut context,
+        &[migrate_out(authority.pubkey(), vault_key)],
+        &authority,
+    )
+    .await
+    .unwrap();
+    let migrated = context.banks_client.get_balance(target).await.unwrap();
+    assert_eq!(migrated, 2 * LAMPORTS_PER_SOL);
+    let closed = context.banks_client.get_account(vault_key).await.unwrap();
+    assert!(closed.is_none());
+}
+
+#[test]
+fn odds_must_keep_a_bounded_house_edge() {
+    let odds = |win_probability_bps, payout_multiplier_bps| Odds {
+        win_probability_bps,
+        payout_multiplier_bps,
+    };
+    assert_eq!(Odds::default().house_edge_bps(), 396);
+    assert!(Odds::default().validate().is_ok());
+
+    // A fair coin is allowed; anything better for the player is not.
+    assert!(odds(5_000, 20_000).validate().is_ok());
+    assert!(odds(5_000, 20_001).validate().is_err());
+    // Edges up to 10%.
+    assert!(odds(5_000, 18_000).validate().is_ok());
+    assert!(odds(5_000, 17_998).validate().is_err());
+    // Not a bet at all.
+    assert!(odds(0, 20_000).validate().is_err());
+    assert!(odds(10_000, 10_000).validate().is_err());
+    assert!(odds(9_000, 10_000).validate().is_err());
+
+    let large = odds(4_900, 19_600).payout(u64::MAX);
+    assert_eq!(large, None);
+}
+
+#[test]
+fn rolls_reject_the_biased_tail() {
+    let words = |words: [u64; 4]| {
+        let mut bytes = [0; 32];
+        for (chunk, word) in bytes.chunks_exact_mut(8).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        Hash::new_from_array(bytes)
+    };
+
+    assert_eq!(odds::roll(words([12_345, 0, 0, 0])), 2_345);
+    // The top 1616 values of a u64 would make rolls 0-1615 more likely.
+    let tail = u64::MAX - 1_615;
+    assert_eq!(
+        odds::roll(words([tail, tail - 1, 7, 0])),
+        (tail - 1) % 10_000
+    );
+    assert_eq!(odds::roll(words([u64::MAX, tail, 7, 0])), 7);
+
+    // With every word rejected, the hash is hashed again.
+    let hash = words([u64::MAX; 4]);
+    let rehashed = solana_sdk::hash::hashv(&[hash.as_ref()]);
+    assert_eq!(odds::roll(hash), odds::roll(rehashed));
+}
@@ -1,59 +1,439 @@
 use solana_program::{
     account_info::AccountInfo,
-    clock::Clock,
-    entrypoint,
     entrypoint::ProgramResult,
+    instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+    program::{invoke, invoke_signed},
+    clock::Clock,
+    hash::hashv,
     program_error::ProgramError,
+    pubkey,
     pubkey::Pubkey,
-    sysvar::Sysvar,
+    rent::Rent,
+    slot_hashes::MAX_ENTRIES,
+    system_instruction,
+    system_program,
+    sysvar::{self, instructions, Sysvar},
 };
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub const WIN_RATE_PERCENT: u64 = 49;
+
+/// Seed of the house PDA, which holds the bankroll and the authority allowed
+/// to withdraw it.
+pub const HOUSE_SEED: &[u8] = b"house";
+/// Seed of a committed bet's PDA, followed by the player's key and the nonce.
+pub const COMMITMENT_SEED: &[u8] = b"commitment";
+
+/// Slots between a commitment and the slot whose hash resolves it: past the
+/// four-slot window of the leader the commitment landed with.
+pub const RESOLVE_DELAY_SLOTS: u64 = 4;
+
+/// The house stores its authority, then the payouts reserved for open
+/// commitments (u64 LE).
+const HOUSE_LEN: usize = 32 + 8;
+/// A commitment stores the player, amount, nonce, commit slot and bump.
+const COMMITMENT_LEN: usize = 32 + 8 + 8 + 8 + 1;
+
+/// Instruction data starts with one header byte: the format version in the
+/// high nibble and the opcode in the low one. Unknown versions are rejected,
+/// so a new layout can't be misread as this one.
+pub const VERSION: u8 = 1;
+/// Accounts: [writable signer authority, writable house, system_program].
+/// Data: header.
+pub const OP_INIT_HOUSE: u8 = 0;
+/// Accounts: [writable signer player, writable house, system_program,
+/// instructions_sysvar]. Data: header, amount (u64 LE).
+///
+/// Unsafe against the slot leader, who knows the clock and the bet before
+/// including it and so the roll. `OP_COMMIT` is the safe way to bet.
+pub const OP_BET: u8 = 1;
+/// Takes nothing reserved for open commitments. Accounts: [writable signer
+/// authority, writable house]. Data: header, amount (u64 LE).
+pub const OP_WITHDRAW: u8 = 2;
+/// Reserves the payout in the house until the bet resolves. Accounts:
+/// [writable signer player, writable house, writable commitment,
+/// system_program]. Data: header, amount (u64 LE), nonce (u64 LE).
+pub const OP_COMMIT: u8 = 3;
+/// Anyone may send it. Accounts: [writable player, writable house, writable
+/// commitment, slot_hashes_sysvar]. Data: header.
+pub const OP_RESOLVE: u8 = 4;
+
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Custom error codes, numbered like catflip-minimal's.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UltraError {
+    CpiNotAllowed = 7000,
+    InstructionAfterBet = 7001,
+    PlayerBelowRentExempt = 7002,
+    BetNotReady = 7004,
+    Unauthorized = 6007,
+}
+
+impl From<UltraError> for ProgramError {
+    fn from(e: UltraError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+/// The header byte for `opcode` in the current format.
+pub fn header(opcode: u8) -> u8 {
+    VERSION << 4 | opcode
+}
+
+/// Address and bump of the house PDA.
+pub fn house_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[HOUSE_SEED], program_id)
+}
+
+/// Address and bump of the PDA holding `player`'s bet committed with `nonce`.
+pub fn commitment_address(program_id: &Pubkey, player: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COMMITMENT_SEED, player.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Whether `seed` wins a bet.
+pub fn is_winning_roll(seed: u64) -> bool {
+    (seed % 100) < WIN_RATE_PERCENT
+}
+
+/// What a winning bet of `amount` pays out, stake included: 1.96x.
+pub fn payout(amount: u64) -> Option<u64> {
+    Some(amount.checked_mul(196)? / 100)
+}
 
 pub fn process_instruction(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    if instruction_data.len() < 8 || accounts.len() < 2 {
+    let (&header, args) = instruction_data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if header >> 4 != VERSION {
         return Err(ProgramError::InvalidInstructionData);
     }
-    
-    let player = &accounts[0];
-    let house = &accounts[1];
-    
+
+    match header & 0x0f {
+        OP_INIT_HOUSE => init_house(program_id, accounts),
+        OP_BET => bet(program_id, accounts, read_amount(args)?),
+        OP_WITHDRAW => withdraw(program_id, accounts, read_amount(args)?),
+        OP_COMMIT => commit(
+            program_id,
+            accounts,
+            read_amount(args)?,
+            read_amount(args.get(8..).unwrap_or_default())?,
+        ),
+        OP_RESOLVE => resolve(program_id, accounts),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn read_amount(args: &[u8]) -> Result<u64, ProgramError> {
+    let bytes = args.get(..8).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn check_system_program(account: &AccountInfo) -> ProgramResult {
+    if !system_program::check_id(account.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// The program creates only the house PDA and commitments, which differ in
+/// size, so an owned account of the house's size is the house; no address
+/// derivation needed.
+fn check_house(program_id: &Pubkey, house: &AccountInfo) -> ProgramResult {
+    if house.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if house.data_len() != HOUSE_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+/// Payouts the house holds for open commitments.
+fn reserved(house: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(u64::from_le_bytes(house.try_borrow_data()?[32..].try_into().unwrap()))
+}
+
+fn set_reserved(house: &AccountInfo, reserved: u64) -> ProgramResult {
+    house.try_borrow_mut_data()?[32..].copy_from_slice(&reserved.to_le_bytes());
+    Ok(())
+}
+
+/// What the house can pay out or hand over: its balance above rent, less
+/// the payouts reserved for open commitments.
+fn available(house: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(house
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(HOUSE_LEN))
+        .saturating_sub(reserved(house)?))
+}
+
+/// Funds, allocates and assigns the PDA `account` in separate steps rather
+/// than with `create_account`, which fails if anyone has sent lamports to the
+/// address first.
+fn create_pda<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+fn init_house(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [authority, house, system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_system_program(system_program)?;
+
+    let (house_key, bump) = house_address(program_id);
+    if *house.key != house_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if house.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // The house stores its authority and starts with nothing reserved.
+    create_pda(program_id, authority, house, system_program, HOUSE_LEN, &[HOUSE_SEED, &[bump]])?;
+    house.try_borrow_mut_data()?[..32].copy_from_slice(authority.key.as_ref());
+
+    Ok(())
+}
+
+fn bet(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let [player, house, system_program, instructions_sysvar, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
     if !player.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
-    
-    let amount = u64::from_le_bytes([
-        instruction_data[0], instruction_data[1], instruction_data[2], instruction_data[3],
-        instruction_data[4], instruction_data[5], instruction_data[6], instruction_data[7],
-    ]);
-    
-    // More secure randomness using clock for unpredictability
+    check_system_program(system_program)?;
+    check_house(program_id, house)?;
+
+    // The bet resolves right here, so nothing may run after it that could
+    // look at the outcome and fail the transaction on a loss: no calling
+    // program, no later instruction other than compute budget ones, and no
+    // stake that leaves the player short of rent only when it is lost.
+    if get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Err(UltraError::CpiNotAllowed.into());
+    }
+    if !instructions::check_id(instructions_sysvar.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut index = instructions::load_current_index_checked(instructions_sysvar)? as usize + 1;
+    while let Ok(next) = instructions::load_instruction_at_checked(index, instructions_sysvar) {
+        if next.program_id != COMPUTE_BUDGET_PROGRAM_ID {
+            return Err(UltraError::InstructionAfterBet.into());
+        }
+        index += 1;
+    }
+
+    invoke(
+        &system_instruction::transfer(player.key, house.key, amount),
+        &[player.clone(), house.clone(), system_program.clone()],
+    )?;
+
+    let rent = Rent::get()?;
+    let remaining = player.lamports();
+    if remaining != 0 && remaining < rent.minimum_balance(player.data_len()) {
+        return Err(UltraError::PlayerBelowRentExempt.into());
+    }
+
+    // The roll comes from the clock, the stake and the player's key, all of
+    // which the slot leader knows; see `OP_BET`.
     let clock = solana_program::clock::Clock::get()?;
     let seed = clock.unix_timestamp as u64 ^ clock.slot ^ amount;
-    
-    // Additional entropy from transaction signature (different each time)
+
     let mut entropy = 0u64;
     for (i, byte) in player.key.to_bytes().iter().enumerate().take(8) {
         entropy = entropy.wrapping_add((*byte as u64) << (i * 8));
     }
-    
-    let is_winner = ((seed ^ entropy) % 100) < 49;
-    
-    if is_winner {
-        let payout = (amount * 196) / 100;
-        if **house.lamports.borrow() < payout {
+
+    if is_winning_roll(seed ^ entropy) {
+        let payout = payout(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        if available(house)? < payout {
             return Err(ProgramError::InsufficientFunds);
         }
         **house.try_borrow_mut_lamports()? -= payout;
         **player.try_borrow_mut_lamports()? += payout;
-    } else {
-        **player.try_borrow_mut_lamports()? -= amount;
-        **house.try_borrow_mut_lamports()? += amount;
     }
-    
+
+    Ok(())
+}
+
+fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let [authority, house, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_house(program_id, house)?;
+    if house.try_borrow_data()?[..32] != authority.key.as_ref()[..] {
+        return Err(UltraError::Unauthorized.into());
+    }
+
+    // Payouts reserved for open commitments stay, so the authority can't
+    // void a bet it sees winning by emptying the house first.
+    if amount > available(house)? {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    **house.try_borrow_mut_lamports()? -= amount;
+    **authority.try_borrow_mut_lamports()? += amount;
+
+    Ok(())
+}
+
+fn commit(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64, nonce: u64) -> ProgramResult {
+    let [player, house, commitment, system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !player.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_system_program(system_program)?;
+    check_house(program_id, house)?;
+
+    // The outcome is unknown until the target slot is hashed, so unlike `bet`
+    // nothing later in the transaction can react to it. The slot hash is
+    // public before anyone resolves it, though, so the payout is reserved.
+    let payout = payout(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    if available(house)? < payout {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    set_reserved(house, reserved(house)? + payout)?;
+
+    let (commitment_key, bump) = commitment_address(program_id, player.key, nonce);
+    if *commitment.key != commitment_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if commitment.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let seeds: &[&[u8]] = &[COMMITMENT_SEED, player.key.as_ref(), &nonce.to_le_bytes(), &[bump]];
+    create_pda(program_id, player, commitment, system_program, COMMITMENT_LEN, seeds)?;
+
+    invoke(
+        &system_instruction::transfer(player.key, house.key, amount),
+        &[player.clone(), house.clone(), system_program.clone()],
+    )?;
+
+    let mut data = commitment.try_borrow_mut_data()?;
+    data[..32].copy_from_slice(player.key.as_ref());
+    data[32..40].copy_from_slice(&amount.to_le_bytes());
+    data[40..48].copy_from_slice(&nonce.to_le_bytes());
+    data[48..56].copy_from_slice(&Clock::get()?.slot.to_le_bytes());
+    data[56] = bump;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+fn resolve(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [player, house, commitment, slot_hashes, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    check_house(program_id, house)?;
+    if commitment.owner != program_id || commitment.data_len() != COMMITMENT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !sysvar::slot_hashes::check_id(slot_hashes.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (amount, nonce, commit_slot) = {
+        let data = commitment.try_borrow_data()?;
+        let word = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+        let (amount, nonce, commit_slot) = (word(32), word(40), word(48));
+        let seeds: &[&[u8]] = &[COMMITMENT_SEED, player.key.as_ref(), &data[40..48], &data[56..]];
+        if Pubkey::create_program_address(seeds, program_id) != Ok(*commitment.key) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        (amount, nonce, commit_slot)
+    };
+
+    // Resolving is open to anyone, so the house can settle bets the player
+    // would rather leave open until they age out and are refunded.
+    let owed = match slot_hash(&slot_hashes.try_borrow_data()?, commit_slot + RESOLVE_DELAY_SLOTS)? {
+        Some(hash) => {
+            let entropy = hashv(&[&hash, player.key.as_ref(), &nonce.to_le_bytes()]).to_bytes();
+            if is_winning_roll(u64::from_le_bytes(entropy[..8].try_into().unwrap())) {
+                payout(amount).ok_or(ProgramError::ArithmeticOverflow)?
+            } else {
+                0
+            }
+        }
+        None => amount,
+    };
+    // The reserve covers the payout, and the refund of the stake within it.
+    set_reserved(house, reserved(house)? - payout(amount).ok_or(ProgramError::ArithmeticOverflow)?)?;
+    if owed > 0 {
+        **house.try_borrow_mut_lamports()? -= owed;
+        **player.try_borrow_mut_lamports()? += owed;
+    }
+
+    // Close the commitment, returning its rent to the player.
+    let rent = commitment.lamports();
+    **commitment.try_borrow_mut_lamports()? = 0;
+    **player.try_borrow_mut_lamports()? += rent;
+    commitment.realloc(0, false)?;
+    commitment.assign(&system_program::ID);
+
+    Ok(())
+}
+
+/// The hash that resolves a bet targeting `target`, read from `SlotHashes`
+/// sysvar data: that of the oldest entry at or after `target`, since entries
+/// are newest first and skipped slots have none. `None` once the sysvar no
+/// longer reaches back to `target`, and an error while no slot at or after
+/// it has been hashed.
+fn slot_hash(data: &[u8], target: u64) -> Result<Option<[u8; 32]>, ProgramError> {
+    let len = data.get(..8).ok_or(ProgramError::InvalidAccountData)?;
+    let len = u64::from_le_bytes(len.try_into().unwrap()) as usize;
+    let entries = data.get(8..8 + len * 40).ok_or(ProgramError::InvalidAccountData)?;
+    let slot = |entry: &[u8]| u64::from_le_bytes(entry[..8].try_into().unwrap());
+
+    let mut found = None;
+    for (index, entry) in entries.chunks_exact(40).enumerate() {
+        if slot(entry) < target {
+            break;
+        }
+        found = Some((index, entry));
+    }
+    match found {
+        None => Err(UltraError::BetNotReady.into()),
+        // A full sysvar may have evicted older entries that came first.
+        Some((index, entry)) if slot(entry) != target && index + 1 == MAX_ENTRIES => Ok(None),
+        Some((_, entry)) => Ok(Some(entry[8..].try_into().unwrap())),
+    }
+}
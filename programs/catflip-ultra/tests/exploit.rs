@@ -0,0 +1,180 @@
+//! catflip-ultra resolves a bet in the instruction that takes the stake, so
+//! anything that runs after it and can abort the transaction must be
+//! refused. These tests play the attacker.
+
+use catflip_ultra::{
+    header, house_address, process_instruction, UltraError, OP_BET, OP_INIT_HOUSE,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+const STAKE: u64 = LAMPORTS_PER_SOL / 100;
+const ABORTED: u32 = 666;
+
+/// Bets through CPI and aborts unless the player's balance went up.
+/// Accounts: `[target program, bet accounts...]`.
+fn attacker(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (target, bet_accounts) = accounts.split_first().unwrap();
+    let player = &bet_accounts[0];
+    let before = player.lamports();
+    let metas = bet_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    invoke(
+        &Instruction::new_with_bytes(*target.key, data, metas),
+        bet_accounts,
+    )?;
+    if player.lamports() <= before {
+        return Err(ProgramError::Custom(ABORTED));
+    }
+    Ok(())
+}
+
+struct Setup {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    attacker_id: Pubkey,
+    house: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let program_id = Pubkey::new_unique();
+    let attacker_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program("catflip_ultra", program_id, processor!(process_instruction));
+    program_test.add_program("attacker", attacker_id, processor!(attacker));
+    let mut context = program_test.start_with_context().await;
+
+    let (house, _) = house_address(&program_id);
+    let payer = context.payer.pubkey();
+    let init_house = Instruction::new_with_bytes(
+        program_id,
+        &[header(OP_INIT_HOUSE)],
+        vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(house, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let fund = system_instruction::transfer(&payer, &house, 10 * LAMPORTS_PER_SOL);
+    send(&mut context, &[init_house, fund], &[]).await.unwrap();
+
+    Setup {
+        context,
+        program_id,
+        attacker_id,
+        house,
+    }
+}
+
+fn bet_data(amount: u64) -> Vec<u8> {
+    let mut data = vec![header(OP_BET)];
+    data.extend(amount.to_le_bytes());
+    data
+}
+
+impl Setup {
+    fn bet_accounts(&self, player: Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(player, true),
+            AccountMeta::new(self.house, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ]
+    }
+
+    fn bet(&self, player: Pubkey, amount: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &bet_data(amount),
+            self.bet_accounts(player),
+        )
+    }
+
+    fn bet_through_attacker(&self, player: Pubkey, amount: u64) -> Instruction {
+        let mut accounts = vec![AccountMeta::new_readonly(self.program_id, false)];
+        accounts.extend(self.bet_accounts(player));
+        Instruction::new_with_bytes(self.attacker_id, &bet_data(amount), accounts)
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+fn assert_bet_rejected(result: Result<(), BanksClientError>, index: u8, expected: UltraError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(index, InstructionError::Custom(expected as u32))
+    );
+}
+
+#[tokio::test]
+async fn bets_through_cpi_are_rejected() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+
+    for amount in [STAKE, STAKE + 1, STAKE + 2] {
+        let wrapped = setup.bet_through_attacker(payer, amount);
+        let result = send(&mut setup.context, &[wrapped], &[]).await;
+        assert_bet_rejected(result, 0, UltraError::CpiNotAllowed);
+    }
+}
+
+#[tokio::test]
+async fn instructions_after_a_bet_are_rejected() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+
+    // A transfer sized to overdraw only after a loss would abort it.
+    let bet = setup.bet(payer, STAKE);
+    let transfer = system_instruction::transfer(&payer, &setup.house, 1);
+    let result = send(&mut setup.context, &[bet, transfer], &[]).await;
+    assert_bet_rejected(result, 0, UltraError::InstructionAfterBet);
+}
+
+#[tokio::test]
+async fn stakes_cannot_strand_the_player_below_rent() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+
+    let player = Keypair::new();
+    let balance = Rent::default().minimum_balance(0) + STAKE - 1;
+    let fund = system_instruction::transfer(&payer, &player.pubkey(), balance);
+    send(&mut setup.context, &[fund], &[]).await.unwrap();
+
+    let bet = setup.bet(player.pubkey(), STAKE);
+    let result = send(&mut setup.context, &[bet], &[&player]).await;
+    assert_bet_rejected(result, 0, UltraError::PlayerBelowRentExempt);
+}
@@ -0,0 +1,418 @@
+use catflip_ultra::{
+    commitment_address, header, house_address, is_winning_roll, payout, process_instruction,
+    UltraError, OP_BET, OP_COMMIT, OP_INIT_HOUSE, OP_RESOLVE, OP_WITHDRAW, RESOLVE_DELAY_SLOTS,
+    VERSION,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    hash::{hashv, Hash},
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    slot_hashes::{SlotHashes, MAX_ENTRIES},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+const STAKE: u64 = LAMPORTS_PER_SOL / 100;
+/// The house's authority and its reserved payouts.
+const HOUSE_LEN: usize = 40;
+
+struct Setup {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    house: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program("catflip_ultra", program_id, processor!(process_instruction));
+    let context = program_test.start_with_context().await;
+    Setup {
+        context,
+        program_id,
+        house: house_address(&program_id).0,
+    }
+}
+
+impl Setup {
+    fn init_house(&self, authority: Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &[header(OP_INIT_HOUSE)],
+            vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(self.house, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        )
+    }
+
+    fn bet(&self, player: Pubkey, house: Pubkey, amount: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &with_amount(OP_BET, amount),
+            vec![
+                AccountMeta::new(player, true),
+                AccountMeta::new(house, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        )
+    }
+
+    fn withdraw(&self, authority: Pubkey, amount: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &with_amount(OP_WITHDRAW, amount),
+            vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(self.house, false),
+            ],
+        )
+    }
+
+    fn commit(&self, player: Pubkey, amount: u64, nonce: u64) -> Instruction {
+        let mut data = with_amount(OP_COMMIT, amount);
+        data.extend(nonce.to_le_bytes());
+        Instruction::new_with_bytes(
+            self.program_id,
+            &data,
+            vec![
+                AccountMeta::new(player, true),
+                AccountMeta::new(self.house, false),
+                AccountMeta::new(
+                    commitment_address(&self.program_id, &player, nonce).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        )
+    }
+
+    fn resolve(&self, player: Pubkey, nonce: u64) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &[header(OP_RESOLVE)],
+            vec![
+                AccountMeta::new(player, false),
+                AccountMeta::new(self.house, false),
+                AccountMeta::new(
+                    commitment_address(&self.program_id, &player, nonce).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+            ],
+        )
+    }
+
+    /// Creates a funded house and a player holding 1 SOL.
+    async fn open_house(&mut self) -> Keypair {
+        let payer = self.context.payer.pubkey();
+        let player = Keypair::new();
+        let init = self.init_house(payer);
+        let fund = system_instruction::transfer(&payer, &self.house, 10 * LAMPORTS_PER_SOL);
+        let fund_player = system_instruction::transfer(&payer, &player.pubkey(), LAMPORTS_PER_SOL);
+        send(&mut self.context, &[init, fund, fund_player], &[])
+            .await
+            .unwrap();
+        player
+    }
+
+    async fn slot(&mut self) -> u64 {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+            .slot
+    }
+
+    async fn house_account(&mut self) -> Account {
+        self.context
+            .banks_client
+            .get_account(self.house)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(address)
+            .await
+            .unwrap()
+    }
+}
+
+/// Payouts the house holds for open commitments.
+fn reserved(house: &Account) -> u64 {
+    u64::from_le_bytes(house.data[32..].try_into().unwrap())
+}
+
+fn with_amount(opcode: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![header(opcode)];
+    data.extend(amount.to_le_bytes());
+    data
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+fn assert_rejected(result: Result<(), BanksClientError>, expected: InstructionError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, expected)
+    );
+}
+
+#[tokio::test]
+async fn init_house_creates_a_program_owned_pda() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+
+    // Lamports sent to the address beforehand must not block creation.
+    let prefund = system_instruction::transfer(&payer, &setup.house, 1_000_000);
+    send(&mut setup.context, &[prefund], &[]).await.unwrap();
+
+    let init = setup.init_house(payer);
+    send(&mut setup.context, &[init], &[]).await.unwrap();
+
+    let house = setup.house_account().await;
+    assert_eq!(house.owner, setup.program_id);
+    assert_eq!(house.data[..32], payer.to_bytes());
+    assert_eq!(reserved(&house), 0);
+    assert!(house.lamports >= Rent::default().minimum_balance(HOUSE_LEN));
+
+    // Nobody can take the house over by initializing it again.
+    let usurper = Keypair::new();
+    let again = setup.init_house(usurper.pubkey());
+    let result = send(&mut setup.context, &[again], &[&usurper]).await;
+    assert_rejected(result, InstructionError::AccountAlreadyInitialized);
+}
+
+#[tokio::test]
+async fn unknown_versions_and_opcodes_are_rejected() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+
+    for data in [
+        vec![],
+        vec![(VERSION + 1) << 4 | OP_INIT_HOUSE],
+        vec![header(0x0f)],
+        // Bets need the full 8-byte amount.
+        vec![header(OP_BET), 1, 2, 3],
+    ] {
+        let mut instruction = setup.init_house(payer);
+        instruction.data = data;
+        let result = send(&mut setup.context, &[instruction], &[]).await;
+        assert_rejected(result, InstructionError::InvalidInstructionData);
+    }
+}
+
+#[tokio::test]
+async fn bets_move_the_stake_through_the_system_program() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+    let init = setup.init_house(payer);
+    let fund = system_instruction::transfer(&payer, &setup.house, 10 * LAMPORTS_PER_SOL);
+    send(&mut setup.context, &[init, fund], &[]).await.unwrap();
+
+    let player = Keypair::new();
+    let fund = system_instruction::transfer(&payer, &player.pubkey(), LAMPORTS_PER_SOL);
+    send(&mut setup.context, &[fund], &[]).await.unwrap();
+
+    for amount in [STAKE, STAKE + 1, STAKE + 2, STAKE + 3] {
+        let player_before = setup.balance(player.pubkey()).await;
+        let house_before = setup.balance(setup.house).await;
+
+        let bet = setup.bet(player.pubkey(), setup.house, amount);
+        send(&mut setup.context, &[bet], &[&player]).await.unwrap();
+
+        // The payer covers fees, so lamports move only between the two.
+        let player_after = setup.balance(player.pubkey()).await;
+        let house_after = setup.balance(setup.house).await;
+        assert_eq!(player_before + house_before, player_after + house_after);
+        let won = payout(amount).unwrap() - amount;
+        assert!(player_after == player_before - amount || player_after == player_before + won);
+    }
+}
+
+#[tokio::test]
+async fn bets_need_a_house_owned_by_the_program() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+
+    let bet = setup.bet(payer, Pubkey::new_unique(), STAKE);
+    let result = send(&mut setup.context, &[bet], &[]).await;
+    assert_rejected(result, InstructionError::IncorrectProgramId);
+}
+
+#[tokio::test]
+async fn only_the_authority_withdraws_and_never_below_rent() {
+    let mut setup = setup().await;
+    let payer = setup.context.payer.pubkey();
+    let authority = Keypair::new();
+    let fund = system_instruction::transfer(&payer, &authority.pubkey(), LAMPORTS_PER_SOL);
+    send(&mut setup.context, &[fund], &[]).await.unwrap();
+    let init = setup.init_house(authority.pubkey());
+    let fund = system_instruction::transfer(&payer, &setup.house, LAMPORTS_PER_SOL);
+    send(&mut setup.context, &[init, fund], &[&authority])
+        .await
+        .unwrap();
+
+    let stranger = setup.withdraw(payer, 1);
+    let result = send(&mut setup.context, &[stranger], &[]).await;
+    assert_rejected(
+        result,
+        InstructionError::Custom(UltraError::Unauthorized as u32),
+    );
+
+    let rent_minimum = Rent::default().minimum_balance(HOUSE_LEN);
+    let available = setup.house_account().await.lamports - rent_minimum;
+    let too_much = setup.withdraw(authority.pubkey(), available + 1);
+    let result = send(&mut setup.context, &[too_much], &[&authority]).await;
+    assert_rejected(result, InstructionError::InsufficientFunds);
+
+    let everything = setup.withdraw(authority.pubkey(), available);
+    send(&mut setup.context, &[everything], &[&authority])
+        .await
+        .unwrap();
+    assert_eq!(setup.house_account().await.lamports, rent_minimum);
+}
+
+#[tokio::test]
+async fn committed_bets_resolve_from_a_later_slot_hash() {
+    let mut setup = setup().await;
+    let player = setup.open_house().await;
+    let before = setup.balance(player.pubkey()).await;
+
+    let commit = setup.commit(player.pubkey(), STAKE, 7);
+    send(&mut setup.context, &[commit], &[&player])
+        .await
+        .unwrap();
+    let target = setup.slot().await + RESOLVE_DELAY_SLOTS;
+
+    let resolve = setup.resolve(player.pubkey(), 7);
+    let result = send(&mut setup.context, std::slice::from_ref(&resolve), &[]).await;
+    assert_rejected(
+        result,
+        InstructionError::Custom(UltraError::BetNotReady as u32),
+    );
+
+    setup.context.warp_to_slot(target + 1).unwrap();
+    let slot_hashes: SlotHashes = setup.context.banks_client.get_sysvar().await.unwrap();
+    let (_, slot_hash) = slot_hashes
+        .iter()
+        .rev()
+        .find(|(slot, _)| *slot >= target)
+        .unwrap();
+    let entropy = hashv(&[
+        slot_hash.as_ref(),
+        player.pubkey().as_ref(),
+        &7u64.to_le_bytes(),
+    ]);
+    let wins = is_winning_roll(u64::from_le_bytes(
+        entropy.to_bytes()[..8].try_into().unwrap(),
+    ));
+
+    // Anyone can resolve; the fee payer does here.
+    send(&mut setup.context, &[resolve], &[]).await.unwrap();
+    let payout = if wins { payout(STAKE).unwrap() } else { 0 };
+    assert_eq!(
+        setup.balance(player.pubkey()).await,
+        before - STAKE + payout
+    );
+}
+
+#[tokio::test]
+async fn bets_whose_slot_hash_aged_out_are_refunded() {
+    let mut setup = setup().await;
+    let player = setup.open_house().await;
+    let before = setup.balance(player.pubkey()).await;
+
+    let commit = setup.commit(player.pubkey(), STAKE, 1);
+    send(&mut setup.context, &[commit], &[&player])
+        .await
+        .unwrap();
+
+    // A full sysvar that starts after the target slot.
+    let oldest = setup.slot().await + RESOLVE_DELAY_SLOTS + 1;
+    let entries: Vec<_> = (oldest..oldest + MAX_ENTRIES as u64)
+        .map(|slot| (slot, Hash::new_unique()))
+        .collect();
+    setup.context.set_sysvar(&SlotHashes::new(&entries));
+
+    let resolve = setup.resolve(player.pubkey(), 1);
+    send(&mut setup.context, &[resolve], &[]).await.unwrap();
+    assert_eq!(setup.balance(player.pubkey()).await, before);
+}
+
+#[tokio::test]
+async fn commitments_cannot_stand_in_for_the_house() {
+    let mut setup = setup().await;
+    let player = setup.open_house().await;
+    let commit = setup.commit(player.pubkey(), STAKE, 3);
+    send(&mut setup.context, &[commit], &[&player])
+        .await
+        .unwrap();
+
+    let (commitment, _) = commitment_address(&setup.program_id, &player.pubkey(), 3);
+    let bet = setup.bet(player.pubkey(), commitment, STAKE);
+    let result = send(&mut setup.context, &[bet], &[&player]).await;
+    assert_rejected(result, InstructionError::InvalidAccountData);
+}
+
+#[tokio::test]
+async fn open_commitments_are_reserved_from_the_authority() {
+    let mut setup = setup().await;
+    let player = setup.open_house().await;
+    let authority = setup.context.payer.pubkey();
+    let commit = setup.commit(player.pubkey(), STAKE, 4);
+    send(&mut setup.context, &[commit], &[&player])
+        .await
+        .unwrap();
+    let target = setup.slot().await + RESOLVE_DELAY_SLOTS;
+    let payout = payout(STAKE).unwrap();
+    let house = setup.house_account().await;
+    assert_eq!(reserved(&house), payout);
+
+    // The authority can take everything but the payout the bet may win.
+    let available = house.lamports - Rent::default().minimum_balance(HOUSE_LEN) - payout;
+    let too_much = setup.withdraw(authority, available + 1);
+    let result = send(&mut setup.context, &[too_much], &[]).await;
+    assert_rejected(result, InstructionError::InsufficientFunds);
+    let everything = setup.withdraw(authority, available);
+    send(&mut setup.context, &[everything], &[]).await.unwrap();
+
+    // What is reserved for one bet doesn't cover another.
+    let commit = setup.commit(player.pubkey(), STAKE, 5);
+    let result = send(&mut setup.context, &[commit], &[&player]).await;
+    assert_rejected(result, InstructionError::InsufficientFunds);
+
+    setup.context.warp_to_slot(target + 1).unwrap();
+    let resolve = setup.resolve(player.pubkey(), 4);
+    send(&mut setup.context, &[resolve], &[]).await.unwrap();
+    assert_eq!(reserved(&setup.house_account().await), 0);
+}
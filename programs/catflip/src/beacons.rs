@@ -0,0 +1,104 @@
+//! Threshold randomness from oracle beacons.
+//!
+//! A beacon is an oracle's ed25519 signature over a round's VRF input, which
+//! covers both the round's address and the player's seed. The runtime checks
+//! the signatures in an Ed25519 precompile instruction placed right before
+//! `fulfill_randomness`; this module only reads that instruction back through
+//! the instructions sysvar and checks that it signs the right input with the
+//! right keys. Hashing the signatures together means one oracle alone can't
+//! choose the outcome.
+//!
+//! It can still veto one. Beacons are public once served, so any signing
+//! oracle can collect the others', work out the outcome and withhold its own.
+//! The round then times out and refunds, which is the same power the VRF key
+//! holder has over its rounds: one oracle can void outcomes it dislikes but
+//! can't make any outcome happen. Ruling that out would take a commit-reveal
+//! round before the beacons, which this module doesn't do.
+//!
+//! Only the first `oracle_threshold` registered oracles count. Letting any
+//! `oracle_threshold` of them sign would let whoever submits the beacons
+//! pick between subsets, and so between outcomes; the remaining oracles are
+//! standbys that `set_oracles` can move to the front.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    hash::hashv,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::errors::CatflipError;
+use crate::state::Vault;
+
+/// Size of one `Ed25519SignatureOffsets` entry.
+const OFFSETS_LEN: usize = 14;
+/// Offsets entries start after the signature count and a padding byte.
+const OFFSETS_START: usize = 2;
+/// Instruction index meaning "this instruction's own data".
+const THIS_INSTRUCTION: u16 = u16::MAX;
+
+/// Checks the beacons over `alpha`, a round's VRF input, and hashes them into
+/// the round's entropy.
+///
+/// The precompile instruction must carry exactly `oracle_threshold`
+/// signatures, one from each of the first `oracle_threshold` registered
+/// oracles and each over `alpha`. They are hashed in registration order, so
+/// the order they were submitted in doesn't matter.
+pub fn entropy(vault: &Vault, alpha: &[u8], instructions: &AccountInfo) -> Result<[u8; 32]> {
+    let current = load_current_index_checked(instructions)?;
+    let precompile = current
+        .checked_sub(1)
+        .map(|index| load_instruction_at_checked(index as usize, instructions))
+        .transpose()?
+        .ok_or(CatflipError::InvalidOracleBeacons)?;
+    require_keys_eq!(
+        precompile.program_id,
+        ed25519_program::ID,
+        CatflipError::InvalidOracleBeacons
+    );
+    
+    let data = &precompile.data;
+    let count = *data.first().ok_or(CatflipError::InvalidOracleBeacons)? as usize;
+    require!(
+        count == vault.oracle_threshold as usize,
+        CatflipError::InvalidOracleBeacons
+    );
+    
+    let oracles = vault
+        .oracles()
+        .get(..count)
+        .ok_or(CatflipError::InvalidOracleBeacons)?;
+    let mut signatures: [Option<&[u8]>; Vault::MAX_ORACLES] = [None; Vault::MAX_ORACLES];
+    for entry in 0..count {
+        let start = OFFSETS_START + entry * OFFSETS_LEN;
+        let offsets = data
+            .get(start..start + OFFSETS_LEN)
+            .ok_or(CatflipError::InvalidOracleBeacons)?;
+        let field = |index: usize| u16::from_le_bytes([offsets[2 * index], offsets[2 * index + 1]]);
+        let (signature_offset, public_key_offset, message_offset, message_len) =
+            (field(0), field(2), field(4), field(5));
+        // Data taken from other instructions could be swapped out by
+        // whoever builds the transaction.
+        require!(
+            [field(1), field(3), field(6)] == [THIS_INSTRUCTION; 3],
+            CatflipError::InvalidOracleBeacons
+        );
+        
+        let slice = |offset: u16, len: usize| {
+            data.get(offset as usize..offset as usize + len)
+                .ok_or(CatflipError::InvalidOracleBeacons)
+        };
+        let message = slice(message_offset, message_len as usize)?;
+        require!(message == alpha, CatflipError::InvalidOracleBeacons);
+        let public_key = slice(public_key_offset, 32)?;
+        let oracle = oracles
+            .iter()
+            .position(|oracle| oracle.as_ref() == public_key)
+            .ok_or(CatflipError::InvalidOracleBeacons)?;
+        require!(signatures[oracle].is_none(), CatflipError::InvalidOracleBeacons);
+        signatures[oracle] = Some(slice(signature_offset, 64)?);
+    }
+    
+    let signatures: Vec<&[u8]> = signatures.iter().flatten().copied().collect();
+    Ok(hashv(&signatures).to_bytes())
+}
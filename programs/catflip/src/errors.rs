@@ -28,4 +28,79 @@ pub enum CatflipError {
     
     #[msg("Math overflow")]
     MathOverflow,
-}
\ No newline at end of file
+    
+    #[msg("Invalid vault configuration")]
+    InvalidConfig,
+    
+    #[msg("VRF proof does not verify for this round")]
+    InvalidVrfProof,
+    
+    #[msg("Oracle beacons are missing or do not match the registered oracles")]
+    InvalidOracleBeacons,
+    
+    #[msg("The vault settles bets from a different randomness source")]
+    RandomnessSourceMismatch,
+    
+    #[msg("Potential payout is below the player's minimum")]
+    PayoutBelowMinimum,
+    
+    #[msg("House edge is above the player's maximum")]
+    HouseEdgeAboveMaximum,
+    
+    #[msg("Bet arrived after its last valid slot")]
+    BetExpired,
+    
+    #[msg("Player balance is too low")]
+    InsufficientPlayerBalance,
+    
+    #[msg("The round was staked from a player balance, which must be passed")]
+    PlayerBalanceRequired,
+    
+    #[msg("Session has expired")]
+    SessionExpired,
+    
+    #[msg("Session does not allow this kind of bet")]
+    SessionNotAllowed,
+    
+    #[msg("Bet would take the session over its maximum total stake")]
+    SessionStakeExceeded,
+    
+    #[msg("Batch plan has no flips, too many, or cannot play its first flip")]
+    InvalidBatch,
+    
+    #[msg("The round is a batch, whose BetBatch must be passed")]
+    BatchRequired,
+    
+    #[msg("The round is not a streak with a won step")]
+    StreakNotWon,
+    
+    #[msg("Streak has played its maximum number of steps")]
+    StreakLimitReached,
+    
+    #[msg("Challenge has already been matched")]
+    ChallengeAlreadyMatched,
+    
+    #[msg("Challenge has not been matched yet")]
+    ChallengeNotMatched,
+    
+    #[msg("Players cannot match their own challenge")]
+    CannotMatchOwnChallenge,
+    
+    #[msg("Pool round is not taking positions")]
+    PoolNotOpen,
+    
+    #[msg("Pool round is not in the phase this needs")]
+    PoolPhaseMismatch,
+    
+    #[msg("Position is on the other side of the pool")]
+    PositionSideMismatch,
+    
+    #[msg("The round has timed out and can only be refunded")]
+    RoundTimedOut,
+    
+    #[msg("This legacy house has already been imported")]
+    LegacyHouseAlreadyImported,
+    
+    #[msg("The slot hash this round's outcome waits for hasn't been produced yet")]
+    RandomnessNotReady,
+}
@@ -2,9 +2,40 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 // Note: In production, you would use Switchboard On-Demand SDK
 // For now, we'll simulate the VRF request structure
-use crate::state::{Vault, BetRound};
+use crate::state::{Vault, BetRound, PlayerBalance};
 use crate::errors::CatflipError;
 
+/// The worst terms a player accepts. Anything that changed between quoting
+/// and landing, like the edge or the vault's liquidity, fails the bet instead
+/// of filling it worse. `None` leaves a term unchecked.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BetGuards {
+    pub min_payout: Option<u64>,
+    pub max_edge_bps: Option<u16>,
+    pub last_valid_slot: Option<u64>,
+}
+
+impl BetGuards {
+    pub fn check(&self, vault: &Vault, potential_payout: u64, slot: u64) -> Result<()> {
+        if let Some(last_valid_slot) = self.last_valid_slot {
+            require!(slot <= last_valid_slot, CatflipError::BetExpired);
+        }
+        if let Some(max_edge_bps) = self.max_edge_bps {
+            require!(
+                vault.house_edge_bps <= max_edge_bps,
+                CatflipError::HouseEdgeAboveMaximum
+            );
+        }
+        if let Some(min_payout) = self.min_payout {
+            require!(
+                potential_payout >= min_payout,
+                CatflipError::PayoutBelowMinimum
+            );
+        }
+        Ok(())
+    }
+}
+
 #[derive(Accounts)]
 pub struct Bet<'info> {
     #[account(mut)]
@@ -27,6 +58,14 @@ pub struct Bet<'info> {
     )]
     pub bet_round: Account<'info, BetRound>,
     
+    /// Stakes from the player's balance instead of their wallet when given.
+    #[account(
+        mut,
+        seeds = [b"balance", player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    
     // Mock VRF account for now - in production use real Switchboard account
     pub vrf: AccountInfo<'info>,
     
@@ -45,64 +84,85 @@ pub struct Bet<'info> {
     pub switchboard_program: AccountInfo<'info>,
 }
 
-pub fn handler(ctx: Context<Bet>, amount_lamports: u64) -> Result<()> {
-    // Get immutable data first
-    let vault_balance = ctx.accounts.vault.to_account_info().lamports();
-    
-    let vault = &mut ctx.accounts.vault;
-    let bet_round = &mut ctx.accounts.bet_round;
+pub fn handler(
+    ctx: Context<Bet>,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Result<()> {
     let clock = Clock::get()?;
+    let potential_payout = quote_guarded(&ctx.accounts.vault, amount_lamports, &guards, clock.slot)?;
     
-    require!(
-        amount_lamports >= vault.min_bet_lamports,
-        CatflipError::BetBelowMinimum
-    );
-    let max_bet = vault_balance
-        .checked_mul(vault.max_exposure_bps as u64)
-        .ok_or(CatflipError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(CatflipError::MathOverflow)?;
-    
-    require!(
-        amount_lamports <= max_bet,
-        CatflipError::BetExceedsMaxExposure
-    );
-    
-    let house_edge_multiplier = 10000u64
-        .checked_sub(vault.house_edge_bps as u64)
-        .ok_or(CatflipError::MathOverflow)?;
-    let potential_payout = amount_lamports
-        .checked_mul(2)
-        .ok_or(CatflipError::MathOverflow)?
-        .checked_mul(house_edge_multiplier)
-        .ok_or(CatflipError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(CatflipError::MathOverflow)?;
-    
-    require!(
-        vault_balance >= potential_payout,
-        CatflipError::InsufficientVaultBalance
-    );
-    
-    system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.player.to_account_info(),
-                to: vault.to_account_info(),
-            },
-        ),
+    let vault = &mut ctx.accounts.vault;
+    match ctx.accounts.player_balance.as_mut() {
+        Some(player_balance) => player_balance.debit(vault, amount_lamports)?,
+        None => system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.player.to_account_info(),
+                    to: vault.to_account_info(),
+                },
+            ),
+            amount_lamports,
+        )?,
+    }
+    
+    open_round(
+        vault,
+        &mut ctx.accounts.bet_round,
+        ctx.bumps.bet_round,
+        ctx.accounts.player.key(),
         amount_lamports,
-    )?;
+        potential_payout,
+        player_seed,
+        ctx.accounts.player_balance.is_some(),
+    )
+}
+
+/// The payout a bet of `amount_lamports` would get, if it is within the
+/// vault's limits and the player's `guards`. Call it before the stake moves:
+/// the quote is against the bankroll without it.
+pub(crate) fn quote_guarded(
+    vault: &Account<Vault>,
+    amount_lamports: u64,
+    guards: &BetGuards,
+    slot: u64,
+) -> Result<u64> {
+    let vault_balance = vault.bankroll(vault.to_account_info().lamports());
+    let potential_payout = vault.quote(vault_balance, amount_lamports)?;
+    guards.check(vault, potential_payout, slot)?;
+    Ok(potential_payout)
+}
+
+/// Fills in a new round once its stake is in the vault, and counts it in
+/// the vault's stats.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn open_round(
+    vault: &mut Vault,
+    bet_round: &mut Account<BetRound>,
+    bump: u8,
+    player: Pubkey,
+    amount_lamports: u64,
+    potential_payout: u64,
+    player_seed: [u8; 32],
+    from_balance: bool,
+) -> Result<()> {
+    let clock = Clock::get()?;
     
-    bet_round.player = ctx.accounts.player.key();
+    bet_round.player = player;
     bet_round.stake_lamports = amount_lamports;
     bet_round.potential_payout = potential_payout;
     bet_round.timestamp = clock.unix_timestamp;
     bet_round.slot = clock.slot;
     bet_round.is_settled = false;
     bet_round.is_winner = false;
-    bet_round.bump = ctx.bumps.bet_round;
+    bet_round.bump = bump;
+    bet_round.player_seed = player_seed;
+    bet_round.from_balance = from_balance;
+    bet_round.flips = 0;
+    bet_round.streak = 0;
+    bet_round.reopened_slot = 0;
     
     vault.total_volume = vault.total_volume
         .checked_add(amount_lamports)
@@ -111,23 +171,16 @@ pub fn handler(ctx: Context<Bet>, amount_lamports: u64) -> Result<()> {
         .checked_add(1)
         .ok_or(CatflipError::MathOverflow)?;
     
-    // Mock randomness request - in production, use Switchboard On-Demand
-    msg!("Mock VRF request - in production, integrate with Switchboard On-Demand");
-    
-    // Generate a mock request ID for now
-    let mock_randomness = [0u8; 32];
-    bet_round.vrf_request_randomness = mock_randomness;
-    
     msg!("BetPlaced: player={}, stake={}, round={}", 
-        ctx.accounts.player.key(), 
+        player, 
         amount_lamports,
-        ctx.accounts.bet_round.key()
+        bet_round.key()
     );
     
     emit!(BetPlaced {
-        player: ctx.accounts.player.key(),
+        player,
         stake: amount_lamports,
-        round_pubkey: ctx.accounts.bet_round.key(),
+        round_pubkey: bet_round.key(),
         timestamp: clock.unix_timestamp,
     });
     
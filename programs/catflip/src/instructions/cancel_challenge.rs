@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::state::Challenge;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct CancelChallenge<'info> {
+    /// The creator for an unmatched challenge; anyone once a matched one
+    /// has timed out.
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    
+    /// Required once the challenge is matched; takes their stake back.
+    #[account(
+        mut,
+        constraint = opponent.key() == challenge.opponent @ CatflipError::Unauthorized
+    )]
+    pub opponent: Option<SystemAccount<'info>>,
+    
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"challenge", creator.key().as_ref(), &challenge.slot.to_le_bytes()],
+        bump = challenge.bump
+    )]
+    pub challenge: Account<'info, Challenge>,
+}
+
+/// Returns the stakes of a challenge that won't be settled: an unmatched
+/// one at its creator's request, or a matched one that has waited past its
+/// timeout for randomness, with the same semantics as `refund_timeout`.
+pub fn handler(ctx: Context<CancelChallenge>) -> Result<()> {
+    let challenge = &ctx.accounts.challenge;
+    
+    if challenge.is_matched() {
+        require!(
+            Clock::get()?.slot > challenge.timeout_slot(),
+            CatflipError::BetNotTimedOut
+        );
+        let opponent = ctx.accounts.opponent
+            .as_ref()
+            .ok_or(CatflipError::Unauthorized)?;
+        **challenge.to_account_info().lamports.borrow_mut() = challenge
+            .to_account_info()
+            .lamports()
+            .checked_sub(challenge.stake)
+            .ok_or(CatflipError::MathOverflow)?;
+        **opponent.lamports.borrow_mut() = opponent
+            .lamports()
+            .checked_add(challenge.stake)
+            .ok_or(CatflipError::MathOverflow)?;
+    } else {
+        require_keys_eq!(
+            ctx.accounts.caller.key(),
+            challenge.creator,
+            CatflipError::Unauthorized
+        );
+    }
+    // The creator's stake leaves with the account's rent.
+    
+    msg!("ChallengeCancelled: creator={}, opponent={}, stake={}, challenge={}",
+        challenge.creator,
+        challenge.opponent,
+        challenge.stake,
+        challenge.key()
+    );
+    
+    emit!(ChallengeCancelled {
+        creator: challenge.creator,
+        opponent: challenge.opponent,
+        stake: challenge.stake,
+        challenge: challenge.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct ChallengeCancelled {
+    pub creator: Pubkey,
+    /// `Pubkey::default()` if the challenge was never matched.
+    pub opponent: Pubkey,
+    /// Each player's stake, returned to them.
+    pub stake: u64,
+    pub challenge: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, BetRound};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct CashOut<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    // Not gated on pause: a won payout is the player's to take.
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"bet", player.key().as_ref(), &bet_round.slot.to_le_bytes()],
+        bump = bet_round.bump,
+        constraint = bet_round.player == player.key() @ CatflipError::Unauthorized,
+        constraint = bet_round.streak > 0
+            && bet_round.is_settled
+            && bet_round.is_winner @ CatflipError::StreakNotWon
+    )]
+    pub bet_round: Account<'info, BetRound>,
+}
+
+/// Ends a streak after a won step: pays the payout in the box to the player,
+/// releases its reserve and closes the round.
+pub fn handler(ctx: Context<CashOut>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let bet_round = &ctx.accounts.bet_round;
+    let payout = bet_round.potential_payout;
+    
+    vault.reserved_payouts = vault.reserved_payouts
+        .checked_sub(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    **vault.to_account_info().lamports.borrow_mut() = vault
+        .to_account_info()
+        .lamports()
+        .checked_sub(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    **ctx.accounts.player.to_account_info().lamports.borrow_mut() = ctx
+        .accounts.player
+        .lamports()
+        .checked_add(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    msg!("StreakCashedOut: player={}, steps={}, payout={}, round={}",
+        bet_round.player,
+        bet_round.streak,
+        payout,
+        bet_round.key()
+    );
+    
+    emit!(StreakCashedOut {
+        player: bet_round.player,
+        steps: bet_round.streak,
+        payout,
+        round_pubkey: bet_round.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct StreakCashedOut {
+    pub player: Pubkey,
+    /// Steps won, the opening bet included.
+    pub steps: u8,
+    pub payout: u64,
+    pub round_pubkey: Pubkey,
+    pub timestamp: i64,
+}
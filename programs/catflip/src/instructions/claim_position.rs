@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, PoolRound, PoolPosition, PoolPhase, Side};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct ClaimPosition<'info> {
+    // Claims are permissionless; the payout and the position's rent always
+    // go to the player recorded on it.
+    #[account(mut)]
+    pub player: SystemAccount<'info>,
+    
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Paid the round's rent when opening it and gets it back when the
+    /// round closes.
+    #[account(mut, address = vault.authority @ CatflipError::Unauthorized)]
+    pub authority: SystemAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"pool".as_ref(), &pool.round_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PoolRound>,
+    
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"position", pool.key().as_ref(), player.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, PoolPosition>,
+}
+
+/// Pays a position of a resolved round what it won, or its stake if the
+/// round was uncontested, and closes it. A losing position closes with
+/// nothing but its rent. The last claim closes the round too, along with
+/// any rounding dust.
+pub fn handler(ctx: Context<ClaimPosition>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let position = &ctx.accounts.position;
+    require!(
+        pool.phase(Clock::get()?.slot) == PoolPhase::Resolved,
+        CatflipError::PoolPhaseMismatch
+    );
+    
+    let payout = pool.payout(position.side, position.amount);
+    **pool.to_account_info().lamports.borrow_mut() = pool
+        .to_account_info()
+        .lamports()
+        .checked_sub(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    **ctx.accounts.player.lamports.borrow_mut() = ctx
+        .accounts.player
+        .lamports()
+        .checked_add(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    msg!("PositionClaimed: player={}, side={:?}, amount={}, payout={}, pool={}",
+        position.player,
+        position.side,
+        position.amount,
+        payout,
+        pool.key()
+    );
+    
+    emit!(PositionClaimed {
+        player: position.player,
+        side: position.side,
+        amount: position.amount,
+        payout,
+        pool: pool.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    if pool.release_position() {
+        pool.close(ctx.accounts.authority.to_account_info())?;
+    }
+    
+    Ok(())
+}
+
+#[event]
+pub struct PositionClaimed {
+    pub player: Pubkey,
+    pub side: Side,
+    pub amount: u64,
+    pub payout: u64,
+    pub pool: Pubkey,
+    pub timestamp: i64,
+}
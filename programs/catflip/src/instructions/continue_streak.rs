@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, BetRound};
+use crate::errors::CatflipError;
+use super::bet::{quote_guarded, BetGuards};
+
+#[derive(Accounts)]
+pub struct ContinueStreak<'info> {
+    pub player: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"bet", player.key().as_ref(), &bet_round.slot.to_le_bytes()],
+        bump = bet_round.bump,
+        constraint = bet_round.player == player.key() @ CatflipError::Unauthorized,
+        constraint = bet_round.streak > 0
+            && bet_round.is_settled
+            && bet_round.is_winner @ CatflipError::StreakNotWon
+    )]
+    pub bet_round: Account<'info, BetRound>,
+}
+
+/// Opens the box again: stakes the won payout on another step of the same
+/// round. The step is quoted like a new bet against the bankroll with the
+/// payout released, so it fails once doubling would take it over
+/// `max_exposure_bps` and the player can only cash out.
+pub fn handler(
+    ctx: Context<ContinueStreak>,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let bet_round = &mut ctx.accounts.bet_round;
+    require!(bet_round.streak < BetRound::MAX_STREAK, CatflipError::StreakLimitReached);
+    
+    let stake = bet_round.potential_payout;
+    ctx.accounts.vault.reserved_payouts = ctx.accounts.vault.reserved_payouts
+        .checked_sub(stake)
+        .ok_or(CatflipError::MathOverflow)?;
+    let potential_payout = quote_guarded(&ctx.accounts.vault, stake, &guards, clock.slot)?;
+    
+    let vault = &mut ctx.accounts.vault;
+    vault.total_volume = vault.total_volume
+        .checked_add(stake)
+        .ok_or(CatflipError::MathOverflow)?;
+    vault.total_bets = vault.total_bets
+        .checked_add(1)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    bet_round.stake_lamports = stake;
+    bet_round.potential_payout = potential_payout;
+    bet_round.timestamp = clock.unix_timestamp;
+    bet_round.reopened_slot = clock.slot;
+    bet_round.player_seed = player_seed;
+    bet_round.is_settled = false;
+    bet_round.is_winner = false;
+    bet_round.streak += 1;
+    
+    msg!("StreakContinued: player={}, step={}, stake={}, round={}",
+        bet_round.player,
+        bet_round.streak,
+        stake,
+        bet_round.key()
+    );
+    
+    emit!(StreakContinued {
+        player: bet_round.player,
+        step: bet_round.streak,
+        stake,
+        round_pubkey: bet_round.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct StreakContinued {
+    pub player: Pubkey,
+    /// The step now open, counting the opening bet as the first.
+    pub step: u8,
+    /// The previous step's payout, staked whole.
+    pub stake: u64,
+    pub round_pubkey: Pubkey,
+    pub timestamp: i64,
+}
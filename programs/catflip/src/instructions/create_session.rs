@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::Session;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = player,
+        space = Session::SIZE,
+        seeds = [b"session", player.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Lets `session_key` bet for the player until `expires_slot`, staking at
+/// most `max_stake` in total, in the ways `allowed` lists. A session that may
+/// bet from the wallet takes `max_stake` from it now, see [`Session::BET`].
+pub fn handler(
+    ctx: Context<CreateSession>,
+    session_key: Pubkey,
+    expires_slot: u64,
+    max_stake: u64,
+    allowed: u8,
+) -> Result<()> {
+    require!(
+        allowed != 0 && allowed & !Session::ALL == 0,
+        CatflipError::InvalidConfig
+    );
+    require!(expires_slot >= Clock::get()?.slot, CatflipError::SessionExpired);
+    
+    if allowed & Session::BET != 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.player.to_account_info(),
+                    to: ctx.accounts.session.to_account_info(),
+                },
+            ),
+            max_stake,
+        )?;
+    }
+    
+    let session = &mut ctx.accounts.session;
+    session.player = ctx.accounts.player.key();
+    session.session_key = session_key;
+    session.expires_slot = expires_slot;
+    session.max_stake = max_stake;
+    session.staked = 0;
+    session.allowed = allowed;
+    session.bump = ctx.bumps.session;
+    
+    msg!("SessionCreated: player={}, session_key={}, expires_slot={}, max_stake={}, allowed={}",
+        session.player,
+        session_key,
+        expires_slot,
+        max_stake,
+        allowed
+    );
+    
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{Vault, PlayerBalance};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PlayerBalance::SIZE,
+        seeds = [b"balance", player.key().as_ref()],
+        bump
+    )]
+    pub player_balance: Account<'info, PlayerBalance>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves `amount` from the player's wallet into the vault and credits it to
+/// their balance, opening the balance on first use.
+pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.player.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let player_balance = &mut ctx.accounts.player_balance;
+    player_balance.player = ctx.accounts.player.key();
+    player_balance.bump = ctx.bumps.player_balance;
+    player_balance.credit(&mut ctx.accounts.vault, amount)?;
+    
+    msg!("Deposit: player={}, amount={}, balance={}",
+        player_balance.player,
+        amount,
+        player_balance.lamports
+    );
+    
+    Ok(())
+}
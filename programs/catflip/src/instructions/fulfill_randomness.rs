@@ -1,38 +1,93 @@
 use anchor_lang::prelude::*;
-// Mock implementation - in production use Switchboard On-Demand SDK
-use crate::state::{Vault, BetRound};
+use anchor_lang::solana_program::sysvar;
+use crate::state::{Vault, BetRound, BetBatch, FlipResult, PlayerBalance};
 use crate::errors::CatflipError;
+use crate::{beacons, vrf};
+
+/// Where a round's outcome comes from. It has to match the vault's
+/// configuration: oracles once `set_oracles` registered a threshold, the VRF
+/// key otherwise.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Randomness {
+    /// ECVRF output and proof from the vault's VRF key.
+    Vrf { output: [u8; 64], proof: [u8; 80] },
+    /// Beacons in the Ed25519 instruction right before this one, see
+    /// [`beacons::entropy`].
+    Oracles,
+}
 
 #[derive(Accounts)]
 pub struct FulfillRandomness<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
     pub vault: Account<'info, Vault>,
     
     #[account(
         mut,
-        constraint = !bet_round.is_settled @ CatflipError::BetAlreadySettled
+        constraint = !bet_round.is_settled @ CatflipError::BetAlreadySettled,
+        constraint = bet_round.player == player.key() @ CatflipError::Unauthorized
     )]
     pub bet_round: Account<'info, BetRound>,
     
     #[account(mut)]
     pub player: SystemAccount<'info>,
     
-    // Mock VRF account - in production use real Switchboard account
-    pub vrf: AccountInfo<'info>,
+    /// Required when the round was staked from it; takes the payout.
+    #[account(
+        mut,
+        seeds = [b"balance", player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    
+    /// Required when the round is a batch; takes its results.
+    #[account(
+        mut,
+        seeds = [b"batch", bet_round.key().as_ref()],
+        bump = bet_batch.bump
+    )]
+    pub bet_batch: Option<Account<'info, BetBatch>>,
+    
+    /// CHECK: the instructions sysvar, read for oracle beacons.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<FulfillRandomness>) -> Result<()> {
+/// Settles a round from `randomness`. Anyone may submit the transaction:
+/// only the house's VRF key or the registered oracles can produce randomness
+/// that verifies, and for a given round it can come out only one way. Past
+/// its timeout a round only refunds, so whoever holds back the randomness
+/// can't settle it once `refund_timeout` is open.
+pub fn handler(ctx: Context<FulfillRandomness>, randomness: Randomness) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let bet_round = &mut ctx.accounts.bet_round;
+    // Checked up front so a missing balance fails the same whatever the roll.
+    require!(
+        !bet_round.from_balance || ctx.accounts.player_balance.is_some(),
+        CatflipError::PlayerBalanceRequired
+    );
+    require!(
+        Clock::get()?.slot <= bet_round.timeout_slot(),
+        CatflipError::RoundTimedOut
+    );
     
-    // Mock randomness - in production, get from Switchboard VRF
-    let clock = Clock::get()?;
-    let pseudo_random = clock.unix_timestamp as u64 * clock.slot;
-    let random_value = pseudo_random;
+    let alpha = bet_round.vrf_input(&bet_round.key());
+    let entropy = entropy(vault, &alpha, randomness, &ctx.accounts.instructions)?;
     
-    let is_winner = (random_value % 2) == 0;
+    if bet_round.flips > 0 {
+        let bet_batch = ctx.accounts.bet_batch
+            .as_mut()
+            .ok_or(CatflipError::BatchRequired)?;
+        return settle_batch(vault, bet_round, bet_batch, &ctx.accounts.player, &entropy);
+    }
+    
+    let random_value = u64::from_le_bytes(entropy[..8].try_into().unwrap());
+    let is_winner = BetRound::is_winning_roll(random_value);
     
     bet_round.is_settled = true;
     bet_round.is_winner = is_winner;
@@ -42,17 +97,30 @@ pub fn handler(ctx: Context<FulfillRandomness>) -> Result<()> {
     if is_winner {
         payout = bet_round.potential_payout;
         
-        **vault.to_account_info().lamports.borrow_mut() = vault
-            .to_account_info()
-            .lamports()
-            .checked_sub(payout)
-            .ok_or(CatflipError::MathOverflow)?;
-        
-        **ctx.accounts.player.lamports.borrow_mut() = ctx
-            .accounts.player
-            .lamports()
-            .checked_add(payout)
-            .ok_or(CatflipError::MathOverflow)?;
+        if bet_round.streak > 0 {
+            // The payout stays in the box until the player cashes out or
+            // stakes it on the next step.
+            vault.reserved_payouts = vault.reserved_payouts
+                .checked_add(payout)
+                .ok_or(CatflipError::MathOverflow)?;
+        } else if bet_round.from_balance {
+            ctx.accounts.player_balance
+                .as_mut()
+                .ok_or(CatflipError::PlayerBalanceRequired)?
+                .credit(vault, payout)?;
+        } else {
+            **vault.to_account_info().lamports.borrow_mut() = vault
+                .to_account_info()
+                .lamports()
+                .checked_sub(payout)
+                .ok_or(CatflipError::MathOverflow)?;
+            
+            **ctx.accounts.player.lamports.borrow_mut() = ctx
+                .accounts.player
+                .lamports()
+                .checked_add(payout)
+                .ok_or(CatflipError::MathOverflow)?;
+        }
         
         vault.total_wins = vault.total_wins
             .checked_add(1)
@@ -63,11 +131,12 @@ pub fn handler(ctx: Context<FulfillRandomness>) -> Result<()> {
         msg!("House won! Stake remains in vault");
     }
     
-    msg!("BetSettled: player={}, stake={}, winner={}, payout={}", 
+    msg!("BetSettled: player={}, stake={}, winner={}, payout={}, round={}",
         bet_round.player,
         bet_round.stake_lamports,
         is_winner,
-        payout
+        payout,
+        bet_round.key()
     );
     
     emit!(BetSettled {
@@ -76,11 +145,111 @@ pub fn handler(ctx: Context<FulfillRandomness>) -> Result<()> {
         is_winner,
         payout,
         timestamp: Clock::get()?.unix_timestamp,
+        round_pubkey: bet_round.key(),
     });
     
     Ok(())
 }
 
+/// The 32 random bytes `randomness` yields for the round or challenge whose
+/// VRF input is `alpha`, once it checks out against the vault's randomness
+/// source.
+pub(crate) fn entropy(
+    vault: &Vault,
+    alpha: &[u8],
+    randomness: Randomness,
+    instructions: &AccountInfo,
+) -> Result<[u8; 32]> {
+    match randomness {
+        Randomness::Vrf { output, proof } => {
+            require!(vault.oracle_threshold == 0, CatflipError::RandomnessSourceMismatch);
+            require!(vault.vrf_public_key != [0; 32], CatflipError::InvalidVrfAccount);
+            let verified = vrf::verify(&vault.vrf_public_key, alpha, &proof)
+                .ok_or(CatflipError::InvalidVrfProof)?;
+            require!(verified == output, CatflipError::InvalidVrfProof);
+            Ok(output[..32].try_into().unwrap())
+        }
+        Randomness::Oracles => {
+            require!(vault.oracle_threshold > 0, CatflipError::RandomnessSourceMismatch);
+            beacons::entropy(vault, alpha, instructions)
+        }
+    }
+}
+
+/// Plays a batch round's flips, each from its own roll of `entropy`, pays
+/// the player what the sequence returns and releases the round's reserve.
+fn settle_batch(
+    vault: &mut Account<Vault>,
+    bet_round: &mut Account<BetRound>,
+    bet_batch: &mut Account<BetBatch>,
+    player: &SystemAccount,
+    entropy: &[u8; 32],
+) -> Result<()> {
+    let results = bet_batch.settle(bet_round, entropy)?;
+    let payout = bet_batch.payout;
+    
+    **vault.to_account_info().lamports.borrow_mut() = vault
+        .to_account_info()
+        .lamports()
+        .checked_sub(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    **player.to_account_info().lamports.borrow_mut() = player
+        .lamports()
+        .checked_add(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    vault.reserved_payouts = vault.reserved_payouts
+        .checked_sub(bet_round.potential_payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    let staked = results
+        .iter()
+        .try_fold(0u64, |staked, flip| staked.checked_add(flip.stake))
+        .ok_or(CatflipError::MathOverflow)?;
+    let wins = bet_batch.wins.count_ones() as u64;
+    vault.total_volume = vault.total_volume
+        .checked_add(staked)
+        .ok_or(CatflipError::MathOverflow)?;
+    vault.total_bets = vault.total_bets
+        .checked_add(results.len() as u64)
+        .ok_or(CatflipError::MathOverflow)?;
+    vault.total_wins = vault.total_wins
+        .checked_add(wins)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    bet_round.is_settled = true;
+    bet_round.is_winner = payout > bet_round.stake_lamports;
+    
+    msg!("BatchSettled: player={}, flips={}, wins={}, staked={}, payout={}, round={}",
+        bet_round.player,
+        results.len(),
+        wins,
+        staked,
+        payout,
+        bet_round.key()
+    );
+    
+    emit!(BatchSettled {
+        player: bet_round.player,
+        results,
+        payout,
+        timestamp: Clock::get()?.unix_timestamp,
+        round_pubkey: bet_round.key(),
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct BatchSettled {
+    pub player: Pubkey,
+    /// Every flip played, in order; flips after a stop are left out.
+    pub results: Vec<FlipResult>,
+    /// The stake taken up front plus the sequence's net.
+    pub payout: u64,
+    pub timestamp: i64,
+    pub round_pubkey: Pubkey,
+}
+
 #[event]
 pub struct BetSettled {
     pub player: Pubkey,
@@ -88,4 +257,5 @@ pub struct BetSettled {
     pub is_winner: bool,
     pub payout: u64,
     pub timestamp: i64,
-}
\ No newline at end of file
+    pub round_pubkey: Pubkey,
+}
@@ -24,6 +24,8 @@ pub fn handler(
     max_exposure_bps: u16,
     house_edge_bps: u16,
 ) -> Result<()> {
+    Vault::validate_config(max_exposure_bps, house_edge_bps)?;
+    
     let vault = &mut ctx.accounts.vault;
     
     vault.authority = ctx.accounts.authority.key();
@@ -35,6 +37,11 @@ pub fn handler(
     vault.total_volume = 0;
     vault.total_bets = 0;
     vault.total_wins = 0;
+    vault.imported_principal = 0;
+    vault.vrf_public_key = [0; 32];
+    vault.player_liabilities = 0;
+    vault.reserved_payouts = 0;
+    vault.rake_bps = 0;
     
     msg!("Vault initialized with authority: {}", vault.authority);
     msg!("Min bet: {} lamports", min_bet_lamports);
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{Vault, PoolRound, PoolPosition, PoolPhase, Side};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct JoinPool<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"pool".as_ref(), &pool.round_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PoolRound>,
+    
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PoolPosition::SIZE,
+        seeds = [b"position", pool.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, PoolPosition>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Stakes `amount` on `side` of an open pool round, into the round's
+/// account. The player's seed goes into the round's VRF input.
+pub fn handler(
+    ctx: Context<JoinPool>,
+    side: Side,
+    amount: u64,
+    player_seed: [u8; 32],
+) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(
+        ctx.accounts.pool.phase(clock.slot) == PoolPhase::Open,
+        CatflipError::PoolNotOpen
+    );
+    require!(
+        amount >= ctx.accounts.vault.min_bet_lamports,
+        CatflipError::BetBelowMinimum
+    );
+    let position = &mut ctx.accounts.position;
+    require!(
+        position.amount == 0 || position.side == side,
+        CatflipError::PositionSideMismatch
+    );
+    
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.player.to_account_info(),
+                to: ctx.accounts.pool.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let pool = &mut ctx.accounts.pool;
+    if position.amount == 0 {
+        pool.open_positions = pool.open_positions
+            .checked_add(1)
+            .ok_or(CatflipError::MathOverflow)?;
+    }
+    let total = pool.total_mut(side);
+    *total = total
+        .checked_add(amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    pool.mix_seed(&player_seed);
+    
+    position.pool = pool.key();
+    position.player = ctx.accounts.player.key();
+    position.side = side;
+    position.amount = position.amount
+        .checked_add(amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    position.bump = ctx.bumps.position;
+    
+    msg!("PositionPlaced: player={}, side={:?}, amount={}, pool={}",
+        position.player,
+        side,
+        amount,
+        pool.key()
+    );
+    
+    emit!(PositionPlaced {
+        player: position.player,
+        side,
+        amount,
+        pool: pool.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct PositionPlaced {
+    pub player: Pubkey,
+    pub side: Side,
+    /// Added by this stake; the position may hold more from earlier ones.
+    pub amount: u64,
+    pub pool: Pubkey,
+    pub timestamp: i64,
+}
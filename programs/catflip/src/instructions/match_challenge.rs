@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{Vault, Challenge};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct MatchChallenge<'info> {
+    #[account(mut)]
+    pub opponent: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"challenge", challenge.creator.as_ref(), &challenge.slot.to_le_bytes()],
+        bump = challenge.bump,
+        constraint = !challenge.is_matched() @ CatflipError::ChallengeAlreadyMatched,
+        constraint = challenge.creator != opponent.key() @ CatflipError::CannotMatchOwnChallenge
+    )]
+    pub challenge: Account<'info, Challenge>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Takes the other side of a challenge with an equal stake. The opponent's
+/// seed goes into the VRF input, so the creator's can't fix the outcome.
+pub fn handler(ctx: Context<MatchChallenge>, player_seed: [u8; 32]) -> Result<()> {
+    let stake = ctx.accounts.challenge.stake;
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.opponent.to_account_info(),
+                to: ctx.accounts.challenge.to_account_info(),
+            },
+        ),
+        stake,
+    )?;
+    
+    let clock = Clock::get()?;
+    let challenge = &mut ctx.accounts.challenge;
+    challenge.opponent = ctx.accounts.opponent.key();
+    challenge.opponent_seed = player_seed;
+    challenge.matched_slot = clock.slot;
+    
+    msg!("ChallengeMatched: creator={}, opponent={}, stake={}, challenge={}",
+        challenge.creator,
+        challenge.opponent,
+        stake,
+        challenge.key()
+    );
+    
+    emit!(ChallengeMatched {
+        creator: challenge.creator,
+        opponent: challenge.opponent,
+        stake,
+        challenge: challenge.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct ChallengeMatched {
+    pub creator: Pubkey,
+    pub opponent: Pubkey,
+    pub stake: u64,
+    pub challenge: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{Vault, LegacyImport};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct MigrateIn<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ CatflipError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: the legacy house's vault, only used as a seed. catflip-minimal
+    /// closes it on `MigrateOut`, so it may no longer exist.
+    pub legacy_house: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = LegacyImport::SIZE,
+        seeds = [b"import", legacy_house.key().as_ref()],
+        bump
+    )]
+    pub legacy_import: Account<'info, LegacyImport>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Imports a legacy house: `amount` lamports move from the authority into
+/// the vault and are recorded as imported principal, and the house's lifetime
+/// stats are added to the vault's. The funds get to the authority first, e.g.
+/// through catflip-minimal's `MigrateOut` or catflip-ultra's withdraw. Each
+/// legacy house is imported once: the `LegacyImport` keyed by its vault
+/// address records the import and refuses a second one.
+///
+/// `total_bets` and `total_volume` are trusted as given. By now the legacy
+/// vault is closed and its final stats only exist in the `MigrateOut` log,
+/// which no program can read; the `LegacyImport` keeps them so anyone can
+/// compare the two. `amount` needs no such trust, since it is transferred.
+pub fn handler(
+    ctx: Context<MigrateIn>,
+    amount: u64,
+    total_bets: u64,
+    total_volume: u64,
+) -> Result<()> {
+    // A default key would leave the record looking unused.
+    require_keys_neq!(
+        ctx.accounts.legacy_house.key(),
+        Pubkey::default(),
+        CatflipError::InvalidConfig
+    );
+    let legacy_import = &mut ctx.accounts.legacy_import;
+    require_keys_eq!(
+        legacy_import.legacy_house,
+        Pubkey::default(),
+        CatflipError::LegacyHouseAlreadyImported
+    );
+    legacy_import.legacy_house = ctx.accounts.legacy_house.key();
+    legacy_import.amount = amount;
+    legacy_import.total_bets = total_bets;
+    legacy_import.total_volume = total_volume;
+    legacy_import.slot = Clock::get()?.slot;
+    legacy_import.bump = ctx.bumps.legacy_import;
+    
+    let transfer_cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.authority.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+        },
+    );
+    
+    system_program::transfer(transfer_cpi_ctx, amount)?;
+    
+    let vault = &mut ctx.accounts.vault;
+    vault.imported_principal = vault
+        .imported_principal
+        .checked_add(amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    vault.total_bets = vault
+        .total_bets
+        .checked_add(total_bets)
+        .ok_or(CatflipError::MathOverflow)?;
+    vault.total_volume = vault
+        .total_volume
+        .checked_add(total_volume)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    msg!("Migrated {} lamports, {} bets, {} volume from {} into the vault", amount, total_bets, total_volume, ctx.accounts.legacy_house.key());
+    
+    Ok(())
+}
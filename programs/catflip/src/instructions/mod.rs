@@ -1,7 +1,71 @@
+// Every instruction module exposes its own `handler`; the program module calls
+// them by path, the glob re-exports are only needed for the Accounts structs.
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize;
 pub mod bet;
 pub mod fulfill_randomness;
+pub mod fund_vault;
+pub mod withdraw;
+pub mod set_pause;
+pub mod set_limits;
+pub mod set_edge;
+pub mod refund_timeout;
+pub mod migrate_in;
+pub mod set_vrf_key;
+pub mod set_oracles;
+pub mod quote_bet;
+pub mod vault_status;
+pub mod deposit;
+pub mod withdraw_balance;
+pub mod create_session;
+pub mod revoke_session;
+pub mod session_bet;
+pub mod place_batch;
+pub mod start_streak;
+pub mod continue_streak;
+pub mod cash_out;
+pub mod set_rake;
+pub mod open_challenge;
+pub mod match_challenge;
+pub mod settle_challenge;
+pub mod cancel_challenge;
+pub mod open_pool;
+pub mod join_pool;
+pub mod resolve_pool;
+pub mod claim_position;
+pub mod refund_position;
 
 pub use initialize::*;
 pub use bet::*;
-pub use fulfill_randomness::*;
\ No newline at end of file
+pub use fulfill_randomness::*;
+pub use fund_vault::*;
+pub use withdraw::*;
+pub use set_pause::*;
+pub use set_limits::*;
+pub use set_edge::*;
+pub use refund_timeout::*;
+pub use migrate_in::*;
+pub use set_vrf_key::*;
+pub use set_oracles::*;
+pub use quote_bet::*;
+pub use vault_status::*;
+pub use deposit::*;
+pub use withdraw_balance::*;
+pub use create_session::*;
+pub use revoke_session::*;
+pub use session_bet::*;
+pub use place_batch::*;
+pub use start_streak::*;
+pub use continue_streak::*;
+pub use cash_out::*;
+pub use set_rake::*;
+pub use open_challenge::*;
+pub use match_challenge::*;
+pub use settle_challenge::*;
+pub use cancel_challenge::*;
+pub use open_pool::*;
+pub use join_pool::*;
+pub use resolve_pool::*;
+pub use claim_position::*;
+pub use refund_position::*;
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{Vault, Challenge, Side};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct OpenChallenge<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = Challenge::SIZE,
+        seeds = [b"challenge", creator.key().as_ref(), &Clock::get()?.slot.to_le_bytes()],
+        bump
+    )]
+    pub challenge: Account<'info, Challenge>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens a challenge for anyone to match, escrowing the creator's stake in
+/// it. The vault's rake is fixed now, so the authority can't raise it on a
+/// pot already escrowed. The vault's minimum bet applies; its exposure limit
+/// doesn't, since the house never pays a challenge out.
+pub fn handler(
+    ctx: Context<OpenChallenge>,
+    stake: u64,
+    side: Side,
+    player_seed: [u8; 32],
+) -> Result<()> {
+    require!(
+        stake >= ctx.accounts.vault.min_bet_lamports,
+        CatflipError::BetBelowMinimum
+    );
+    
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: ctx.accounts.challenge.to_account_info(),
+            },
+        ),
+        stake,
+    )?;
+    
+    let clock = Clock::get()?;
+    let challenge = &mut ctx.accounts.challenge;
+    challenge.creator = ctx.accounts.creator.key();
+    challenge.opponent = Pubkey::default();
+    challenge.stake = stake;
+    challenge.creator_side = side;
+    challenge.creator_seed = player_seed;
+    challenge.opponent_seed = [0; 32];
+    challenge.slot = clock.slot;
+    challenge.matched_slot = 0;
+    challenge.rake_bps = ctx.accounts.vault.rake_bps;
+    challenge.bump = ctx.bumps.challenge;
+    
+    msg!("ChallengeOpened: creator={}, stake={}, side={:?}, challenge={}",
+        challenge.creator,
+        stake,
+        side,
+        challenge.key()
+    );
+    
+    emit!(ChallengeOpened {
+        creator: challenge.creator,
+        stake,
+        side,
+        challenge: challenge.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct ChallengeOpened {
+    pub creator: Pubkey,
+    pub stake: u64,
+    /// The creator's side; whoever matches takes the other.
+    pub side: Side,
+    pub challenge: Pubkey,
+    pub timestamp: i64,
+}
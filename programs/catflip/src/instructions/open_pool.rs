@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, PoolRound, Side};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct OpenPool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ CatflipError::Unauthorized,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = PoolRound::SIZE,
+        seeds = [b"pool".as_ref(), &round_id.to_le_bytes()],
+        bump
+    )]
+    pub pool: Account<'info, PoolRound>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens pool round `round_id` for positions over the next `betting_slots`
+/// slots, charging the vault's current rake as its fee (authority only). The
+/// authority pays the round's rent and gets it back once it closes.
+pub fn handler(ctx: Context<OpenPool>, round_id: u64, betting_slots: u64) -> Result<()> {
+    require!(betting_slots > 0, CatflipError::InvalidConfig);
+    
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    pool.round_id = round_id;
+    pool.lock_slot = clock.slot
+        .checked_add(betting_slots)
+        .ok_or(CatflipError::MathOverflow)?;
+    pool.fee_bps = ctx.accounts.vault.rake_bps;
+    pool.alive_total = 0;
+    pool.dead_total = 0;
+    pool.seed = [0; 32];
+    pool.is_resolved = false;
+    pool.winning_side = Side::Alive;
+    pool.fee = 0;
+    pool.bump = ctx.bumps.pool;
+    pool.open_positions = 0;
+    pool.counts_positions = true;
+    
+    msg!("PoolOpened: round_id={}, lock_slot={}, fee_bps={}, pool={}",
+        round_id,
+        pool.lock_slot,
+        pool.fee_bps,
+        pool.key()
+    );
+    
+    emit!(PoolOpened {
+        round_id,
+        lock_slot: pool.lock_slot,
+        fee_bps: pool.fee_bps,
+        pool: pool.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct PoolOpened {
+    pub round_id: u64,
+    pub lock_slot: u64,
+    pub fee_bps: u16,
+    pub pool: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{Vault, BetRound, BetBatch, BatchPlan};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct PlaceBatch<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = player,
+        space = BetRound::SIZE,
+        seeds = [b"bet", player.key().as_ref(), &Clock::get()?.slot.to_le_bytes()],
+        bump
+    )]
+    pub bet_round: Account<'info, BetRound>,
+    
+    #[account(
+        init,
+        payer = player,
+        space = BetBatch::SIZE,
+        seeds = [b"batch", bet_round.key().as_ref()],
+        bump
+    )]
+    pub bet_batch: Account<'info, BetBatch>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Places every flip of `plan` as one round. The player pays the most the
+/// plan can lose up front, and the vault reserves that plus the most the
+/// plan can win until the round settles or is refunded. Each flip is then
+/// checked against the vault's limits like a single bet, and the plan's
+/// worst case for the house like a single bet's stake. Every flip pays at
+/// the house edge of placement, whatever `set_edge` does before settlement.
+pub fn handler(ctx: Context<PlaceBatch>, plan: BatchPlan, player_seed: [u8; 32]) -> Result<()> {
+    plan.validate()?;
+    let vault_balance = ctx
+        .accounts.vault
+        .bankroll(ctx.accounts.vault.to_account_info().lamports());
+    
+    let vault = &mut ctx.accounts.vault;
+    let bounds = plan.bounds(vault.house_edge_bps)?;
+    vault.quote(vault_balance, plan.base_stake)?;
+    vault.quote(vault_balance, bounds.max_stake)?;
+    let max_bet = vault
+        .max_bet(vault_balance)
+        .ok_or(CatflipError::MathOverflow)?;
+    require!(bounds.max_win <= max_bet, CatflipError::BetExceedsMaxExposure);
+    
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.player.to_account_info(),
+                to: vault.to_account_info(),
+            },
+        ),
+        bounds.max_loss,
+    )?;
+    let reserved = bounds
+        .max_loss
+        .checked_add(bounds.max_win)
+        .ok_or(CatflipError::MathOverflow)?;
+    vault.reserved_payouts = vault.reserved_payouts
+        .checked_add(reserved)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    let clock = Clock::get()?;
+    let bet_round = &mut ctx.accounts.bet_round;
+    bet_round.player = ctx.accounts.player.key();
+    bet_round.stake_lamports = bounds.max_loss;
+    bet_round.potential_payout = reserved;
+    bet_round.timestamp = clock.unix_timestamp;
+    bet_round.slot = clock.slot;
+    bet_round.player_seed = player_seed;
+    bet_round.is_settled = false;
+    bet_round.is_winner = false;
+    bet_round.bump = ctx.bumps.bet_round;
+    bet_round.from_balance = false;
+    bet_round.flips = plan.flips;
+    bet_round.streak = 0;
+    bet_round.reopened_slot = 0;
+    
+    let bet_batch = &mut ctx.accounts.bet_batch;
+    bet_batch.plan = plan;
+    bet_batch.house_edge_bps = vault.house_edge_bps;
+    bet_batch.bump = ctx.bumps.bet_batch;
+    
+    msg!("BatchPlaced: player={}, flips={}, stake={}, reserved={}, round={}",
+        bet_round.player,
+        bet_round.flips,
+        bet_round.stake_lamports,
+        reserved,
+        bet_round.key()
+    );
+    
+    emit!(BatchPlaced {
+        player: bet_round.player,
+        flips: bet_round.flips,
+        stake: bet_round.stake_lamports,
+        reserved,
+        round_pubkey: bet_round.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct BatchPlaced {
+    pub player: Pubkey,
+    pub flips: u8,
+    /// Taken from the player up front: the most the plan can lose.
+    pub stake: u64,
+    pub reserved: u64,
+    pub round_pubkey: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct QuoteBet<'info> {
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// What `bet` would do with a stake right now.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BetQuote {
+    pub stake: u64,
+    /// Paid out on a win, stake included.
+    pub payout: u64,
+    pub max_bet: u64,
+    pub house_edge_bps: u16,
+}
+
+/// Runs `bet`'s checks on `stake` against live state without moving funds,
+/// failing with the same error `bet` would. Meant to be simulated.
+pub fn handler(ctx: Context<QuoteBet>, stake: u64) -> Result<BetQuote> {
+    let vault = &ctx.accounts.vault;
+    let vault_balance = vault.bankroll(vault.to_account_info().lamports());
+    
+    let payout = vault.quote(vault_balance, stake)?;
+    let max_bet = vault
+        .max_bet(vault_balance)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    Ok(BetQuote {
+        stake,
+        payout,
+        max_bet,
+        house_edge_bps: vault.house_edge_bps,
+    })
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, PoolRound, PoolPosition, PoolPhase};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct RefundPosition<'info> {
+    // Refunds are permissionless once the round has expired, like
+    // `refund_timeout`.
+    #[account(mut)]
+    pub player: SystemAccount<'info>,
+    
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Paid the round's rent when opening it and gets it back when the
+    /// round closes.
+    #[account(mut, address = vault.authority @ CatflipError::Unauthorized)]
+    pub authority: SystemAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"pool".as_ref(), &pool.round_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PoolRound>,
+    
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"position", pool.key().as_ref(), player.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, PoolPosition>,
+}
+
+/// Returns a position's stake from a round that locked and was never
+/// resolved within `BetRound::TIMEOUT_SLOTS`. An expired round can't be
+/// resolved any more, so refunds and claims never mix. The last refund
+/// closes the round.
+pub fn handler(ctx: Context<RefundPosition>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let position = &ctx.accounts.position;
+    require!(
+        pool.phase(Clock::get()?.slot) == PoolPhase::Expired,
+        CatflipError::BetNotTimedOut
+    );
+    
+    **pool.to_account_info().lamports.borrow_mut() = pool
+        .to_account_info()
+        .lamports()
+        .checked_sub(position.amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    **ctx.accounts.player.lamports.borrow_mut() = ctx
+        .accounts.player
+        .lamports()
+        .checked_add(position.amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    msg!("PositionRefunded: player={}, amount={}, pool={}",
+        position.player,
+        position.amount,
+        pool.key()
+    );
+    
+    emit!(PositionRefunded {
+        player: position.player,
+        amount: position.amount,
+        pool: pool.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    if pool.release_position() {
+        pool.close(ctx.accounts.authority.to_account_info())?;
+    }
+    
+    Ok(())
+}
+
+#[event]
+pub struct PositionRefunded {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub pool: Pubkey,
+    pub timestamp: i64,
+}
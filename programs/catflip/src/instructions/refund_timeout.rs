@@ -1,13 +1,19 @@
 use anchor_lang::prelude::*;
-use crate::state::{Vault, BetRound};
+use crate::state::{Vault, BetRound, BetBatch, PlayerBalance};
 use crate::errors::CatflipError;
 
 #[derive(Accounts)]
 pub struct RefundTimeout<'info> {
+    // Refunds are permissionless once the round has timed out; the stake and
+    // the round's rent always go back to the player recorded on the round.
     #[account(mut)]
-    pub player: Signer<'info>,
+    pub player: SystemAccount<'info>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
     pub vault: Account<'info, Vault>,
     
     #[account(
@@ -20,6 +26,23 @@ pub struct RefundTimeout<'info> {
     )]
     pub bet_round: Account<'info, BetRound>,
     
+    /// Required when the round was staked from it; takes the stake back.
+    #[account(
+        mut,
+        seeds = [b"balance", player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    
+    /// Required when the round is a batch; closed with the round.
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"batch", bet_round.key().as_ref()],
+        bump = bet_batch.bump
+    )]
+    pub bet_batch: Option<Account<'info, BetBatch>>,
+    
     pub system_program: Program<'info, System>,
 }
 
@@ -28,31 +51,47 @@ pub fn handler(ctx: Context<RefundTimeout>) -> Result<()> {
     let current_slot = Clock::get()?.slot;
     
     require!(
-        current_slot > bet_round.slot + BetRound::TIMEOUT_SLOTS,
+        current_slot > bet_round.timeout_slot(),
         CatflipError::BetNotTimedOut
     );
     
-    **ctx.accounts.vault.to_account_info().lamports.borrow_mut() = ctx
-        .accounts.vault.to_account_info()
-        .lamports()
-        .checked_sub(bet_round.stake_lamports)
-        .ok_or(CatflipError::MathOverflow)?;
+    if bet_round.flips > 0 {
+        require!(ctx.accounts.bet_batch.is_some(), CatflipError::BatchRequired);
+        ctx.accounts.vault.reserved_payouts = ctx.accounts.vault.reserved_payouts
+            .checked_sub(bet_round.potential_payout)
+            .ok_or(CatflipError::MathOverflow)?;
+    }
     
-    **ctx.accounts.player.lamports.borrow_mut() = ctx
-        .accounts.player
-        .lamports()
-        .checked_add(bet_round.stake_lamports)
-        .ok_or(CatflipError::MathOverflow)?;
+    if bet_round.from_balance {
+        ctx.accounts.player_balance
+            .as_mut()
+            .ok_or(CatflipError::PlayerBalanceRequired)?
+            .credit(&mut ctx.accounts.vault, bet_round.stake_lamports)?;
+    } else {
+        **ctx.accounts.vault.to_account_info().lamports.borrow_mut() = ctx
+            .accounts.vault.to_account_info()
+            .lamports()
+            .checked_sub(bet_round.stake_lamports)
+            .ok_or(CatflipError::MathOverflow)?;
+        
+        **ctx.accounts.player.lamports.borrow_mut() = ctx
+            .accounts.player
+            .lamports()
+            .checked_add(bet_round.stake_lamports)
+            .ok_or(CatflipError::MathOverflow)?;
+    }
     
-    msg!("BetRefunded: player={}, stake={}", 
+    msg!("BetRefunded: player={}, stake={}, round={}", 
         ctx.accounts.player.key(),
-        bet_round.stake_lamports
+        bet_round.stake_lamports,
+        bet_round.key()
     );
     
     emit!(BetRefunded {
         player: ctx.accounts.player.key(),
         stake: bet_round.stake_lamports,
         timestamp: Clock::get()?.unix_timestamp,
+        round_pubkey: bet_round.key(),
     });
     
     Ok(())
@@ -63,4 +102,5 @@ pub struct BetRefunded {
     pub player: Pubkey,
     pub stake: u64,
     pub timestamp: i64,
+    pub round_pubkey: Pubkey,
 }
\ No newline at end of file
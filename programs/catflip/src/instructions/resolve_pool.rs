@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use crate::state::{Vault, PoolRound, PoolPhase, Side};
+use crate::errors::CatflipError;
+use crate::slot_hashes;
+use super::fulfill_randomness::{entropy, Randomness};
+
+#[derive(Accounts)]
+pub struct ResolvePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"pool".as_ref(), &pool.round_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, PoolRound>,
+    
+    /// Paid the round's rent when opening it and gets it back when the
+    /// round closes.
+    #[account(mut, address = vault.authority @ CatflipError::Unauthorized)]
+    pub authority: SystemAccount<'info>,
+    
+    /// CHECK: the instructions sysvar, read for oracle beacons.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    /// CHECK: the SlotHashes sysvar, read for the round's entropy slot.
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// Opens the box on a locked pool round once its entropy slot has been
+/// hashed. Like `fulfill_randomness`, anyone may submit it with randomness
+/// from the vault's source. A contested round pays its fee to the vault
+/// here; positions then claim their share. A round nobody joined closes
+/// right away.
+pub fn handler(ctx: Context<ResolvePool>, randomness: Randomness) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let pool = &mut ctx.accounts.pool;
+    require!(
+        pool.phase(Clock::get()?.slot) == PoolPhase::Locked,
+        CatflipError::PoolPhaseMismatch
+    );
+    
+    let slot_hash = slot_hashes::hash_for(&ctx.accounts.slot_hashes, pool.entropy_slot())?;
+    let alpha = pool.vrf_input(&pool.key(), &slot_hash);
+    let entropy = entropy(vault, &alpha, randomness, &ctx.accounts.instructions)?;
+    let side = Side::of_roll(u64::from_le_bytes(entropy[..8].try_into().unwrap()));
+    pool.is_resolved = true;
+    pool.winning_side = side;
+    
+    if pool.is_contested() {
+        pool.fee = pool.fee_on(pool.total(side.opposite()));
+        **pool.to_account_info().lamports.borrow_mut() = pool
+            .to_account_info()
+            .lamports()
+            .checked_sub(pool.fee)
+            .ok_or(CatflipError::MathOverflow)?;
+        **vault.to_account_info().lamports.borrow_mut() = vault
+            .to_account_info()
+            .lamports()
+            .checked_add(pool.fee)
+            .ok_or(CatflipError::MathOverflow)?;
+    }
+    
+    msg!("PoolResolved: round_id={}, side={:?}, alive={}, dead={}, fee={}, pool={}",
+        pool.round_id,
+        side,
+        pool.alive_total,
+        pool.dead_total,
+        pool.fee,
+        pool.key()
+    );
+    
+    emit!(PoolResolved {
+        round_id: pool.round_id,
+        winning_side: side,
+        alive_total: pool.alive_total,
+        dead_total: pool.dead_total,
+        fee: pool.fee,
+        pool: pool.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    if pool.counts_positions && pool.open_positions == 0 {
+        pool.close(ctx.accounts.authority.to_account_info())?;
+    }
+    
+    Ok(())
+}
+
+#[event]
+pub struct PoolResolved {
+    pub round_id: u64,
+    pub winning_side: Side,
+    pub alive_total: u64,
+    pub dead_total: u64,
+    /// Zero when one side had no stakes and every position is returned.
+    pub fee: u64,
+    pub pool: Pubkey,
+    pub timestamp: i64,
+}
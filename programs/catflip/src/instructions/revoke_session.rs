@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::Session;
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    // Closing returns the rent and whatever wallet stake the key didn't use.
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"session", player.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+}
+
+/// Ends the session, expired or not. Rounds the key already opened are
+/// unaffected.
+pub fn handler(ctx: Context<RevokeSession>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    msg!("SessionRevoked: player={}, session_key={}, staked={}",
+        session.player,
+        session.session_key,
+        session.staked
+    );
+    
+    Ok(())
+}
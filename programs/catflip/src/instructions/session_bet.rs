@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, BetRound, PlayerBalance, Session};
+use crate::errors::CatflipError;
+use super::bet::{open_round, quote_guarded, BetGuards};
+
+#[derive(Accounts)]
+pub struct SessionBet<'info> {
+    /// Signs in place of the player and pays the round's rent, which goes to
+    /// the player when the round is refunded.
+    #[account(mut)]
+    pub session_key: Signer<'info>,
+    
+    pub player: SystemAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"session", player.key().as_ref(), session_key.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = session_key,
+        space = BetRound::SIZE,
+        seeds = [b"bet", player.key().as_ref(), &Clock::get()?.slot.to_le_bytes()],
+        bump
+    )]
+    pub bet_round: Account<'info, BetRound>,
+    
+    /// Stakes from the player's balance instead of the session's wallet
+    /// funds when given.
+    #[account(
+        mut,
+        seeds = [b"balance", player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// `bet`, signed by a session key for its player. The round belongs to the
+/// player exactly as if they had signed, so it settles and refunds to their
+/// wallet or balance.
+pub fn handler(
+    ctx: Context<SessionBet>,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let potential_payout = quote_guarded(&ctx.accounts.vault, amount_lamports, &guards, clock.slot)?;
+    
+    let vault = &mut ctx.accounts.vault;
+    let session = &mut ctx.accounts.session;
+    match ctx.accounts.player_balance.as_mut() {
+        Some(player_balance) => {
+            session.spend(Session::BET_FROM_BALANCE, amount_lamports, clock.slot)?;
+            player_balance.debit(vault, amount_lamports)?;
+        }
+        None => {
+            // The wallet stake was moved into the session when it was
+            // created; it is program-owned, so it can be debited directly.
+            session.spend(Session::BET, amount_lamports, clock.slot)?;
+            let session_info = session.to_account_info();
+            **session_info.lamports.borrow_mut() = session_info
+                .lamports()
+                .checked_sub(amount_lamports)
+                .ok_or(CatflipError::MathOverflow)?;
+            let vault_info = vault.to_account_info();
+            **vault_info.lamports.borrow_mut() = vault_info
+                .lamports()
+                .checked_add(amount_lamports)
+                .ok_or(CatflipError::MathOverflow)?;
+        }
+    }
+    
+    msg!("SessionBet: player={}, session_key={}, staked={}, max_stake={}",
+        session.player,
+        session.session_key,
+        session.staked,
+        session.max_stake
+    );
+    
+    open_round(
+        vault,
+        &mut ctx.accounts.bet_round,
+        ctx.bumps.bet_round,
+        ctx.accounts.player.key(),
+        amount_lamports,
+        potential_payout,
+        player_seed,
+        ctx.accounts.player_balance.is_some(),
+    )
+}
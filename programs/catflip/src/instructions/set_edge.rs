@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct SetEdge<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ CatflipError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<SetEdge>, house_edge_bps: u16) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    Vault::validate_config(vault.max_exposure_bps, house_edge_bps)?;
+    
+    vault.house_edge_bps = house_edge_bps;
+    
+    msg!("House edge: {}%", house_edge_bps as f64 / 100.0);
+    
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ CatflipError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<SetLimits>, min_bet_lamports: u64, max_exposure_bps: u16) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    Vault::validate_config(max_exposure_bps, vault.house_edge_bps)?;
+    
+    vault.min_bet_lamports = min_bet_lamports;
+    vault.max_exposure_bps = max_exposure_bps;
+    
+    msg!("Min bet: {} lamports", min_bet_lamports);
+    msg!("Max exposure: {}%", max_exposure_bps as f64 / 100.0);
+    
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct SetOracles<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: grown and checked by the handler, as in `set_vrf_key`.
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        owner = crate::ID
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Registers the oracles whose beacons settle bets and how many of them each
+/// round needs: the first `threshold` sign, the rest are standbys. An empty
+/// set with a zero threshold goes back to the VRF key.
+pub fn handler(ctx: Context<SetOracles>, oracles: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    let unique = oracles
+        .iter()
+        .enumerate()
+        .all(|(index, oracle)| !oracles[..index].contains(oracle));
+    require!(
+        oracles.len() <= Vault::MAX_ORACLES
+            && threshold as usize <= oracles.len()
+            && (threshold > 0 || oracles.is_empty())
+            && unique,
+        CatflipError::InvalidConfig
+    );
+    
+    let vault_info = ctx.accounts.vault.to_account_info();
+    Vault::grow(
+        &vault_info,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    
+    let mut vault = Vault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        vault.authority,
+        ctx.accounts.authority.key(),
+        CatflipError::Unauthorized
+    );
+    
+    vault.oracles = [Pubkey::default(); Vault::MAX_ORACLES];
+    vault.oracles[..oracles.len()].copy_from_slice(&oracles);
+    vault.oracle_count = oracles.len() as u8;
+    vault.oracle_threshold = threshold;
+    vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+    
+    msg!("Oracles: {} of {}", threshold, oracles.len());
+    
+    Ok(())
+}
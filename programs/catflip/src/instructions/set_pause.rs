@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ CatflipError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<SetPause>, is_paused: bool) -> Result<()> {
+    ctx.accounts.vault.is_paused = is_paused;
+    
+    msg!("Vault paused: {}", is_paused);
+    
+    Ok(())
+}
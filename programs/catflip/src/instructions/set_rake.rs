@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct SetRake<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ CatflipError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<SetRake>, rake_bps: u16) -> Result<()> {
+    require!(rake_bps < 10000, CatflipError::InvalidConfig);
+    
+    let vault = &mut ctx.accounts.vault;
+    vault.rake_bps = rake_bps;
+    
+    msg!("Challenge rake: {}%", rake_bps as f64 / 100.0);
+    
+    Ok(())
+}
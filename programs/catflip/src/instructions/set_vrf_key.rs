@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+use crate::vrf;
+
+#[derive(Accounts)]
+pub struct SetVrfKey<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: vaults created by an earlier version of the program are too
+    /// short to deserialize, so the handler grows them first and checks the
+    /// authority itself.
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+        owner = crate::ID
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetVrfKey>, vrf_public_key: [u8; 32]) -> Result<()> {
+    let vault_info = ctx.accounts.vault.to_account_info();
+    Vault::grow(
+        &vault_info,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    
+    let mut vault = Vault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        vault.authority,
+        ctx.accounts.authority.key(),
+        CatflipError::Unauthorized
+    );
+    require!(
+        vrf::is_valid_public_key(&vrf_public_key),
+        CatflipError::InvalidVrfAccount
+    );
+    
+    vault.vrf_public_key = vrf_public_key;
+    vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+    
+    msg!("VRF key: {}", Pubkey::from(vrf_public_key));
+    
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use crate::state::{Vault, Challenge, Side};
+use crate::errors::CatflipError;
+use crate::slot_hashes;
+use super::fulfill_randomness::{entropy, Randomness};
+
+#[derive(Accounts)]
+pub struct SettleChallenge<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"challenge", creator.key().as_ref(), &challenge.slot.to_le_bytes()],
+        bump = challenge.bump,
+        constraint = challenge.is_matched() @ CatflipError::ChallengeNotMatched,
+        constraint = challenge.opponent == opponent.key() @ CatflipError::Unauthorized
+    )]
+    pub challenge: Account<'info, Challenge>,
+    
+    /// Takes the account's rent back, and the pot if they win.
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    
+    /// CHECK: the challenge's opponent, checked against it; only credited.
+    /// Unmatched, it is the default key, which isn't a system account.
+    #[account(mut)]
+    pub opponent: UncheckedAccount<'info>,
+    
+    /// CHECK: the instructions sysvar, read for oracle beacons.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    /// CHECK: the SlotHashes sysvar, read for the challenge's entropy slot.
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// Opens the box on a matched challenge. Like `fulfill_randomness`, anyone
+/// may submit it with randomness from the vault's source until the challenge
+/// times out, once its entropy slot has been hashed; the winner gets both
+/// stakes less the rake the challenge opened with.
+pub fn handler(ctx: Context<SettleChallenge>, randomness: Randomness) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let challenge = &ctx.accounts.challenge;
+    require!(
+        Clock::get()?.slot <= challenge.timeout_slot(),
+        CatflipError::RoundTimedOut
+    );
+    
+    let slot_hash = slot_hashes::hash_for(&ctx.accounts.slot_hashes, challenge.entropy_slot())?;
+    let alpha = challenge.vrf_input(&challenge.key(), &slot_hash);
+    let entropy = entropy(vault, &alpha, randomness, &ctx.accounts.instructions)?;
+    let side = Side::of_roll(u64::from_le_bytes(entropy[..8].try_into().unwrap()));
+    let winner = if side == challenge.creator_side {
+        ctx.accounts.creator.to_account_info()
+    } else {
+        ctx.accounts.opponent.to_account_info()
+    };
+    
+    let pot = challenge.stake
+        .checked_mul(2)
+        .ok_or(CatflipError::MathOverflow)?;
+    let rake = challenge.rake(pot).ok_or(CatflipError::MathOverflow)?;
+    let payout = pot - rake;
+    
+    **challenge.to_account_info().lamports.borrow_mut() = challenge
+        .to_account_info()
+        .lamports()
+        .checked_sub(pot)
+        .ok_or(CatflipError::MathOverflow)?;
+    **winner.lamports.borrow_mut() = winner
+        .lamports()
+        .checked_add(payout)
+        .ok_or(CatflipError::MathOverflow)?;
+    **vault.to_account_info().lamports.borrow_mut() = vault
+        .to_account_info()
+        .lamports()
+        .checked_add(rake)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    msg!("ChallengeSettled: winner={}, side={:?}, payout={}, rake={}, challenge={}",
+        winner.key(),
+        side,
+        payout,
+        rake,
+        challenge.key()
+    );
+    
+    emit!(ChallengeSettled {
+        creator: challenge.creator,
+        opponent: challenge.opponent,
+        winner: winner.key(),
+        side,
+        payout,
+        rake,
+        challenge: challenge.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[event]
+pub struct ChallengeSettled {
+    pub creator: Pubkey,
+    pub opponent: Pubkey,
+    pub winner: Pubkey,
+    /// The side the box opened on.
+    pub side: Side,
+    /// Both stakes less the rake.
+    pub payout: u64,
+    pub rake: u64,
+    pub challenge: Pubkey,
+    pub timestamp: i64,
+}
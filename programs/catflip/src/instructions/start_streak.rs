@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{Vault, BetRound};
+use crate::errors::CatflipError;
+use super::bet::{open_round, quote_guarded, BetGuards};
+
+#[derive(Accounts)]
+pub struct StartStreak<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = !vault.is_paused @ CatflipError::GamePaused
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = player,
+        space = BetRound::SIZE,
+        seeds = [b"bet", player.key().as_ref(), &Clock::get()?.slot.to_le_bytes()],
+        bump
+    )]
+    pub bet_round: Account<'info, BetRound>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Places the first step of a double-or-nothing streak from the player's
+/// wallet. It settles like a bet, except that a win stays in the box: the
+/// player then either cashes it out or stakes all of it on the next step.
+pub fn handler(
+    ctx: Context<StartStreak>,
+    amount_lamports: u64,
+    player_seed: [u8; 32],
+    guards: BetGuards,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let potential_payout = quote_guarded(&ctx.accounts.vault, amount_lamports, &guards, clock.slot)?;
+    
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.player.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount_lamports,
+    )?;
+    
+    open_round(
+        &mut ctx.accounts.vault,
+        &mut ctx.accounts.bet_round,
+        ctx.bumps.bet_round,
+        ctx.accounts.player.key(),
+        amount_lamports,
+        potential_payout,
+        player_seed,
+        false,
+    )?;
+    ctx.accounts.bet_round.streak = 1;
+    
+    Ok(())
+}
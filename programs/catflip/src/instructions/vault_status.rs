@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct VaultStatus<'info> {
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// The vault's limits and liquidity as `bet` and `withdraw` see them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultView {
+    pub is_paused: bool,
+    pub balance: u64,
+    /// Owed to players' balances; part of `balance` but not bankroll.
+    pub player_liabilities: u64,
+    /// Set aside for open batches and won streak steps; part of `balance`
+    /// but not bankroll.
+    pub reserved_payouts: u64,
+    /// Balance above the rent-exempt minimum, player liabilities and
+    /// reserves.
+    pub free_liquidity: u64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub max_exposure_bps: u16,
+    pub house_edge_bps: u16,
+    /// Payout per unit staked, in basis points: 20000 less twice the edge.
+    pub payout_multiplier_bps: u64,
+    pub total_volume: u64,
+    pub total_bets: u64,
+    pub total_wins: u64,
+}
+
+pub fn handler(ctx: Context<VaultStatus>) -> Result<VaultView> {
+    let vault = &ctx.accounts.vault;
+    let balance = vault.to_account_info().lamports();
+    
+    Ok(VaultView {
+        is_paused: vault.is_paused,
+        balance,
+        player_liabilities: vault.player_liabilities,
+        reserved_payouts: vault.reserved_payouts,
+        free_liquidity: vault.free_liquidity(balance)?,
+        min_bet: vault.min_bet_lamports,
+        max_bet: vault
+            .max_bet(vault.bankroll(balance))
+            .ok_or(CatflipError::MathOverflow)?,
+        max_exposure_bps: vault.max_exposure_bps,
+        house_edge_bps: vault.house_edge_bps,
+        payout_multiplier_bps: vault
+            .potential_payout(10000)
+            .ok_or(CatflipError::MathOverflow)?,
+        total_volume: vault.total_volume,
+        total_bets: vault.total_bets,
+        total_wins: vault.total_wins,
+    })
+}
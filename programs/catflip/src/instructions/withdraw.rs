@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ CatflipError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let available = ctx.accounts.vault.free_liquidity(vault_info.lamports())?;
+    
+    require!(amount <= available, CatflipError::InsufficientVaultBalance);
+    
+    **vault_info.lamports.borrow_mut() = vault_info
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    **ctx.accounts.authority.lamports.borrow_mut() = ctx
+        .accounts.authority
+        .lamports()
+        .checked_add(amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    msg!("Vault withdrawal: {} lamports", amount);
+    msg!("New vault balance: {} lamports", vault_info.lamports());
+    
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::{Vault, PlayerBalance};
+use crate::errors::CatflipError;
+
+#[derive(Accounts)]
+pub struct WithdrawBalance<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    // Not gated on `is_paused`: players can always take their funds out.
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"balance", player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Account<'info, PlayerBalance>,
+}
+
+pub fn handler(ctx: Context<WithdrawBalance>, amount: u64) -> Result<()> {
+    let player_balance = &mut ctx.accounts.player_balance;
+    player_balance.debit(&mut ctx.accounts.vault, amount)?;
+    
+    let vault_info = ctx.accounts.vault.to_account_info();
+    **vault_info.lamports.borrow_mut() = vault_info
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    **ctx.accounts.player.lamports.borrow_mut() = ctx
+        .accounts.player
+        .lamports()
+        .checked_add(amount)
+        .ok_or(CatflipError::MathOverflow)?;
+    
+    msg!("BalanceWithdrawal: player={}, amount={}, balance={}",
+        ctx.accounts.player.key(),
+        amount,
+        player_balance.lamports
+    );
+    
+    Ok(())
+}
@@ -1,10 +1,14 @@
 use anchor_lang::prelude::*;
 
+pub mod beacons;
 pub mod errors;
 pub mod instructions;
+pub mod slot_hashes;
 pub mod state;
+pub mod vrf;
 
 use instructions::*;
+use state::{BatchPlan, Side};
 
 declare_id!("8bG8NieUJjFAi3vSKd6CdXQmfwVKqcZhe7CaGpo87gGh");
 
@@ -21,12 +25,179 @@ pub mod catflip {
         instructions::initialize::handler(ctx, min_bet_lamports, max_exposure_bps, house_edge_bps)
     }
 
-    pub fn bet(ctx: Context<Bet>, amount_lamports: u64) -> Result<()> {
-        instructions::bet::handler(ctx, amount_lamports)
+    pub fn bet(
+        ctx: Context<Bet>,
+        amount_lamports: u64,
+        player_seed: [u8; 32],
+        guards: BetGuards,
+    ) -> Result<()> {
+        instructions::bet::handler(ctx, amount_lamports, player_seed, guards)
+    }
+
+    pub fn fulfill_randomness(ctx: Context<FulfillRandomness>, randomness: Randomness) -> Result<()> {
+        instructions::fulfill_randomness::handler(ctx, randomness)
+    }
+
+    pub fn fund_vault(ctx: Context<FundVault>, amount: u64) -> Result<()> {
+        instructions::fund_vault::handler(ctx, amount)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        instructions::withdraw::handler(ctx, amount)
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, is_paused: bool) -> Result<()> {
+        instructions::set_pause::handler(ctx, is_paused)
+    }
+
+    pub fn set_limits(
+        ctx: Context<SetLimits>,
+        min_bet_lamports: u64,
+        max_exposure_bps: u16,
+    ) -> Result<()> {
+        instructions::set_limits::handler(ctx, min_bet_lamports, max_exposure_bps)
+    }
+
+    pub fn set_edge(ctx: Context<SetEdge>, house_edge_bps: u16) -> Result<()> {
+        instructions::set_edge::handler(ctx, house_edge_bps)
+    }
+
+    pub fn refund_timeout(ctx: Context<RefundTimeout>) -> Result<()> {
+        instructions::refund_timeout::handler(ctx)
+    }
+
+    pub fn migrate_in(
+        ctx: Context<MigrateIn>,
+        amount: u64,
+        total_bets: u64,
+        total_volume: u64,
+    ) -> Result<()> {
+        instructions::migrate_in::handler(ctx, amount, total_bets, total_volume)
+    }
+
+    pub fn set_vrf_key(ctx: Context<SetVrfKey>, vrf_public_key: [u8; 32]) -> Result<()> {
+        instructions::set_vrf_key::handler(ctx, vrf_public_key)
+    }
+
+    pub fn set_oracles(ctx: Context<SetOracles>, oracles: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        instructions::set_oracles::handler(ctx, oracles, threshold)
+    }
+
+    pub fn quote_bet(ctx: Context<QuoteBet>, stake: u64) -> Result<BetQuote> {
+        instructions::quote_bet::handler(ctx, stake)
+    }
+
+    pub fn vault_status(ctx: Context<VaultStatus>) -> Result<VaultView> {
+        instructions::vault_status::handler(ctx)
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        instructions::deposit::handler(ctx, amount)
+    }
+
+    pub fn withdraw_balance(ctx: Context<WithdrawBalance>, amount: u64) -> Result<()> {
+        instructions::withdraw_balance::handler(ctx, amount)
+    }
+
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_slot: u64,
+        max_stake: u64,
+        allowed: u8,
+    ) -> Result<()> {
+        instructions::create_session::handler(ctx, session_key, expires_slot, max_stake, allowed)
+    }
+
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        instructions::revoke_session::handler(ctx)
+    }
+
+    pub fn session_bet(
+        ctx: Context<SessionBet>,
+        amount_lamports: u64,
+        player_seed: [u8; 32],
+        guards: BetGuards,
+    ) -> Result<()> {
+        instructions::session_bet::handler(ctx, amount_lamports, player_seed, guards)
+    }
+
+    pub fn place_batch(
+        ctx: Context<PlaceBatch>,
+        plan: BatchPlan,
+        player_seed: [u8; 32],
+    ) -> Result<()> {
+        instructions::place_batch::handler(ctx, plan, player_seed)
+    }
+
+    pub fn start_streak(
+        ctx: Context<StartStreak>,
+        amount_lamports: u64,
+        player_seed: [u8; 32],
+        guards: BetGuards,
+    ) -> Result<()> {
+        instructions::start_streak::handler(ctx, amount_lamports, player_seed, guards)
+    }
+
+    pub fn continue_streak(
+        ctx: Context<ContinueStreak>,
+        player_seed: [u8; 32],
+        guards: BetGuards,
+    ) -> Result<()> {
+        instructions::continue_streak::handler(ctx, player_seed, guards)
+    }
+
+    pub fn cash_out(ctx: Context<CashOut>) -> Result<()> {
+        instructions::cash_out::handler(ctx)
+    }
+
+    pub fn set_rake(ctx: Context<SetRake>, rake_bps: u16) -> Result<()> {
+        instructions::set_rake::handler(ctx, rake_bps)
+    }
+
+    pub fn open_challenge(
+        ctx: Context<OpenChallenge>,
+        stake: u64,
+        side: Side,
+        player_seed: [u8; 32],
+    ) -> Result<()> {
+        instructions::open_challenge::handler(ctx, stake, side, player_seed)
+    }
+
+    pub fn match_challenge(ctx: Context<MatchChallenge>, player_seed: [u8; 32]) -> Result<()> {
+        instructions::match_challenge::handler(ctx, player_seed)
+    }
+
+    pub fn settle_challenge(ctx: Context<SettleChallenge>, randomness: Randomness) -> Result<()> {
+        instructions::settle_challenge::handler(ctx, randomness)
+    }
+
+    pub fn cancel_challenge(ctx: Context<CancelChallenge>) -> Result<()> {
+        instructions::cancel_challenge::handler(ctx)
+    }
+
+    pub fn open_pool(ctx: Context<OpenPool>, round_id: u64, betting_slots: u64) -> Result<()> {
+        instructions::open_pool::handler(ctx, round_id, betting_slots)
+    }
+
+    pub fn join_pool(
+        ctx: Context<JoinPool>,
+        side: Side,
+        amount: u64,
+        player_seed: [u8; 32],
+    ) -> Result<()> {
+        instructions::join_pool::handler(ctx, side, amount, player_seed)
+    }
+
+    pub fn resolve_pool(ctx: Context<ResolvePool>, randomness: Randomness) -> Result<()> {
+        instructions::resolve_pool::handler(ctx, randomness)
+    }
+
+    pub fn claim_position(ctx: Context<ClaimPosition>) -> Result<()> {
+        instructions::claim_position::handler(ctx)
     }
 
-    pub fn fulfill_randomness(ctx: Context<FulfillRandomness>) -> Result<()> {
-        instructions::fulfill_randomness::handler(ctx)
+    pub fn refund_position(ctx: Context<RefundPosition>) -> Result<()> {
+        instructions::refund_position::handler(ctx)
     }
-    
-}
\ No newline at end of file
+}
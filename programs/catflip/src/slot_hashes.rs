@@ -0,0 +1,79 @@
+//! Entropy from the `SlotHashes` sysvar for games whose last player input
+//! is known before the box opens.
+//!
+//! A challenge's VRF input holds both players' seeds, and a pool round's
+//! every position's. Whoever holds the VRF key could otherwise match a
+//! challenge, or join a pool last, with a seed it already knows wins; an
+//! oracle could do the same. Mixing in the hash of a slot that comes
+//! after the last player input takes that away: nobody knows it when they
+//! pick a seed, and once it is known no seed can change any more.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::slot_hashes::MAX_ENTRIES;
+
+use crate::errors::CatflipError;
+
+/// Slots between the last player input and the slot whose hash goes into
+/// the VRF input. Leaders produce slots in aligned runs of four, so that
+/// slot always falls in a later leader window than the one that took the
+/// input.
+pub const ENTROPY_DELAY_SLOTS: u64 = 4;
+
+/// Serialized size of one `(slot, hash)` entry.
+const ENTRY_LEN: usize = 8 + 32;
+
+/// Where a target slot stands in the `SlotHashes` sysvar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotHash {
+    /// Hash of the first slot at or after the target that wasn't skipped.
+    Found([u8; 32]),
+    /// No slot at or after the target has been hashed yet.
+    Pending,
+    /// The sysvar no longer reaches back to the target.
+    AgedOut,
+}
+
+/// The hash for `target` from the `SlotHashes` sysvar account, whose address
+/// the instruction's accounts check. Fails with `RandomnessNotReady` until
+/// the slot has been hashed; rounds time out long before their slot ages out
+/// of the sysvar, so that case fails as timed out.
+pub fn hash_for(slot_hashes: &AccountInfo, target: u64) -> Result<[u8; 32]> {
+    match find(&slot_hashes.try_borrow_data()?, target) {
+        SlotHash::Found(hash) => Ok(hash),
+        SlotHash::Pending => err!(CatflipError::RandomnessNotReady),
+        SlotHash::AgedOut => err!(CatflipError::RoundTimedOut),
+    }
+}
+
+/// Looks up `target` in raw `SlotHashes` data, e.g. as fetched by a client.
+///
+/// The sysvar is read in place rather than deserialized: it is 20 KB, and
+/// only the entries around `target` are needed. Entries are newest first
+/// and skipped slots have none, so the hash used is that of the oldest entry
+/// at or after `target`. The same `target` always picks the same entry.
+pub fn find(data: &[u8], target: u64) -> SlotHash {
+    let entries = data
+        .get(..8)
+        .map(|len| u64::from_le_bytes(len.try_into().unwrap()) as usize)
+        .and_then(|len| data.get(8..8 + len.checked_mul(ENTRY_LEN)?))
+        .unwrap_or_default();
+    let slot = |entry: &[u8]| u64::from_le_bytes(entry[..8].try_into().unwrap());
+    
+    let mut found = None;
+    for (index, entry) in entries.chunks_exact(ENTRY_LEN).enumerate() {
+        if slot(entry) < target {
+            break;
+        }
+        found = Some((index, entry));
+    }
+    
+    match found {
+        None => SlotHash::Pending,
+        // Every entry is at or after the target, and a full sysvar has
+        // evicted older ones that may have come first.
+        Some((index, entry)) if slot(entry) != target && index + 1 == MAX_ENTRIES => {
+            SlotHash::AgedOut
+        }
+        Some((_, entry)) => SlotHash::Found(entry[8..].try_into().unwrap()),
+    }
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::errors::CatflipError;
+use crate::state::{BetRound, Vault};
+
+/// How a batch sizes each flip's stake from the last outcome.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Progression {
+    /// Every flip stakes the base stake.
+    Flat,
+    /// Doubles the stake after a loss, back to the base after a win.
+    Martingale,
+    /// Doubles the stake after a win, back to the base after a loss.
+    AntiMartingale,
+}
+
+/// A sequence of flips placed as one bet.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchPlan {
+    pub base_stake: u64,
+    pub flips: u8,
+    pub progression: Progression,
+    /// Skips any flip that, lost, would put the sequence more than this
+    /// far behind, and everything after it.
+    pub stop_loss: Option<u64>,
+    /// Stops once the sequence is at least this far ahead.
+    pub take_profit: Option<u64>,
+}
+
+/// One flip of a settled batch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlipResult {
+    pub stake: u64,
+    pub is_winner: bool,
+}
+
+/// The extremes a plan can reach over every possible run of outcomes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchBounds {
+    /// What the player can lose at most; `place_batch` takes it up front.
+    pub max_loss: u64,
+    /// What the player can win at most, net of stakes.
+    pub max_win: u64,
+    /// The largest stake any flip can have.
+    pub max_stake: u64,
+}
+
+impl BatchPlan {
+    pub const SIZE: usize = 8 + // base_stake
+        1 + // flips
+        1 + // progression
+        9 + // stop_loss
+        9; // take_profit
+    
+    /// Flips one batch can hold; its outcomes fit `BetBatch::wins`.
+    pub const MAX_FLIPS: u8 = 32;
+    
+    pub fn validate(&self) -> Result<()> {
+        // A stop-loss below the base stake would skip the very first flip.
+        let first_flip_plays = match self.stop_loss {
+            Some(stop_loss) => stop_loss >= self.base_stake,
+            None => true,
+        };
+        require!(
+            (1..=Self::MAX_FLIPS).contains(&self.flips)
+                && first_flip_plays
+                && self.take_profit != Some(0),
+            CatflipError::InvalidBatch
+        );
+        Ok(())
+    }
+    
+    /// The stake after `level` doublings.
+    fn stake(&self, level: u8) -> Result<u64> {
+        1u64
+            .checked_shl(level as u32)
+            .and_then(|factor| self.base_stake.checked_mul(factor))
+            .ok_or_else(|| CatflipError::MathOverflow.into())
+    }
+    
+    fn next_level(&self, level: u8, won: bool) -> u8 {
+        match (self.progression, won) {
+            (Progression::Flat, _) => 0,
+            (Progression::Martingale, false) | (Progression::AntiMartingale, true) => level + 1,
+            (Progression::Martingale, true) | (Progression::AntiMartingale, false) => 0,
+        }
+    }
+    
+    /// Whether a flip of `stake` is played with the sequence at `net`.
+    fn plays(&self, net: i128, stake: u64) -> bool {
+        let below_take_profit = match self.take_profit {
+            Some(take_profit) => net < take_profit as i128,
+            None => true,
+        };
+        let within_stop_loss = match self.stop_loss {
+            Some(stop_loss) => net - stake as i128 >= -(stop_loss as i128),
+            None => true,
+        };
+        below_take_profit && within_stop_loss
+    }
+    
+    /// Plays the plan at a house edge of `house_edge_bps`, flip `i` winning
+    /// when `won(i)` does, and returns the flips played.
+    pub fn play(&self, house_edge_bps: u16, mut won: impl FnMut(u8) -> bool) -> Result<Vec<FlipResult>> {
+        let mut results = Vec::with_capacity(self.flips as usize);
+        let mut net = 0i128;
+        let mut level = 0;
+        for i in 0..self.flips {
+            let stake = self.stake(level)?;
+            if !self.plays(net, stake) {
+                break;
+            }
+            let is_winner = won(i);
+            net += Self::net_of(house_edge_bps, stake, is_winner)?;
+            results.push(FlipResult { stake, is_winner });
+            level = self.next_level(level, is_winner);
+        }
+        Ok(results)
+    }
+    
+    /// Bounds every run of [`BatchPlan::play`], tracking the range of nets
+    /// reachable at each stake level flip by flip. Ranges only ever widen,
+    /// so the bounds can be loose but never too tight.
+    pub fn bounds(&self, house_edge_bps: u16) -> Result<BatchBounds> {
+        let levels = self.flips as usize + 1;
+        let mut ranges: Vec<Option<(i128, i128)>> = vec![None; levels];
+        ranges[0] = Some((0, 0));
+        let (mut lowest, mut highest, mut max_stake) = (0i128, 0i128, 0u64);
+        for _ in 0..self.flips {
+            let mut next: Vec<Option<(i128, i128)>> = vec![None; levels];
+            for (level, range) in ranges.iter().enumerate() {
+                let Some((low, high)) = *range else { continue };
+                let stake = self.stake(level as u8)?;
+                // Only the part of the range that goes on to play this flip.
+                let low = match self.stop_loss {
+                    Some(stop_loss) => low.max(stake as i128 - stop_loss as i128),
+                    None => low,
+                };
+                let high = match self.take_profit {
+                    Some(take_profit) => high.min(take_profit as i128 - 1),
+                    None => high,
+                };
+                if low > high {
+                    continue;
+                }
+                max_stake = max_stake.max(stake);
+                for won in [false, true] {
+                    let net = Self::net_of(house_edge_bps, stake, won)?;
+                    let (low, high) = (low + net, high + net);
+                    lowest = lowest.min(low);
+                    highest = highest.max(high);
+                    let entry = &mut next[self.next_level(level as u8, won) as usize];
+                    *entry = Some(match *entry {
+                        Some((l, h)) => (l.min(low), h.max(high)),
+                        None => (low, high),
+                    });
+                }
+            }
+            ranges = next;
+        }
+        let to_u64 = |value: i128| u64::try_from(value).map_err(|_| CatflipError::MathOverflow);
+        Ok(BatchBounds {
+            max_loss: to_u64(-lowest)?,
+            max_win: to_u64(highest)?,
+            max_stake,
+        })
+    }
+    
+    /// What a flip of `stake` adds to the player's net.
+    fn net_of(house_edge_bps: u16, stake: u64, won: bool) -> Result<i128> {
+        if !won {
+            return Ok(-(stake as i128));
+        }
+        let payout = Vault::payout_at_edge(house_edge_bps, stake)
+            .ok_or(CatflipError::MathOverflow)?;
+        Ok(payout as i128 - stake as i128)
+    }
+}
+
+/// The plan and, once settled, the outcomes of a batch. It sits next to the
+/// batch's `BetRound`, which holds the player's worst-case loss as its stake
+/// and everything the vault reserved for it as its potential payout, so
+/// settlement and refunds otherwise follow the single-bet path.
+#[account]
+pub struct BetBatch {
+    pub plan: BatchPlan,
+    pub flips_played: u8,
+    /// Bit `i` is set when flip `i` won.
+    pub wins: u32,
+    /// Paid to the player at settlement: the stake taken up front plus the
+    /// sequence's net.
+    pub payout: u64,
+    /// The vault's house edge when the batch was placed. Its bounds were
+    /// reserved at that edge, so it also settles at that edge.
+    pub house_edge_bps: u16,
+    pub bump: u8,
+}
+
+impl BetBatch {
+    pub const SIZE: usize = 8 + // discriminator
+        BatchPlan::SIZE + // plan
+        1 + // flips_played
+        4 + // wins
+        8 + // payout
+        2 + // house_edge_bps
+        1 + // bump
+        14; // padding
+    
+    /// Flip `index`'s roll, derived from the round's one random value.
+    pub fn roll(entropy: &[u8; 32], index: u8) -> u64 {
+        let hash = hashv(&[entropy, &[index]]);
+        u64::from_le_bytes(hash.to_bytes()[..8].try_into().unwrap())
+    }
+    
+    /// Plays the plan on `entropy` and records the outcome, returning every
+    /// flip. The caller pays `payout` out and releases the round's reserve.
+    pub fn settle(&mut self, bet_round: &BetRound, entropy: &[u8; 32]) -> Result<Vec<FlipResult>> {
+        let results = self
+            .plan
+            .play(self.house_edge_bps, |index| BetRound::is_winning_roll(Self::roll(entropy, index)))?;
+        let mut returned = bet_round.stake_lamports as i128;
+        for (index, flip) in results.iter().enumerate() {
+            returned += BatchPlan::net_of(self.house_edge_bps, flip.stake, flip.is_winner)?;
+            if flip.is_winner {
+                self.wins |= 1 << index;
+            }
+        }
+        self.flips_played = results.len() as u8;
+        self.payout = u64::try_from(returned).map_err(|_| CatflipError::MathOverflow)?;
+        Ok(results)
+    }
+}
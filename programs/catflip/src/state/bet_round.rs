@@ -7,10 +7,25 @@ pub struct BetRound {
     pub potential_payout: u64,
     pub timestamp: i64,
     pub slot: u64,
-    pub vrf_request_randomness: [u8; 32],
+    /// Chosen by the player; part of the VRF input so the house can't
+    /// precompute outcomes before the bet exists.
+    pub player_seed: [u8; 32],
     pub is_settled: bool,
     pub is_winner: bool,
     pub bump: u8,
+    /// Staked from the player's `PlayerBalance`, which takes the payout or
+    /// refund instead of the wallet.
+    pub from_balance: bool,
+    /// Flips in the round's batch, zero for a single bet. A batch's plan
+    /// and results are in its `BetBatch`.
+    pub flips: u8,
+    /// Steps a streak round has played, zero for other rounds. A won step's
+    /// payout stays in the vault, reserved, until the player cashes out or
+    /// stakes it on the next step.
+    pub streak: u8,
+    /// Slot the streak's latest step was placed in, zero until the first
+    /// `continue_streak`. The round keeps its address, so `slot` doesn't move.
+    pub reopened_slot: u64,
 }
 
 impl BetRound {
@@ -20,11 +35,47 @@ impl BetRound {
         8 + // potential_payout
         8 + // timestamp
         8 + // slot
-        32 + // vrf_request_randomness
+        32 + // player_seed
         1 + // is_settled
         1 + // is_winner
         1 + // bump
-        16; // padding
+        1 + // from_balance
+        1 + // flips
+        1 + // streak
+        8 + // reopened_slot
+        5; // padding
     
     pub const TIMEOUT_SLOTS: u64 = 150; // ~1 minute timeout
+    
+    /// Steps one streak can play, its opening bet included.
+    pub const MAX_STREAK: u8 = 10;
+    
+    /// Last slot the round's current step waits for randomness; after it the
+    /// step can only be refunded.
+    pub fn timeout_slot(&self) -> u64 {
+        self.slot.max(self.reopened_slot) + Self::TIMEOUT_SLOTS
+    }
+    
+    /// Whether `random_value` from the randomness source wins the flip.
+    pub fn is_winning_roll(random_value: u64) -> bool {
+        random_value & 1 == 0
+    }
+    
+    /// The VRF input (`alpha`) for the round at `address`: its key, the
+    /// player's seed and the streak step.
+    pub fn vrf_input(&self, address: &Pubkey) -> [u8; 65] {
+        Self::step_vrf_input(address, &self.player_seed, self.streak)
+    }
+    
+    /// The VRF input for step `streak` of the round at `address` with
+    /// `player_seed`. A streak keeps its address from step to step, so
+    /// without the step a seed that won once would win every step it was
+    /// played again.
+    pub fn step_vrf_input(address: &Pubkey, player_seed: &[u8; 32], streak: u8) -> [u8; 65] {
+        let mut alpha = [0u8; 65];
+        alpha[..32].copy_from_slice(address.as_ref());
+        alpha[32..64].copy_from_slice(player_seed);
+        alpha[64] = streak;
+        alpha
+    }
 }
\ No newline at end of file
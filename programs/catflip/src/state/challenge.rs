@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::slot_hashes::ENTROPY_DELAY_SLOTS;
+use crate::state::BetRound;
+
+/// What the box shows when it is opened.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Alive,
+    Dead,
+}
+
+impl Side {
+    /// The side `random_value` from the randomness source lands on.
+    pub fn of_roll(random_value: u64) -> Self {
+        if BetRound::is_winning_roll(random_value) {
+            Side::Alive
+        } else {
+            Side::Dead
+        }
+    }
+    
+    pub fn opposite(self) -> Self {
+        match self {
+            Side::Alive => Side::Dead,
+            Side::Dead => Side::Alive,
+        }
+    }
+}
+
+/// A player-vs-player flip. The creator's stake sits in the account until
+/// someone matches it; the matched pot then waits here for randomness, and
+/// the vault only takes its rake.
+#[account]
+pub struct Challenge {
+    pub creator: Pubkey,
+    /// `Pubkey::default()` until the challenge is matched.
+    pub opponent: Pubkey,
+    /// Each player's stake; the pot is twice this.
+    pub stake: u64,
+    pub creator_side: Side,
+    pub creator_seed: [u8; 32],
+    pub opponent_seed: [u8; 32],
+    /// Slot the challenge was opened in, part of its address.
+    pub slot: u64,
+    /// Slot the challenge was matched in, zero before.
+    pub matched_slot: u64,
+    /// The vault's rake when the challenge opened, taken from the pot.
+    pub rake_bps: u16,
+    pub bump: u8,
+}
+
+impl Challenge {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // creator
+        32 + // opponent
+        8 + // stake
+        1 + // creator_side
+        32 + // creator_seed
+        32 + // opponent_seed
+        8 + // slot
+        8 + // matched_slot
+        2 + // rake_bps
+        1 + // bump
+        14; // padding
+    
+    pub fn is_matched(&self) -> bool {
+        self.opponent != Pubkey::default()
+    }
+    
+    /// Last slot a matched challenge waits for randomness; after it either
+    /// player's stake can be returned.
+    pub fn timeout_slot(&self) -> u64 {
+        self.matched_slot + BetRound::TIMEOUT_SLOTS
+    }
+    
+    /// Slot whose hash goes into the VRF input: a few slots after the match,
+    /// so it isn't known when the opponent picks a seed.
+    pub fn entropy_slot(&self) -> u64 {
+        self.matched_slot + ENTROPY_DELAY_SLOTS
+    }
+    
+    /// The rake on a pot of `pot` lamports.
+    pub fn rake(&self, pot: u64) -> Option<u64> {
+        pot.checked_mul(self.rake_bps as u64)?.checked_div(10000)
+    }
+    
+    /// The VRF input for the challenge at `address`: its key followed by a
+    /// hash of both players' seeds and `slot_hash`, the hash for
+    /// `entropy_slot`. Neither player can pick the outcome alone, and a
+    /// matcher holding the VRF key can't grind its seed against one it
+    /// doesn't know yet.
+    pub fn vrf_input(&self, address: &Pubkey, slot_hash: &[u8; 32]) -> [u8; 64] {
+        let mut alpha = [0u8; 64];
+        alpha[..32].copy_from_slice(address.as_ref());
+        alpha[32..].copy_from_slice(
+            &hashv(&[&self.creator_seed, &self.opponent_seed, slot_hash]).to_bytes(),
+        );
+        alpha
+    }
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+/// Record of a legacy house brought in by `migrate_in`, keyed by the legacy
+/// house's vault address. Its existence is what stops the same house from
+/// being imported, and its stats counted, twice.
+#[account]
+pub struct LegacyImport {
+    pub legacy_house: Pubkey,
+    pub amount: u64,
+    pub total_bets: u64,
+    pub total_volume: u64,
+    pub slot: u64,
+    pub bump: u8,
+}
+
+impl LegacyImport {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // legacy_house
+        8 + // amount
+        8 + // total_bets
+        8 + // total_volume
+        8 + // slot
+        1 + // bump
+        16; // padding
+}
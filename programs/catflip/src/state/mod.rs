@@ -1,5 +1,17 @@
 pub mod vault;
 pub mod bet_round;
+pub mod player_balance;
+pub mod session;
+pub mod bet_batch;
+pub mod challenge;
+pub mod pool;
+pub mod legacy_import;
 
 pub use vault::*;
-pub use bet_round::*;
\ No newline at end of file
+pub use bet_round::*;
+pub use player_balance::*;
+pub use session::*;
+pub use bet_batch::*;
+pub use challenge::*;
+pub use pool::*;
+pub use legacy_import::*;
\ No newline at end of file
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::errors::CatflipError;
+use crate::state::Vault;
+
+/// Lamports the vault holds for one player. `deposit` and `withdraw_balance`
+/// move funds in and out; bets staked from the balance debit it and their
+/// payouts and refunds credit it, so only deposits and withdrawals touch the
+/// player's wallet.
+#[account]
+pub struct PlayerBalance {
+    pub player: Pubkey,
+    pub lamports: u64,
+    pub bump: u8,
+}
+
+impl PlayerBalance {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // player
+        8 + // lamports
+        1 + // bump
+        16; // padding
+    
+    /// Adds `lamports` to the balance and to what `vault` owes players.
+    pub fn credit(&mut self, vault: &mut Vault, lamports: u64) -> Result<()> {
+        self.lamports = self
+            .lamports
+            .checked_add(lamports)
+            .ok_or(CatflipError::MathOverflow)?;
+        vault.player_liabilities = vault
+            .player_liabilities
+            .checked_add(lamports)
+            .ok_or(CatflipError::MathOverflow)?;
+        Ok(())
+    }
+    
+    /// Takes `lamports` off the balance and off what `vault` owes players.
+    pub fn debit(&mut self, vault: &mut Vault, lamports: u64) -> Result<()> {
+        self.lamports = self
+            .lamports
+            .checked_sub(lamports)
+            .ok_or(CatflipError::InsufficientPlayerBalance)?;
+        vault.player_liabilities = vault
+            .player_liabilities
+            .checked_sub(lamports)
+            .ok_or(CatflipError::MathOverflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::slot_hashes::ENTROPY_DELAY_SLOTS;
+use crate::state::{BetRound, Side};
+
+/// Where a pool round is in its life, as of a given slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolPhase {
+    /// Taking positions until `lock_slot`.
+    Open,
+    /// Betting is closed and the box waits for randomness.
+    Locked,
+    Resolved,
+    /// Locked past its timeout without resolving; positions get their
+    /// stakes back.
+    Expired,
+}
+
+/// A parimutuel round: players stake on either side, and once betting has
+/// closed the winning side splits the losing side's pool, less a fee. All
+/// stakes sit in the account; the vault only takes the fee.
+#[account]
+pub struct PoolRound {
+    pub round_id: u64,
+    /// First slot that no longer takes positions.
+    pub lock_slot: u64,
+    /// The vault's rake when the round opened, taken from the losing pool.
+    pub fee_bps: u16,
+    pub alive_total: u64,
+    pub dead_total: u64,
+    /// Every position's player seed hashed in, in order; the VRF input.
+    pub seed: [u8; 32],
+    pub is_resolved: bool,
+    /// The side the box opened on, once resolved.
+    pub winning_side: Side,
+    /// Taken from the losing pool at resolution.
+    pub fee: u64,
+    pub bump: u8,
+    /// Positions not yet claimed or refunded. The round closes, returning
+    /// its rent to the vault authority, when the last one is.
+    pub open_positions: u32,
+    /// Set by `open_pool` since `open_positions` exists. Rounds opened
+    /// before read it from zeroed padding, so their count misses positions
+    /// and they are never closed.
+    pub counts_positions: bool,
+}
+
+impl PoolRound {
+    pub const SIZE: usize = 8 + // discriminator
+        8 + // round_id
+        8 + // lock_slot
+        2 + // fee_bps
+        8 + // alive_total
+        8 + // dead_total
+        32 + // seed
+        1 + // is_resolved
+        1 + // winning_side
+        8 + // fee
+        1 + // bump
+        4 + // open_positions
+        1 + // counts_positions
+        11; // padding
+    
+    pub fn phase(&self, slot: u64) -> PoolPhase {
+        if self.is_resolved {
+            PoolPhase::Resolved
+        } else if slot < self.lock_slot {
+            PoolPhase::Open
+        } else if slot <= self.timeout_slot() {
+            PoolPhase::Locked
+        } else {
+            PoolPhase::Expired
+        }
+    }
+    
+    /// Last slot the round can resolve in.
+    pub fn timeout_slot(&self) -> u64 {
+        self.lock_slot + BetRound::TIMEOUT_SLOTS
+    }
+    
+    /// Slot whose hash goes into the VRF input: a few slots after betting
+    /// closes, so it isn't known when the last position's seed goes in.
+    pub fn entropy_slot(&self) -> u64 {
+        self.lock_slot + ENTROPY_DELAY_SLOTS
+    }
+    
+    /// Counts off a claimed or refunded position. True when it was the last
+    /// one and the round can close.
+    pub fn release_position(&mut self) -> bool {
+        self.open_positions = self.open_positions.saturating_sub(1);
+        self.counts_positions && self.open_positions == 0
+    }
+    
+    pub fn total(&self, side: Side) -> u64 {
+        match side {
+            Side::Alive => self.alive_total,
+            Side::Dead => self.dead_total,
+        }
+    }
+    
+    pub fn total_mut(&mut self, side: Side) -> &mut u64 {
+        match side {
+            Side::Alive => &mut self.alive_total,
+            Side::Dead => &mut self.dead_total,
+        }
+    }
+    
+    /// Whether the resolved round had stakes on both sides. Without, there
+    /// is no one to win from and every position gets its stake back.
+    pub fn is_contested(&self) -> bool {
+        self.alive_total > 0 && self.dead_total > 0
+    }
+    
+    /// The fee on a losing pool of `losing_total`.
+    pub fn fee_on(&self, losing_total: u64) -> u64 {
+        (losing_total as u128 * self.fee_bps as u128 / 10000) as u64
+    }
+    
+    /// What a position of `amount` on `side` claims from the resolved round:
+    /// its stake plus its share of the losing pool after the fee, rounded
+    /// down. Losing positions claim nothing.
+    pub fn payout(&self, side: Side, amount: u64) -> u64 {
+        if !self.is_contested() {
+            return amount;
+        }
+        if side != self.winning_side {
+            return 0;
+        }
+        let winnings = self.total(side.opposite()) - self.fee;
+        let share = winnings as u128 * amount as u128 / self.total(side) as u128;
+        amount + share as u64
+    }
+    
+    /// Hashes a new position's seed into the round's VRF input.
+    pub fn mix_seed(&mut self, player_seed: &[u8; 32]) {
+        self.seed = hashv(&[&self.seed, player_seed]).to_bytes();
+    }
+    
+    /// The VRF input for the round at `address`: its key followed by a hash
+    /// of the seed every position has mixed into and `slot_hash`, the hash
+    /// for `entropy_slot`. Whoever holds the VRF key can't join last with a
+    /// seed it has ground against the others.
+    pub fn vrf_input(&self, address: &Pubkey, slot_hash: &[u8; 32]) -> [u8; 64] {
+        let mut alpha = [0u8; 64];
+        alpha[..32].copy_from_slice(address.as_ref());
+        alpha[32..].copy_from_slice(&hashv(&[&self.seed, slot_hash]).to_bytes());
+        alpha
+    }
+}
+
+/// A player's stake in a pool round. Staking again adds to it, on the same
+/// side only.
+#[account]
+pub struct PoolPosition {
+    pub pool: Pubkey,
+    pub player: Pubkey,
+    pub side: Side,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl PoolPosition {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // pool
+        32 + // player
+        1 + // side
+        8 + // amount
+        1 + // bump
+        16; // padding
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::errors::CatflipError;
+
+/// An ephemeral key a player has allowed to bet on their behalf, so a front
+/// end can place bets without a wallet prompt for each one. The player's
+/// wallet or balance still funds the bets and takes the payouts; the session
+/// only limits how long, how much and which way the key may bet.
+#[account]
+pub struct Session {
+    pub player: Pubkey,
+    pub session_key: Pubkey,
+    /// Last slot the key may bet in.
+    pub expires_slot: u64,
+    /// Total the key may stake over the session's lifetime.
+    pub max_stake: u64,
+    pub staked: u64,
+    /// [`Session::BET`] and/or [`Session::BET_FROM_BALANCE`].
+    pub allowed: u8,
+    pub bump: u8,
+}
+
+impl Session {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // player
+        32 + // session_key
+        8 + // expires_slot
+        8 + // max_stake
+        8 + // staked
+        1 + // allowed
+        1 + // bump
+        16; // padding
+    
+    /// Bets staked from the player's wallet. A wallet can't be debited
+    /// without its signature, so these sessions hold `max_stake` from the
+    /// wallet up front and return what's left when revoked.
+    pub const BET: u8 = 1 << 0;
+    /// Bets staked from the player's `PlayerBalance`.
+    pub const BET_FROM_BALANCE: u8 = 1 << 1;
+    pub const ALL: u8 = Self::BET | Self::BET_FROM_BALANCE;
+    
+    /// Counts a stake of `amount` against the session, failing if the key
+    /// may not place it in `slot`.
+    pub fn spend(&mut self, permission: u8, amount: u64, slot: u64) -> Result<()> {
+        require!(slot <= self.expires_slot, CatflipError::SessionExpired);
+        require!(self.allowed & permission != 0, CatflipError::SessionNotAllowed);
+        self.staked = self
+            .staked
+            .checked_add(amount)
+            .filter(|staked| *staked <= self.max_stake)
+            .ok_or(CatflipError::SessionStakeExceeded)?;
+        Ok(())
+    }
+}
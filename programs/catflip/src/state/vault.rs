@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::CatflipError;
 
 #[account]
 pub struct Vault {
@@ -11,6 +13,27 @@ pub struct Vault {
     pub total_volume: u64,
     pub total_bets: u64,
     pub total_wins: u64,
+    /// Lamports brought over from legacy houses by `migrate_in`.
+    pub imported_principal: u64,
+    /// ed25519 key whose ECVRF proofs settle bets; all zeroes until
+    /// `set_vrf_key` registers one.
+    pub vrf_public_key: [u8; 32],
+    /// Registered entries in `oracles`.
+    pub oracle_count: u8,
+    /// Beacons `fulfill_randomness` needs; zero settles with the VRF key
+    /// instead.
+    pub oracle_threshold: u8,
+    pub oracles: [Pubkey; Vault::MAX_ORACLES],
+    /// Lamports in the vault that belong to players' `PlayerBalance`s. They
+    /// are owed, not bankroll: bets and withdrawals can't use them.
+    pub player_liabilities: u64,
+    /// Lamports set aside for open batches, the most each can return to its
+    /// player, and for won streak steps waiting to be cashed out. Like
+    /// player liabilities, they aren't bankroll.
+    pub reserved_payouts: u64,
+    /// Cut of a challenge's pot or a pool round's losing side the vault
+    /// takes, in basis points. Each takes the rake it opened with.
+    pub rake_bps: u16,
 }
 
 impl Vault {
@@ -24,5 +47,120 @@ impl Vault {
         8 + // total_volume
         8 + // total_bets
         8 + // total_wins
-        32; // padding
-}
\ No newline at end of file
+        8 + // imported_principal
+        32 + // vrf_public_key
+        1 + // oracle_count
+        1 + // oracle_threshold
+        32 * Vault::MAX_ORACLES + // oracles
+        8 + // player_liabilities
+        8 + // reserved_payouts
+        2 + // rake_bps
+        6; // padding
+    
+    /// Beacons from this many oracles still fit one transaction next to
+    /// `fulfill_randomness`.
+    pub const MAX_ORACLES: usize = 8;
+    
+    /// Grows a vault created by an earlier version of the program to `SIZE`,
+    /// zeroing the new fields. `payer` covers the extra rent so the bankroll
+    /// stays whole.
+    pub fn grow<'info>(
+        vault: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system: &AccountInfo<'info>,
+    ) -> Result<()> {
+        if vault.data_len() >= Self::SIZE {
+            return Ok(());
+        }
+        let rent = Rent::get()?;
+        let top_up = rent
+            .minimum_balance(Self::SIZE)
+            .saturating_sub(rent.minimum_balance(vault.data_len()));
+        system_program::transfer(
+            CpiContext::new(
+                system.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: vault.clone(),
+                },
+            ),
+            top_up,
+        )?;
+        vault.realloc(Self::SIZE, true)?;
+        Ok(())
+    }
+    
+    /// The registered oracle keys.
+    pub fn oracles(&self) -> &[Pubkey] {
+        &self.oracles[..self.oracle_count as usize]
+    }
+    
+    pub fn validate_config(max_exposure_bps: u16, house_edge_bps: u16) -> Result<()> {
+        require!(
+            max_exposure_bps <= 10000 && house_edge_bps < 10000,
+            CatflipError::InvalidConfig
+        );
+        Ok(())
+    }
+    
+    /// Largest stake `bet` accepts while the vault holds `vault_balance`.
+    pub fn max_bet(&self, vault_balance: u64) -> Option<u64> {
+        vault_balance
+            .checked_mul(self.max_exposure_bps as u64)?
+            .checked_div(10000)
+    }
+    
+    /// What a winning `stake` pays out, stake included: 2x less the house edge.
+    pub fn potential_payout(&self, stake: u64) -> Option<u64> {
+        Self::payout_at_edge(self.house_edge_bps, stake)
+    }
+    
+    /// What a winning `stake` pays out at a house edge of `house_edge_bps`,
+    /// e.g. the one a batch was placed at.
+    pub fn payout_at_edge(house_edge_bps: u16, stake: u64) -> Option<u64> {
+        let house_edge_multiplier = 10000u64.checked_sub(house_edge_bps as u64)?;
+        stake
+            .checked_mul(2)?
+            .checked_mul(house_edge_multiplier)?
+            .checked_div(10000)
+    }
+    
+    /// The part of `vault_balance` that is the house's own, net of what it
+    /// owes players and has reserved for batches and streaks.
+    pub fn bankroll(&self, vault_balance: u64) -> u64 {
+        vault_balance
+            .saturating_sub(self.player_liabilities)
+            .saturating_sub(self.reserved_payouts)
+    }
+    
+    /// Checks `stake` against the limits `bet` enforces while the vault's
+    /// bankroll is `vault_balance` and returns its potential payout.
+    pub fn quote(&self, vault_balance: u64, stake: u64) -> Result<u64> {
+        require!(
+            stake >= self.min_bet_lamports,
+            CatflipError::BetBelowMinimum
+        );
+        let max_bet = self
+            .max_bet(vault_balance)
+            .ok_or(CatflipError::MathOverflow)?;
+        require!(
+            stake <= max_bet,
+            CatflipError::BetExceedsMaxExposure
+        );
+        let potential_payout = self
+            .potential_payout(stake)
+            .ok_or(CatflipError::MathOverflow)?;
+        require!(
+            vault_balance >= potential_payout,
+            CatflipError::InsufficientVaultBalance
+        );
+        Ok(potential_payout)
+    }
+    
+    /// Lamports above the vault's rent-exempt minimum, its player
+    /// liabilities and its reserves, all of which `withdraw` can take.
+    pub fn free_liquidity(&self, vault_balance: u64) -> Result<u64> {
+        let rent_exempt = Rent::get()?.minimum_balance(Self::SIZE);
+        Ok(self.bankroll(vault_balance).saturating_sub(rent_exempt))
+    }
+}
@@ -0,0 +1,148 @@
+//! ECVRF-EDWARDS25519-SHA512-TAI verification (RFC 9381, suite 0x03).
+//!
+//! The house proves each round's output with its VRF key; anyone can check
+//! the proof against the key registered in the `Vault`, so the house can't
+//! pick an output. It can still withhold one: it learns the outcome before
+//! anyone else and may simply not submit it. A withheld round can't settle
+//! past its timeout and only refunds the stake, so withholding can cancel
+//! rounds the house would lose but never turn them into wins; a house that
+//! lets many rounds time out is doing exactly that. Curve arithmetic goes
+//! through the curve25519 syscalls, which fall back to curve25519-dalek
+//! off-chain, so clients reuse these functions to prove.
+
+use sha2::{Digest, Sha512};
+use solana_zk_token_sdk::curve25519::{
+    edwards::{multiply_edwards, multiscalar_multiply_edwards, validate_edwards, PodEdwardsPoint},
+    scalar::PodScalar,
+};
+
+/// `pi_string`: Gamma, the 16-byte challenge and the scalar s.
+pub const PROOF_LEN: usize = 80;
+/// `beta_string`, the VRF output.
+pub const OUTPUT_LEN: usize = 64;
+
+const SUITE: u8 = 0x03;
+const COFACTOR: PodScalar = scalar(8);
+
+const BASEPOINT: PodEdwardsPoint = PodEdwardsPoint([
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+]);
+
+const IDENTITY: PodEdwardsPoint = PodEdwardsPoint([
+    1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+]);
+
+/// Order of the prime subgroup, little-endian.
+const L: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+];
+
+const fn scalar(value: u8) -> PodScalar {
+    let mut bytes = [0u8; 32];
+    bytes[0] = value;
+    PodScalar(bytes)
+}
+
+/// Whether `public_key` is a curve point outside the small-order subgroup,
+/// the key validation step of RFC 9381 section 5.3.
+pub fn is_valid_public_key(public_key: &[u8; 32]) -> bool {
+    let point = PodEdwardsPoint(*public_key);
+    validate_edwards(&point)
+        && multiply_edwards(&COFACTOR, &point).is_some_and(|cleared| cleared != IDENTITY)
+}
+
+/// Verifies `proof` for `alpha` under `public_key` and returns the VRF
+/// output, or `None` if the proof doesn't check out.
+pub fn verify(public_key: &[u8; 32], alpha: &[u8], proof: &[u8; PROOF_LEN]) -> Option<[u8; OUTPUT_LEN]> {
+    let y = PodEdwardsPoint(*public_key);
+    let gamma = PodEdwardsPoint(proof[..32].try_into().unwrap());
+    let c: [u8; 16] = proof[32..48].try_into().unwrap();
+    let s = PodScalar(proof[48..].try_into().unwrap());
+    if !validate_edwards(&gamma) || !is_canonical(&s.0) {
+        return None;
+    }
+
+    let h = hash_to_curve(public_key, alpha)?;
+    // U = s*B - c*Y and V = s*H - c*Gamma.
+    let minus_c = negate(&c)?;
+    let u = multiscalar_multiply_edwards(&[s, minus_c], &[BASEPOINT, y])?;
+    let v = multiscalar_multiply_edwards(&[s, minus_c], &[PodEdwardsPoint(h), gamma])?;
+
+    if challenge(&[public_key, &h, &gamma.0, &u.0, &v.0]) != c {
+        return None;
+    }
+    proof_to_hash(&gamma.0)
+}
+
+/// `encode_to_curve` by try-and-increment (RFC 9381 section 5.4.1.1).
+pub fn hash_to_curve(public_key: &[u8; 32], alpha: &[u8]) -> Option<[u8; 32]> {
+    for counter in 0..=u8::MAX {
+        let hash = Sha512::new()
+            .chain_update([SUITE, 0x01])
+            .chain_update(public_key)
+            .chain_update(alpha)
+            .chain_update([counter, 0x00])
+            .finalize();
+        let candidate = PodEdwardsPoint(hash[..32].try_into().unwrap());
+        if validate_edwards(&candidate) {
+            return multiply_edwards(&COFACTOR, &candidate).map(|point| point.0);
+        }
+    }
+    None
+}
+
+/// The 16-byte challenge over `[Y, H, Gamma, U, V]` (RFC 9381 section 5.4.3).
+pub fn challenge(points: &[&[u8; 32]; 5]) -> [u8; 16] {
+    let mut hasher = Sha512::new();
+    hasher.update([SUITE, 0x02]);
+    for point in points {
+        hasher.update(point);
+    }
+    hasher.update([0x00]);
+    hasher.finalize()[..16].try_into().unwrap()
+}
+
+/// The VRF output for a proof's Gamma (RFC 9381 section 5.2).
+pub fn proof_to_hash(gamma: &[u8; 32]) -> Option<[u8; OUTPUT_LEN]> {
+    let cleared = multiply_edwards(&COFACTOR, &PodEdwardsPoint(*gamma))?;
+    Some(
+        Sha512::new()
+            .chain_update([SUITE, 0x03])
+            .chain_update(cleared.0)
+            .chain_update([0x00])
+            .finalize()
+            .into(),
+    )
+}
+
+/// Whether little-endian `bytes` is below `L`.
+fn is_canonical(bytes: &[u8; 32]) -> bool {
+    for (byte, limit) in bytes.iter().zip(L.iter()).rev() {
+        if byte != limit {
+            return byte < limit;
+        }
+    }
+    false
+}
+
+/// `L - c` for a nonzero 128-bit challenge `c`.
+fn negate(c: &[u8; 16]) -> Option<PodScalar> {
+    if c.iter().all(|byte| *byte == 0) {
+        return None;
+    }
+    let mut result = [0u8; 32];
+    let mut borrow = 0i16;
+    for (index, limit) in L.iter().enumerate() {
+        let subtrahend = c.get(index).copied().unwrap_or(0) as i16;
+        let mut difference = *limit as i16 - subtrahend - borrow;
+        borrow = (difference < 0) as i16;
+        if difference < 0 {
+            difference += 256;
+        }
+        result[index] = difference as u8;
+    }
+    Some(PodScalar(result))
+}
@@ -1,6 +1,7 @@
 import * as anchor from '@coral-xyz/anchor';
 import { PublicKey, SystemProgram, LAMPORTS_PER_SOL, Keypair } from '@solana/web3.js';
 import { Program } from '@coral-xyz/anchor';
+import { randomBytes } from 'crypto';
 import { Catflip } from '../target/types/catflip';
 
 async function main() {
@@ -68,7 +69,11 @@ async function main() {
     const mockSwitchboard = new PublicKey('SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f');
 
     const tx = await program.methods
-      .bet(new anchor.BN(betAmountLamports))
+      .bet(new anchor.BN(betAmountLamports), Array.from(randomBytes(32)), {
+        minPayout: null,
+        maxEdgeBps: null,
+        lastValidSlot: null,
+      })
       .accounts({
         player: player.publicKey,
         vault: vaultPDA,
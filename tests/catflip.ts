@@ -1,5 +1,6 @@
 import * as anchor from '@coral-xyz/anchor';
 import { Program } from '@coral-xyz/anchor';
+import { randomBytes } from 'crypto';
 import { Catflip } from '../target/types/catflip';
 import { PublicKey, SystemProgram, Keypair, LAMPORTS_PER_SOL } from '@solana/web3.js';
 import { expect } from 'chai';
@@ -132,7 +133,11 @@ describe('catflip', () => {
 
       try {
         const tx = await program.methods
-          .bet(new anchor.BN(betAmount))
+          .bet(new anchor.BN(betAmount), Array.from(randomBytes(32)), {
+            minPayout: null,
+            maxEdgeBps: null,
+            lastValidSlot: null,
+          })
           .accounts({
             player: player.publicKey,
             vault: vaultPDA,
@@ -208,7 +213,11 @@ describe('catflip', () => {
 
       try {
         await program.methods
-          .bet(new anchor.BN(betAmount))
+          .bet(new anchor.BN(betAmount), Array.from(randomBytes(32)), {
+            minPayout: null,
+            maxEdgeBps: null,
+            lastValidSlot: null,
+          })
           .accounts(mockAccounts)
           .signers([player])
           .rpc();